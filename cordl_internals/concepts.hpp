@@ -21,6 +21,29 @@ namespace cordl_internals {
         {t.convert()} -> convertible_to<void*>;
     };
 
+    /// @brief whether T implements the interface I, interfaces aren't bases so this checks the conversion operator
+    /// @tparam T the type to check, reference types are queried as T*
+    /// @tparam I the interface type
+    template <typename T, typename I>
+    concept implements_interface = std::is_convertible_v<T, I*> || std::is_convertible_v<std::remove_pointer_t<T>&, I*>;
+
+#pragma region value type trait
+    /// @brief whether T is a generated value type, reference types are queried as T*
+    /// generated value types specialize this through CORDL_MARK_VAL_T or CORDL_MARK_GEN_VAL_T
+    /// @tparam T the type to check
+    template <typename T>
+    struct is_value_type : std::false_type {};
+
+    /// @brief shorthand to is_value_type<T>::value
+    /// @tparam T the type to check
+    template <typename T>
+    constexpr bool is_value_type_v = is_value_type<T>::value;
+
+    // written next to the bs hook MARK_VAL_T and MARK_GEN_VAL_T for every generated value type
+    #define CORDL_MARK_VAL_T(...) template<> struct ::cordl_internals::is_value_type<__VA_ARGS__> : std::true_type {}
+    #define CORDL_MARK_GEN_VAL_T(...) template<typename... TArgs> struct ::cordl_internals::is_value_type<__VA_ARGS__<TArgs...>> : std::true_type {}
+#pragma endregion // value type trait
+
 #pragma region offset check
     /// @brief struct to check validity of an offset, since the requires clause makes it so only valid structs for this exist, we get nice errors
     /// @tparam instance_sz the size of the instance
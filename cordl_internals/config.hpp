@@ -9,23 +9,10 @@
 #define CORDL_HIDDEN __attribute__((visibility("hidden")))
 #endif
 
-#if defined(__cpp_modules) && CORDL_COMPILE_MODULES
-#define CORDL_MODULE_EXPORT_STRUCT export
-#define CORDL_MODULE_EXPORT(m) export module m;
-#define CORDL_MODULE_INIT module;
-
-#else
-
-#define CORDL_MODULE_EXPORT_STRUCT
-#define CORDL_MODULE_EXPORT(m)
-#define CORDL_MODULE_INIT
-
-#endif
-
 
 #ifndef CORDL_METHOD
 // attributes for methods
-#define CORDL_METHOD CORDL_HIDDEN CORDL_ALWAYS_INLINE CORDL_MODULE_EXPORT_STRUCT
+#define CORDL_METHOD CORDL_HIDDEN CORDL_ALWAYS_INLINE
 #endif
 
 #ifndef CORDL_TYPE
@@ -4,6 +4,7 @@
 #include "size-utils.hpp"
 #include "ptr-utils.hpp"
 #include "method-utils.hpp"
+#include "enumerator-utils.hpp"
 #include "field-utils.hpp"
 #include "unity-utils.hpp"
 
@@ -0,0 +1,60 @@
+#pragma once
+
+#include "config.hpp"
+#include <cstddef>
+#include <iterator>
+#include <type_traits>
+#include <utility>
+
+namespace {
+namespace cordl_internals {
+
+/// @brief input iterator over a C# enumerator, returned by the generated begin()
+/// value type enumerators (List<T>.Enumerator, Span<T>.Enumerator, ...) are held by value
+/// and MoveNext/get_Current are called on them directly, so iterating never boxes them.
+/// reference type enumerators are held as returned by GetEnumerator
+template <typename TEnumerator> class EnumeratorIterator {
+  static constexpr bool is_pointer = std::is_pointer_v<TEnumerator>;
+
+  CORDL_HIDDEN static auto& target(TEnumerator& enumerator) {
+    if constexpr (is_pointer) {
+      return *enumerator;
+    } else {
+      return enumerator;
+    }
+  }
+
+public:
+  using value_type = std::remove_cvref_t<decltype(std::declval<std::remove_pointer_t<TEnumerator>&>().get_Current())>;
+  using difference_type = std::ptrdiff_t;
+
+  /// @brief C# enumerators start before the first element, so this moves to it
+  CORDL_HIDDEN explicit EnumeratorIterator(TEnumerator enumerator) : enumerator(std::move(enumerator)) {
+    ++*this;
+  }
+
+  CORDL_HIDDEN value_type operator*() const {
+    return target(enumerator).get_Current();
+  }
+
+  CORDL_HIDDEN EnumeratorIterator& operator++() {
+    done = !target(enumerator).MoveNext();
+    return *this;
+  }
+
+  CORDL_HIDDEN void operator++(int) {
+    ++*this;
+  }
+
+  CORDL_HIDDEN bool operator==(std::default_sentinel_t) const noexcept {
+    return done;
+  }
+
+private:
+  // MoveNext and get_Current aren't const, even for the value type enumerator itself
+  mutable TEnumerator enumerator;
+  bool done = false;
+};
+
+} // namespace cordl_internals
+} // namespace
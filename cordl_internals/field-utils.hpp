@@ -9,10 +9,6 @@
 #include <bit>
 #include <cstddef>
 
-namespace UnityEngine {
-  class Object;
-}
-
 namespace {
 namespace cordl_internals {
 
@@ -1,15 +1,13 @@
 #pragma once
 
 #include "config.hpp"
+#include "unity-object.hpp"
 #include <array>
 #include <cstddef>
 #include <cstring>
+#include <limits>
 #include <string_view>
 
-namespace UnityEngine {
-    class Object;
-}
-
 namespace {
 namespace cordl_internals {
     namespace internal {
@@ -38,9 +36,9 @@ namespace cordl_internals {
 
     /// @brief reads the cachedptr on the given unity object instance
     template<typename T>
-    requires(std::is_convertible_v<T, UnityEngine::Object*>)
+    requires(std::is_convertible_v<T, CORDL_UNITY_OBJECT*>)
     CORDL_HIDDEN inline constexpr void* read_cachedptr(T instance) {
-        return *static_cast<void**>(getAtOffset<0x10>(static_cast<UnityEngine::Object*>(instance)));
+        return *static_cast<void**>(getAtOffset<0x10>(static_cast<CORDL_UNITY_OBJECT*>(instance)));
     }
 
     // if you compile with the define RUNTIME_FIELD_NULL_CHECKS at runtime every field access will be null checked for you, and a c++ exception will be thrown if the instance is null.
@@ -8,10 +8,7 @@
 #include <sstream>
 #include "il2cpp-tabledefs.h"
 #include "beatsaber-hook/shared/utils/il2cpp-utils-methods.hpp"
-
-namespace UnityEngine {
-class Object;
-}
+#include "beatsaber-hook/shared/utils/utils-functions.h"
 
 namespace {
 namespace cordl_internals {
@@ -39,8 +36,8 @@ CORDL_HIDDEN TOut RunMethodRethrow(T&& instance, MethodInfo const* method, TArgs
       }
 
 #ifndef ALLOW_INVALID_UNITY_METHOD_CALLS
-      if constexpr (std::is_convertible_v<T, UnityEngine::Object*>) {
-        if (!::cordl_internals::read_cachedptr(static_cast<UnityEngine::Object*>(inst))) {
+      if constexpr (std::is_convertible_v<T, CORDL_UNITY_OBJECT*>) {
+        if (!::cordl_internals::read_cachedptr(static_cast<CORDL_UNITY_OBJECT*>(inst))) {
           // if cached ptr evaluates as false, we are dealing with an invalid unity instance, and the instance method call is a bad idea
           std::stringstream str;
           // FIXME: should we use this string, or something else? log a stacktrace?
@@ -68,5 +65,12 @@ CORDL_HIDDEN TOut RunMethodRethrow(T&& instance, MethodInfo const* method, TArgs
 
   return ::il2cpp_utils::RunMethodRethrow<TOut, checkTypes>(std::forward<T>(instance), method, std::forward<TArgs>(params)...);
 }
+
+/// @brief Function pointer of type TFn to the method at addr in libil2cpp, resolved once
+template <typename TFn, std::size_t addr>
+CORDL_HIDDEN TFn MethodPointer() {
+  static auto ptr = reinterpret_cast<TFn>(::getRealOffset(reinterpret_cast<void const*>(addr)));
+  return ptr;
+}
 } // namespace cordl_internals
 } // end anonymous namespace
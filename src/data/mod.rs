@@ -1,2 +1,4 @@
 pub mod name_components;
+pub mod offset_overrides;
 pub mod type_resolver;
+pub mod xml_docs;
@@ -14,11 +14,21 @@ impl NameComponents {
 
         // add declaring types
         if let Some(declaring_types) = self.declaring_types.as_ref() {
-            completed = format!("{}/{completed}", declaring_types.join("/"));
+            let declaring_types = declaring_types
+                .iter()
+                .filter(|d| !d.is_empty())
+                .map(|d| d.as_str())
+                .collect::<Vec<_>>();
+
+            if !declaring_types.is_empty() {
+                completed = format!("{}/{completed}", declaring_types.join("/"));
+            }
         }
 
-        // add namespace
-        if let Some(namespace) = self.namespace.as_ref() {
+        // add namespace, skipping empty namespaces so we don't produce a leading `.`
+        if let Some(namespace) = self.namespace.as_ref()
+            && !namespace.is_empty()
+        {
             completed = format!("{namespace}.{completed}");
         }
 
@@ -73,3 +83,46 @@ impl From<String> for NameComponents {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::NameComponents;
+
+    #[test]
+    fn combine_all_full_name() {
+        let name = NameComponents {
+            namespace: Some("System.Collections.Generic".to_string()),
+            declaring_types: Some(vec!["Dictionary`2".to_string()]),
+            name: "Enumerator".to_string(),
+            generics: Some(vec!["TKey".to_string(), "TValue".to_string()]),
+        };
+
+        assert_eq!(
+            name.combine_all(),
+            "System.Collections.Generic.Dictionary`2/Enumerator<TKey,TValue>"
+        );
+    }
+
+    #[test]
+    fn combine_all_skips_empty_namespace() {
+        let name = NameComponents {
+            namespace: Some(String::new()),
+            name: "Foo".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(name.combine_all(), "Foo");
+    }
+
+    #[test]
+    fn combine_all_skips_empty_declaring_types() {
+        let name = NameComponents {
+            namespace: Some("Bar".to_string()),
+            declaring_types: Some(vec![String::new(), "Outer".to_string()]),
+            name: "Inner".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(name.combine_all(), "Bar.Outer/Inner");
+    }
+}
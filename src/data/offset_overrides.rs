@@ -0,0 +1,60 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use color_eyre::eyre::{bail, Context};
+
+/// Field offsets replacing the ones computed from metadata,
+/// for game versions where the metadata layout is known to be wrong
+///
+/// One override per line as `TYPE FIELD OFFSET`, e.g. `UnityEngine.Vector3 x 0x0`
+/// Offsets are as emitted, so value type offsets don't include the object header.
+/// Empty lines and lines starting with `#` are ignored
+#[derive(Debug, Default)]
+pub struct OffsetOverrides {
+    /// Full type name -> field name -> offset
+    offsets: HashMap<String, HashMap<String, u32>>,
+}
+
+impl OffsetOverrides {
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("offset overrides not found {}", path.display()))?;
+
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> color_eyre::Result<Self> {
+        let mut offsets: HashMap<String, HashMap<String, u32>> = HashMap::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let [type_name, field_name, offset] = line.split_whitespace().collect::<Vec<_>>()[..]
+            else {
+                bail!(
+                    "Expected `TYPE FIELD OFFSET` on line {}: {line}",
+                    line_number + 1
+                );
+            };
+
+            let offset = match offset.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => offset.parse(),
+            }
+            .with_context(|| format!("Invalid offset on line {}: {offset}", line_number + 1))?;
+
+            offsets
+                .entry(type_name.to_string())
+                .or_default()
+                .insert(field_name.to_string(), offset);
+        }
+
+        Ok(Self { offsets })
+    }
+
+    pub fn get(&self, type_name: &str, field_name: &str) -> Option<u32> {
+        self.offsets.get(type_name)?.get(field_name).copied()
+    }
+}
@@ -1,15 +1,18 @@
 use brocolib::{
-    global_metadata::{GenericParameterIndex, MethodIndex},
+    global_metadata::{GenericParameterIndex, MethodIndex, TypeDefinitionIndex},
     runtime_metadata::{Il2CppType, Il2CppTypeEnum, TypeData},
 };
 
+use std::cell::RefCell;
+
 use itertools::Itertools;
 use log::warn;
 use serde::{Deserialize, Serialize};
 
 use crate::generate::{
     cs_context_collection::TypeContextCollection, cs_type::CsType, cs_type_tag::CsTypeTag,
-    metadata::CordlMetadata, type_extensions::ParameterDefinitionExtensions,
+    diagnostics::DiagnosticKind, metadata::CordlMetadata,
+    type_extensions::ParameterDefinitionExtensions,
 };
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
@@ -50,6 +53,9 @@ pub struct ResolvedType {
 pub struct TypeResolver<'a, 'b> {
     pub cordl_metadata: &'a CordlMetadata<'b>,
     pub collection: &'a TypeContextCollection,
+    /// Generic class index and argument position currently being resolved,
+    /// guards against F-bounded recursion
+    pub resolving_generic_classes: RefCell<Vec<(usize, usize)>>,
 }
 
 impl TypeResolver<'_, '_> {
@@ -113,12 +119,18 @@ impl TypeResolver<'_, '_> {
                     ));
                  ResolvedTypeData::Primitive(to_resolve.ty)
             }
+            // native int/uint (IntPtr/UIntPtr)
+            // only use the class if it is not IntPtr/UIntPtr itself
+            Il2CppTypeEnum::I | Il2CppTypeEnum::U => match typ_tag {
+                TypeData::TypeDefinitionIndex(tdi) if !self.is_native_int_tdi(tdi) => {
+                    self.resolve_ptr(typ_tag, declaring_cs_type, to_resolve, typ_usage, add_include)
+                }
+                _ => ResolvedTypeData::Primitive(to_resolve.ty),
+            },
             Il2CppTypeEnum::Class
             | Il2CppTypeEnum::Valuetype
-            | Il2CppTypeEnum::Typedbyref
             // ptr types
-            | Il2CppTypeEnum::I
-            | Il2CppTypeEnum::U => self.resolve_ptr(typ_tag, declaring_cs_type, to_resolve, add_include),
+            | Il2CppTypeEnum::Typedbyref => self.resolve_ptr(typ_tag, declaring_cs_type, to_resolve, typ_usage, add_include),
 
             // Single dimension array
             Il2CppTypeEnum::Szarray => {
@@ -133,7 +145,7 @@ impl TypeResolver<'_, '_> {
                         )
                     }
 
-                    _ => panic!("Unknown type data for array {to_resolve:?}!"),
+                    _ => return self.unimplemented_type(declaring_cs_type, format!("array type data {to_resolve:?}")),
                 };
 
                 ResolvedTypeData::Array(Box::new(generic))
@@ -142,6 +154,11 @@ impl TypeResolver<'_, '_> {
             Il2CppTypeEnum::Array => {
                 // FIXME: when stack further implements the TypeData::ArrayType we can actually implement this fully to be a multidimensional array, whatever that might mean
                 warn!("Multidimensional array was requested but this is not implemented, typ: {to_resolve:?}, instead returning Il2CppObject!");
+                metadata.diagnostics.record(
+                    DiagnosticKind::MultidimArray,
+                    &declaring_cs_type.cs_name_components.combine_all(),
+                    format!("{to_resolve:?}"),
+                );
                 ResolvedTypeData::Primitive(Il2CppTypeEnum::Object)
             }
             //
@@ -150,20 +167,17 @@ impl TypeResolver<'_, '_> {
                     let generic_param: &brocolib::global_metadata::Il2CppGenericParameter =
                         &metadata.metadata.global_metadata.generic_parameters[index];
 
+                    // resolved from the method's own generic container,
+                    // its positions restart at 0 and can collide with the declaring type's generics
                     let owner = generic_param.owner(metadata.metadata);
-                    assert!(owner.is_method != u32::MAX);
-
-                    let (_gen_idx, gen_param) = owner
-                        .generic_parameters(metadata.metadata)
-                        .iter()
-                        .find_position(|&p| p.name_index == generic_param.name_index)
-                        .unwrap();
+                    assert!(owner.is_method != 0);
 
-                                            let method_index = MethodIndex::new(owner.owner_index);
+                    let method_index = MethodIndex::new(owner.owner_index);
+                    let position = generic_position(owner.generic_parameter_start, index);
 
-                        ResolvedTypeData::GenericMethodArg(method_index, index, gen_param.num)
+                    ResolvedTypeData::GenericMethodArg(method_index, index, position)
                 }
-                _ => todo!(),
+                _ => self.unimplemented_type(declaring_cs_type, format!("method generic type data {to_resolve:?}")),
             },
             Il2CppTypeEnum::Var => match to_resolve.data {
                 // Il2CppMetadataGenericParameterHandle
@@ -175,7 +189,7 @@ impl TypeResolver<'_, '_> {
 
                     ResolvedTypeData::GenericArg(index, generic_param.num)
                 }
-                _ => todo!(),
+                _ => self.unimplemented_type(declaring_cs_type, format!("generic type data {to_resolve:?}")),
             },
             Il2CppTypeEnum::Genericinst => match to_resolve.data {
                 TypeData::GenericClassIndex(e) => {
@@ -193,29 +207,52 @@ impl TypeResolver<'_, '_> {
                         panic!()
                     };
 
+                    // always depend on the open definition, even if only forward declared
+                    // references like `SomeGeneric<T>` are never instantiated, so nothing else keeps it
+                    declaring_cs_type.requirements.add_dependency_tag(tdi.into());
+
                     if add_include {
                         let generic_tag = CsTypeTag::from_type_data(to_resolve.data, metadata.metadata);
 
-                        // depend on both tdi and generic instantiation
-                        declaring_cs_type.requirements.add_dependency_tag(tdi.into());
+                        // depend on the generic instantiation too
                         declaring_cs_type.requirements.add_dependency_tag(generic_tag);
                     }
 
                     let generic_resolved_args = new_generic_inst_types
                         // let generic_types_formatted = new_generic_inst_types
                         .iter()
-                        .map(|gen_arg_t_idx| {
+                        .enumerate()
+                        .map(|(arg_pos, gen_arg_t_idx)| {
                             let gen_arg_ty = mr.types.get(*gen_arg_t_idx).unwrap();
                             // we must include if the type is a value type
                             let should_include = gen_arg_ty.valuetype && add_include;
 
-                            let t =self.resolve_type(
-                                declaring_cs_type,
-                                *gen_arg_t_idx,
-                                TypeUsage::GenericArg,
-                                should_include
-                            );
-                            (t, should_include)
+                            // F-bounded generics (`Enum<TSelf> where TSelf : Enum<TSelf>`) can lead back
+                            // to the argument being resolved, erase it instead of expanding it again.
+                            // the template parameter isn't in scope wherever the type is named
+                            let resolved = with_recursion_guard(&self.resolving_generic_classes, (e, arg_pos), || {
+                                self.resolve_type(
+                                    declaring_cs_type,
+                                    *gen_arg_t_idx,
+                                    TypeUsage::GenericArg,
+                                    should_include
+                                )
+                            });
+
+                            match resolved {
+                                Some(t) => (t, should_include),
+                                None => {
+                                    warn!(
+                                        "Recursive generic argument {arg_pos} of {}, erasing it to System.Object",
+                                        generic_type_def.full_name(metadata.metadata)
+                                    );
+                                    let t = ResolvedType {
+                                        data: ResolvedTypeData::Primitive(Il2CppTypeEnum::Object),
+                                        ty: *gen_arg_t_idx,
+                                    };
+                                    (t, false)
+                                }
+                            }
                         })
                         .collect_vec();
 
@@ -231,7 +268,7 @@ impl TypeResolver<'_, '_> {
                     ResolvedTypeData::GenericInst(Box::new(generic_resolved_type), generic_resolved_args)
                 }
 
-                _ => panic!("Unknown type data for generic inst {to_resolve:?}!"),
+                _ => self.unimplemented_type(declaring_cs_type, format!("generic inst type data {to_resolve:?}")),
             },
 
 
@@ -246,12 +283,28 @@ impl TypeResolver<'_, '_> {
                         )
                     }
 
-                    _ => panic!("Unknown type data for array {to_resolve:?}!"),
+                    _ => return self.unimplemented_type(declaring_cs_type, format!("pointer type data {to_resolve:?}")),
                 };
 
                 ResolvedTypeData::Ptr(Box::new(ptr_type))
             }
-            _ => panic!("/* UNKNOWN TYPE! {to_resolve:?} */"),
+            // pinning only keeps a local from being moved by the GC, the pinned type is used as is
+            Il2CppTypeEnum::Pinned => match to_resolve.data {
+                TypeData::TypeIndex(e) => {
+                    self.resolve_type(
+                        declaring_cs_type,
+                        e,
+                        typ_usage,
+                        add_include
+                    ).data
+                }
+
+                _ => self.unimplemented_type(declaring_cs_type, format!("pinned type data {to_resolve:?}")),
+            },
+            // marks where the variadic arguments of a vararg signature start, it isn't a type itself
+            // parameters stop before it, see `CsType::make_parameters`
+            Il2CppTypeEnum::Sentinel => ResolvedTypeData::Primitive(Il2CppTypeEnum::Object),
+            _ => self.unimplemented_type(declaring_cs_type, format!("type {to_resolve:?}")),
         };
 
         let byref_allowed = matches!(
@@ -281,11 +334,32 @@ impl TypeResolver<'_, '_> {
         ret
     }
 
+    /// `System.Object` in place of a type that can't be resolved yet, see `CordlMetadata::unimplemented_fallback`
+    fn unimplemented_type(&self, declaring_cs_type: &mut CsType, context: String) -> ResolvedTypeData {
+        let comment = self.cordl_metadata.unimplemented_fallback_as(
+            DiagnosticKind::UnresolvedType,
+            &declaring_cs_type.cs_name_components.combine_all(),
+            &context,
+        );
+        declaring_cs_type.placeholder_comments.push(comment);
+
+        ResolvedTypeData::Primitive(Il2CppTypeEnum::Object)
+    }
+
+    /// Whether the type definition is `System.IntPtr` or `System.UIntPtr`
+    fn is_native_int_tdi(&self, tdi: TypeDefinitionIndex) -> bool {
+        let metadata = self.cordl_metadata.metadata;
+        let td = &metadata.global_metadata.type_definitions[tdi];
+
+        td.namespace(metadata) == "System" && matches!(td.name(metadata), "IntPtr" | "UIntPtr")
+    }
+
     fn resolve_ptr(
         &self,
         typ_tag: TypeData,
         declaring_cs_type: &mut CsType,
         to_resolve: &Il2CppType,
+        typ_usage: TypeUsage,
         add_include: bool,
     ) -> ResolvedTypeData {
         let metadata = self.cordl_metadata;
@@ -299,7 +373,22 @@ impl TypeResolver<'_, '_> {
         if let TypeData::TypeDefinitionIndex(tdi) = to_resolve.data
             && metadata.blacklisted_types.contains(&tdi)
         {
+            // blacklisted enums have no definition to name, so `List<MyEnum>` uses the
+            // element type backing the enum, which has its exact layout, instead of the enum wrapper
+            if typ_usage == TypeUsage::GenericArg
+                && let Some(backing_idx) = CsType::enum_backing_type_index(metadata, tdi)
+            {
+                let backing_ty = &metadata.metadata_registration.types[backing_idx];
+                return ResolvedTypeData::Primitive(backing_ty.ty);
+            }
+
             // blacklist if needed
+            metadata.diagnostics.record(
+                DiagnosticKind::BlacklistedReference,
+                &declaring_cs_type.cs_name_components.combine_all(),
+                metadata.metadata.global_metadata.type_definitions[tdi]
+                    .full_name(metadata.metadata, true),
+            );
 
             return ResolvedTypeData::Blacklisted(typ_cpp_tag);
         }
@@ -318,8 +407,228 @@ impl TypeResolver<'_, '_> {
     }
 }
 
+/// Position of `index` within the generic container starting at `generic_parameter_start`,
+/// i.e. which argument of an instantiation it is substituted with
+fn generic_position(
+    generic_parameter_start: GenericParameterIndex,
+    index: GenericParameterIndex,
+) -> u16 {
+    (index.index() - generic_parameter_start.index()) as u16
+}
+
+/// Runs `resolve` with `key` on the `stack`,
+/// or returns `None` if `key` is already on it and resolving it again would never end
+fn with_recursion_guard<K: PartialEq, T>(
+    stack: &RefCell<Vec<K>>,
+    key: K,
+    resolve: impl FnOnce() -> T,
+) -> Option<T> {
+    if stack.borrow().contains(&key) {
+        return None;
+    }
+
+    stack.borrow_mut().push(key);
+    let resolved = resolve();
+    stack.borrow_mut().pop();
+
+    Some(resolved)
+}
+
 impl ResolvedType {
     pub fn get_type<'a>(&self, metadata: &CordlMetadata<'a>) -> &'a Il2CppType {
         &metadata.metadata_registration.types[self.ty]
     }
+
+    /// Replaces method generic parameters with the arguments of a generic method instantiation,
+    /// `ty` keeps pointing at the open type
+    pub fn substitute_method_generics(&self, args: &[ResolvedType]) -> ResolvedType {
+        let substitute = |t: &ResolvedType| Box::new(t.substitute_method_generics(args));
+
+        let data = match &self.data {
+            ResolvedTypeData::GenericMethodArg(_, _, num) => match args.get(*num as usize) {
+                Some(arg) => return arg.clone(),
+                None => self.data.clone(),
+            },
+            ResolvedTypeData::Array(t) => ResolvedTypeData::Array(substitute(t)),
+            ResolvedTypeData::GenericInst(t, generic_args) => ResolvedTypeData::GenericInst(
+                substitute(t),
+                generic_args
+                    .iter()
+                    .map(|(arg, is_ref)| (arg.substitute_method_generics(args), *is_ref))
+                    .collect(),
+            ),
+            ResolvedTypeData::Ptr(t) => ResolvedTypeData::Ptr(substitute(t)),
+            ResolvedTypeData::ByRef(t) => ResolvedTypeData::ByRef(substitute(t)),
+            ResolvedTypeData::ByRefConst(t) => ResolvedTypeData::ByRefConst(substitute(t)),
+            ResolvedTypeData::GenericArg(..)
+            | ResolvedTypeData::Type(_)
+            | ResolvedTypeData::Primitive(_)
+            | ResolvedTypeData::Blacklisted(_) => self.data.clone(),
+        };
+
+        ResolvedType { data, ty: self.ty }
+    }
+
+    /// Replaces class generic parameters with the arguments of a generic class instantiation,
+    /// `ty` keeps pointing at the open type
+    pub fn substitute_class_generics(&self, args: &[ResolvedType]) -> ResolvedType {
+        let substitute = |t: &ResolvedType| Box::new(t.substitute_class_generics(args));
+
+        let data = match &self.data {
+            ResolvedTypeData::GenericArg(_, num) => match args.get(*num as usize) {
+                Some(arg) => return arg.clone(),
+                None => self.data.clone(),
+            },
+            ResolvedTypeData::Array(t) => ResolvedTypeData::Array(substitute(t)),
+            ResolvedTypeData::GenericInst(t, generic_args) => ResolvedTypeData::GenericInst(
+                substitute(t),
+                generic_args
+                    .iter()
+                    .map(|(arg, is_ref)| (arg.substitute_class_generics(args), *is_ref))
+                    .collect(),
+            ),
+            ResolvedTypeData::Ptr(t) => ResolvedTypeData::Ptr(substitute(t)),
+            ResolvedTypeData::ByRef(t) => ResolvedTypeData::ByRef(substitute(t)),
+            ResolvedTypeData::ByRefConst(t) => ResolvedTypeData::ByRefConst(substitute(t)),
+            ResolvedTypeData::GenericMethodArg(..)
+            | ResolvedTypeData::Type(_)
+            | ResolvedTypeData::Primitive(_)
+            | ResolvedTypeData::Blacklisted(_) => self.data.clone(),
+        };
+
+        ResolvedType { data, ty: self.ty }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brocolib::{
+        global_metadata::{GenericParameterIndex, MethodIndex, TypeDefinitionIndex},
+        runtime_metadata::Il2CppTypeEnum,
+    };
+
+    use std::cell::RefCell;
+
+    use super::{generic_position, with_recursion_guard, ResolvedType, ResolvedTypeData};
+
+    fn generic_arg(position: u16) -> ResolvedType {
+        ResolvedType {
+            data: ResolvedTypeData::GenericArg(GenericParameterIndex::new(0), position),
+            ty: 0,
+        }
+    }
+
+    fn primitive(ty: Il2CppTypeEnum) -> ResolvedType {
+        ResolvedType {
+            data: ResolvedTypeData::Primitive(ty),
+            ty: 1,
+        }
+    }
+
+    /// `ICollection<T>` as extended by an `IList<T>` instantiation
+    fn base_interface(args: Vec<(ResolvedType, bool)>) -> ResolvedType {
+        let open = ResolvedType {
+            data: ResolvedTypeData::Type(TypeDefinitionIndex::new(0).into()),
+            ty: 2,
+        };
+
+        ResolvedType {
+            data: ResolvedTypeData::GenericInst(Box::new(open), args),
+            ty: 3,
+        }
+    }
+
+    #[test]
+    fn base_interface_takes_instantiation_args() {
+        let int = primitive(Il2CppTypeEnum::I4);
+        let substituted = base_interface(vec![(generic_arg(0), false)])
+            .substitute_class_generics(std::slice::from_ref(&int));
+
+        assert_eq!(substituted, base_interface(vec![(int, false)]));
+    }
+
+    #[test]
+    fn unmatched_generic_arg_is_kept() {
+        let int = primitive(Il2CppTypeEnum::I4);
+        let substituted = generic_arg(1).substitute_class_generics(&[int]);
+
+        assert_eq!(substituted, generic_arg(1));
+    }
+
+    /// Resolves `Enum<TSelf>` instantiated with itself as `TSelf`, the way generic inst args are
+    /// resolved, erasing the argument once it is reached again
+    fn resolve_self_referential(stack: &RefCell<Vec<(usize, usize)>>) -> ResolvedType {
+        let arg = with_recursion_guard(stack, (0, 0), || resolve_self_referential(stack))
+            .unwrap_or_else(|| primitive(Il2CppTypeEnum::Object));
+
+        base_interface(vec![(arg, false)])
+    }
+
+    #[test]
+    fn self_referential_generic_is_erased() {
+        let stack = RefCell::default();
+        let resolved = resolve_self_referential(&stack);
+
+        let erased = base_interface(vec![(primitive(Il2CppTypeEnum::Object), false)]);
+        assert_eq!(resolved, base_interface(vec![(erased, false)]));
+        assert!(stack.borrow().is_empty());
+    }
+
+    #[test]
+    fn other_arguments_are_not_guarded() {
+        let stack = RefCell::new(vec![(0, 0)]);
+
+        assert_eq!(with_recursion_guard(&stack, (0, 1), || 1), Some(1));
+        assert_eq!(with_recursion_guard(&stack, (1, 0), || 2), Some(2));
+        assert_eq!(with_recursion_guard(&stack, (0, 0), || 3), None);
+    }
+
+    /// `T Convert<U>(U)` in `Converter<T>`, where both `T` and `U` are at position 0
+    /// of their own generic containers
+    #[test]
+    fn method_generic_shares_position_with_class_generic() {
+        let class_start = GenericParameterIndex::new(3);
+        let method_start = GenericParameterIndex::new(7);
+        let class_t = GenericParameterIndex::new(3);
+        let method_u = GenericParameterIndex::new(7);
+
+        let class_position = generic_position(class_start, class_t);
+        let method_position = generic_position(method_start, method_u);
+        assert_eq!((class_position, method_position), (0, 0));
+
+        let int = primitive(Il2CppTypeEnum::I4);
+        let float = primitive(Il2CppTypeEnum::R4);
+        let class_arg = ResolvedType {
+            data: ResolvedTypeData::GenericArg(class_t, class_position),
+            ty: 4,
+        };
+        let method_arg = ResolvedType {
+            data: ResolvedTypeData::GenericMethodArg(
+                MethodIndex::new(0),
+                method_u,
+                method_position,
+            ),
+            ty: 5,
+        };
+        let signature = base_interface(vec![
+            (class_arg.clone(), false),
+            (method_arg.clone(), false),
+        ]);
+
+        // each instantiation only replaces its own parameters
+        assert_eq!(
+            signature.substitute_method_generics(std::slice::from_ref(&int)),
+            base_interface(vec![(class_arg, false), (int.clone(), false)])
+        );
+        assert_eq!(
+            signature.substitute_class_generics(std::slice::from_ref(&float)),
+            base_interface(vec![(float.clone(), false), (method_arg, false)])
+        );
+        assert_eq!(
+            signature
+                .substitute_class_generics(&[float.clone()])
+                .substitute_method_generics(&[int.clone()]),
+            base_interface(vec![(float, false), (int, false)])
+        );
+    }
 }
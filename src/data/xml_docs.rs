@@ -0,0 +1,217 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use brocolib::{
+    global_metadata::Il2CppTypeDefinition,
+    runtime_metadata::{Il2CppType, Il2CppTypeEnum, TypeData},
+};
+use color_eyre::eyre::Context;
+use itertools::Itertools;
+
+use crate::generate::metadata::CordlMetadata;
+
+/// Summaries parsed from a C# XML documentation file
+/// Keyed by documentation id, e.g. `M:UnityEngine.Transform.Rotate(System.Single,System.Single,System.Single)`
+#[derive(Debug, Default)]
+pub struct XmlDocs {
+    members: HashMap<String, String>,
+    /// `M:Type.Method` -> ids of all its overloads
+    overloads: HashMap<String, Vec<String>>,
+}
+
+impl XmlDocs {
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let xml = fs::read_to_string(path)
+            .with_context(|| format!("xml documentation not found {}", path.display()))?;
+
+        Self::parse(&xml)
+    }
+
+    pub fn parse(xml: &str) -> color_eyre::Result<Self> {
+        let document = roxmltree::Document::parse(xml).context("Invalid xml documentation")?;
+
+        let members: HashMap<String, String> = document
+            .descendants()
+            .filter(|n| n.has_tag_name("member"))
+            .filter_map(|member| {
+                let name = member.attribute("name")?;
+                let summary = member.children().find(|c| c.has_tag_name("summary"))?;
+                let text = summary_text(summary);
+
+                (!text.is_empty()).then(|| (name.to_string(), text))
+            })
+            .collect();
+
+        let overloads = members
+            .keys()
+            .filter_map(|id| {
+                let (method, _params) = id.split_once('(')?;
+                Some((method.to_string(), id.clone()))
+            })
+            .into_group_map();
+
+        Ok(Self { members, overloads })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.members.get(id).map(|s| s.as_str())
+    }
+
+    pub fn type_doc(&self, metadata: &CordlMetadata, td: &Il2CppTypeDefinition) -> Option<&str> {
+        self.get(&format!("T:{}", type_id(metadata, td)))
+    }
+
+    pub fn field_doc(
+        &self,
+        metadata: &CordlMetadata,
+        td: &Il2CppTypeDefinition,
+        field_name: &str,
+    ) -> Option<&str> {
+        self.get(&format!("F:{}.{field_name}", type_id(metadata, td)))
+    }
+
+    /// Matches by full signature, falling back to the method name if it has a single documented overload
+    pub fn method_doc(
+        &self,
+        metadata: &CordlMetadata,
+        td: &Il2CppTypeDefinition,
+        method_name: &str,
+        param_types: &[&Il2CppType],
+    ) -> Option<&str> {
+        // .ctor -> #ctor
+        let method_name = method_name.replace('.', "#");
+        let method_id = format!("M:{}.{method_name}", type_id(metadata, td));
+
+        let params = param_types
+            .iter()
+            .map(|ty| param_type_id(metadata, ty))
+            .collect::<Option<Vec<_>>>();
+
+        let exact = params.and_then(|params| match params.is_empty() {
+            true => self.get(&method_id),
+            false => self.get(&format!("{method_id}({})", params.join(","))),
+        });
+
+        exact.or_else(|| match self.overloads.get(&method_id) {
+            Some(overloads) if overloads.len() == 1 => self.get(&overloads[0]),
+            Some(_) => None,
+            None => self.get(&method_id),
+        })
+    }
+}
+
+/// Flattens a `<summary>` into plain lines, keeping the names of `<see cref>` and `<paramref>` references
+fn summary_text(summary: roxmltree::Node) -> String {
+    let text: String = summary
+        .descendants()
+        .filter_map(|n| {
+            if n.is_text() {
+                return n.text().map(|t| t.to_string());
+            }
+
+            let reference = n.attribute("cref").or(n.attribute("name"))?;
+            let reference = reference
+                .split_once(':')
+                .map(|(_, r)| r)
+                .unwrap_or(reference);
+
+            Some(reference.to_string())
+        })
+        .collect();
+
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .join("\n")
+}
+
+/// Documentation id of a type, nested types are separated by `.`
+fn type_id(metadata: &CordlMetadata, td: &Il2CppTypeDefinition) -> String {
+    td.full_name(metadata.metadata, false)
+        .replace("::", ".")
+        .replace('/', ".")
+}
+
+/// Documentation id of a parameter type, `None` if it can't be represented
+fn param_type_id(metadata: &CordlMetadata, ty: &Il2CppType) -> Option<String> {
+    let id = match ty.ty {
+        Il2CppTypeEnum::Void => "System.Void".to_string(),
+        Il2CppTypeEnum::Boolean => "System.Boolean".to_string(),
+        Il2CppTypeEnum::Char => "System.Char".to_string(),
+        Il2CppTypeEnum::I1 => "System.SByte".to_string(),
+        Il2CppTypeEnum::U1 => "System.Byte".to_string(),
+        Il2CppTypeEnum::I2 => "System.Int16".to_string(),
+        Il2CppTypeEnum::U2 => "System.UInt16".to_string(),
+        Il2CppTypeEnum::I4 => "System.Int32".to_string(),
+        Il2CppTypeEnum::U4 => "System.UInt32".to_string(),
+        Il2CppTypeEnum::I8 => "System.Int64".to_string(),
+        Il2CppTypeEnum::U8 => "System.UInt64".to_string(),
+        Il2CppTypeEnum::R4 => "System.Single".to_string(),
+        Il2CppTypeEnum::R8 => "System.Double".to_string(),
+        Il2CppTypeEnum::String => "System.String".to_string(),
+        Il2CppTypeEnum::Object => "System.Object".to_string(),
+        Il2CppTypeEnum::I => "System.IntPtr".to_string(),
+        Il2CppTypeEnum::U => "System.UIntPtr".to_string(),
+        Il2CppTypeEnum::Typedbyref => "System.TypedReference".to_string(),
+        Il2CppTypeEnum::Class | Il2CppTypeEnum::Valuetype => match ty.data {
+            TypeData::TypeDefinitionIndex(tdi) => type_id(
+                metadata,
+                &metadata.metadata.global_metadata.type_definitions[tdi],
+            ),
+            _ => return None,
+        },
+        Il2CppTypeEnum::Szarray | Il2CppTypeEnum::Ptr => {
+            let TypeData::TypeIndex(element) = ty.data else {
+                return None;
+            };
+            let suffix = match ty.ty {
+                Il2CppTypeEnum::Szarray => "[]",
+                _ => "*",
+            };
+
+            let element_ty = &metadata.metadata_registration.types[element];
+            format!("{}{suffix}", param_type_id(metadata, element_ty)?)
+        }
+        Il2CppTypeEnum::Var | Il2CppTypeEnum::Mvar => {
+            let TypeData::GenericParameterIndex(index) = ty.data else {
+                return None;
+            };
+            let generic_param = &metadata.metadata.global_metadata.generic_parameters[index];
+            let prefix = match ty.ty {
+                Il2CppTypeEnum::Var => "`",
+                _ => "``",
+            };
+
+            format!("{prefix}{}", generic_param.num)
+        }
+        Il2CppTypeEnum::Genericinst => {
+            let TypeData::GenericClassIndex(index) = ty.data else {
+                return None;
+            };
+            let mr = &metadata.metadata_registration;
+            let generic_class = &mr.generic_classes[index];
+            let generic_inst = &mr.generic_insts[generic_class.context.class_inst_idx?];
+
+            // List`1 -> List{System.Int32}
+            let generic_ty = &mr.types[generic_class.type_index];
+            let generic_name = param_type_id(metadata, generic_ty)?;
+            let generic_name = generic_name
+                .split_once('`')
+                .map(|(name, _arity)| name)
+                .unwrap_or(&generic_name);
+
+            let args = generic_inst
+                .types
+                .iter()
+                .map(|arg| param_type_id(metadata, &mr.types[*arg]))
+                .collect::<Option<Vec<_>>>()?;
+
+            format!("{generic_name}{{{}}}", args.join(","))
+        }
+        _ => return None,
+    };
+
+    match ty.byref {
+        true => Some(format!("{id}@")),
+        false => Some(id),
+    }
+}
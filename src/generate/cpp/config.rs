@@ -1,30 +1,246 @@
-use std::{path::PathBuf, sync::LazyLock};
-
-pub static STATIC_CONFIG: LazyLock<CppGenerationConfig> = LazyLock::new(|| CppGenerationConfig {
-    header_path: PathBuf::from("./codegen/include"),
-    source_path: PathBuf::from("./codegen/src"),
-    dst_internals_path: PathBuf::from("./codegen/include/cordl_internals"),
-    dst_header_internals_file: PathBuf::from(
-        "./codegen/include/cordl_internals/cordl_internals.hpp",
-    ),
-    use_anonymous_namespace: false,
-});
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
+use brocolib::runtime_metadata::Il2CppTypeEnum;
+use color_eyre::eyre::bail;
+
+use crate::data::name_components::NameComponents;
+use crate::generate::cs_members::CsMethod;
+use crate::generate::writer::WriterStyle;
+
+use super::cpp_type::CppType;
+
+/// A hand written C++ type used in place of a generated one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CppTypeOverride {
+    /// Fully qualified C++ name, e.g. `::UnityEngine::Vector3`
+    /// Reference types are used as a pointer to it
+    pub cpp_name: String,
+    /// Header declaring `cpp_name`, included as written
+    pub include: PathBuf,
+}
+
+/// How types with an explicit packing are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Default, clap::ValueEnum)]
+pub enum PackingStyle {
+    /// `#pragma pack(push, N)` around the type
+    #[default]
+    Pragma,
+    /// GCC/Clang `__attribute__((packed, aligned(N)))` on the type
+    Attribute,
+}
+
+/// Kinds of types whose definitions are written,
+/// excluded kinds are still forward declared so references to them keep compiling.
+///
+/// Value types embed their value type and enum fields,
+/// so excluding those kinds breaks any value type that still uses them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeKindFilter {
+    pub value_types: bool,
+    pub reference_types: bool,
+    pub enums: bool,
+    pub interfaces: bool,
+}
+
+impl Default for TypeKindFilter {
+    fn default() -> Self {
+        Self {
+            value_types: true,
+            reference_types: true,
+            enums: true,
+            interfaces: true,
+        }
+    }
+}
+
+impl TypeKindFilter {
+    pub fn includes(&self, cpp_type: &CppType) -> bool {
+        if cpp_type.is_enum_type {
+            self.enums
+        } else if cpp_type.is_interface {
+            self.interfaces
+        } else if cpp_type.is_value_type {
+            self.value_types
+        } else {
+            self.reference_types
+        }
+    }
+}
+
+/// Methods left out of generated types to shrink method heavy headers.
+///
+/// Property accessors and operators are always kept so properties keep compiling,
+/// omitted methods are noted in a comment on the type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MethodFilter {
+    /// Skip methods stripped from the binary, i.e. with an estimated size of 0
+    pub skip_stripped: bool,
+    /// Emit at most this many methods per type, in metadata order
+    pub max_per_type: Option<usize>,
+}
+
+impl MethodFilter {
+    pub fn is_stripped(&self, method: &CsMethod) -> bool {
+        self.skip_stripped && method.method_data.estimated_size == Some(0)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct CppGenerationConfig {
     pub source_path: PathBuf,
     pub header_path: PathBuf,
+    pub include_root: PathBuf,
     pub dst_internals_path: PathBuf,
     pub dst_header_internals_file: PathBuf,
     pub use_anonymous_namespace: bool,
+    /// Namespace used for types in the C# global namespace,
+    /// both in C++ code and as their include directory
+    pub global_namespace: String,
+    /// Emit the field-initializing and default constructors for value types.
+    ///
+    /// Any user-declared constructor (even `= default` since C++20) makes a type
+    /// a non-aggregate, so `T{a, b}` and designated initializers stop compiling.
+    /// Disabling this keeps value types aggregates, at the cost of fields
+    /// no longer being zero initialized by `T()` through the generated ctor.
+    /// Enums always keep their constructors.
+    pub value_type_constructors: bool,
+    /// Types referenced through a hand written C++ type instead of being generated,
+    /// keyed by full C# name, e.g. `UnityEngine.Vector3` or `Outer/Nested`
+    pub type_overrides: HashMap<String, CppTypeOverride>,
+    /// Write a `.csv` next to each header listing every field's offset and size,
+    /// for auditing offsets against other tools
+    pub layout_report: bool,
+    /// Pairs of full C# name and old C++ namespace, e.g. `UnityEngine.Vector3` and `OldUnity`.
+    /// Each type gets a `using` declaration in that namespace in `cordl_compat.hpp`,
+    /// so code written against a previous layout keeps compiling
+    pub compat_shims: Vec<(String, String)>,
+    /// Emit a static `{method}_ptr` helper for each non generic method,
+    /// calling it through its function pointer resolved from the method address.
+    /// Skips reflection and virtual dispatch entirely
+    pub method_pointers: bool,
+    /// Emit pointer typed fields as raw `T*` instead of wrapping them in `cordl_internals::Ptr<T>`
+    pub raw_field_pointers: bool,
+    /// Assert that value types made only of primitives, pointers and other such value types
+    /// are trivially copyable and standard layout, so they can be copied out of game memory
+    pub trivially_copyable_asserts: bool,
+    /// Emit `__cordl_field_count` with the number of instance fields in the metadata,
+    /// see `CppType::add_field_count_member`
+    pub field_count_members: bool,
+    /// Indentation and line endings of written headers
+    pub writer_style: WriterStyle,
+    /// See `PackingStyle`
+    pub packing_style: PackingStyle,
+    /// Write `cordl_manifest.txt` so the generated headers can be used as a prebuilt set,
+    /// see `PrebuiltManifest`
+    pub write_manifest: bool,
+    /// Kinds of types to write definitions for, see `TypeKindFilter`
+    pub type_kinds: TypeKindFilter,
+    /// Methods to leave out, see `MethodFilter`
+    pub method_filter: MethodFilter,
+    /// Write each assembly's types under a directory named after it,
+    /// e.g. `Assembly_CSharp/GlobalNamespace/Utils.hpp`.
+    /// Types whose full name is also defined in another assembly are put in a namespace
+    /// named after their assembly too, so neither their headers nor their names collide
+    pub split_assemblies: bool,
+    /// Give reference types a static `__cordl_cached_class()` resolving their class once,
+    /// used by the generated methods instead of looking the class up on every call
+    pub cached_class: bool,
+    /// Prepended to the outermost namespace of every generated type and its include directory,
+    /// e.g. `bs_` gives `::bs_System::Object` in `bs_System/Object.hpp`,
+    /// so the headers can be linked alongside other bindings of the same types
+    pub namespace_prefix: String,
+    /// Write every type into a single `cordl_all.hpp` instead of a header per type,
+    /// see `CppContextCollection::write_amalgamation`
+    pub amalgamate: bool,
+    /// Write closed generic instantiations into a `{name}.generics.hpp` per context
+    /// which includes the open template, instead of next to the template
+    pub split_generic_instantiations: bool,
+    /// clang-format binary run over every written header,
+    /// `clang-format` from `PATH` is used if formatting is enabled without one
+    pub clang_format: Option<PathBuf>,
+    /// Emit only instance fields at their offsets, padded to the exact size,
+    /// as plain mirrors for reading game memory. Methods, properties, constructors,
+    /// operators and accessors are left out and reference fields become `void*`
+    pub layout_only: bool,
+    /// C++ names used for primitives instead of the defaults, keyed by the `Il2CppTypeEnum`
+    /// variant of one of `OVERRIDABLE_PRIMITIVES`, e.g. `R4` -> `float`.
+    /// Applies everywhere the primitive is named, including enum backing types and constants
+    pub primitive_overrides: HashMap<String, String>,
+    /// Also write an experimental C++20 module interface unit `{name}.cppm` next to
+    /// every header, which exports the header's types, see `CppContext::write_module_interface`
+    pub modules: bool,
 }
 
 impl CppGenerationConfig {
+    /// Config with default options writing headers to `{output_dir}/include`
+    /// and sources to `{output_dir}/src`
+    pub fn new(output_dir: &Path) -> Self {
+        let header_path = output_dir.join("include");
+        let dst_internals_path = header_path.join("cordl_internals");
+
+        CppGenerationConfig {
+            source_path: output_dir.join("src"),
+            include_root: header_path.clone(),
+            dst_header_internals_file: dst_internals_path.join("cordl_internals.hpp"),
+            dst_internals_path,
+            header_path,
+            use_anonymous_namespace: false,
+            global_namespace: "GlobalNamespace".to_owned(),
+            value_type_constructors: true,
+            type_overrides: Default::default(),
+            layout_report: false,
+            compat_shims: Default::default(),
+            method_pointers: false,
+            raw_field_pointers: false,
+            trivially_copyable_asserts: false,
+            field_count_members: false,
+            writer_style: Default::default(),
+            packing_style: Default::default(),
+            write_manifest: false,
+            type_kinds: Default::default(),
+            method_filter: Default::default(),
+            split_assemblies: false,
+            cached_class: false,
+            namespace_prefix: Default::default(),
+            amalgamate: false,
+            split_generic_instantiations: true,
+            clang_format: None,
+            layout_only: false,
+            primitive_overrides: Default::default(),
+            modules: false,
+        }
+    }
+
+    /// Rejects option combinations that can't produce working headers.
+    /// Module interface units export types through `::{namespace}::{name}`,
+    /// which an anonymous namespace makes unreachable from other translation units
+    pub fn validate(&self) -> color_eyre::Result<()> {
+        if self.modules && self.use_anonymous_namespace {
+            bail!("modules can't be combined with use_anonymous_namespace");
+        }
+
+        Ok(())
+    }
+
+    pub fn type_override(&self, cs_name_components: &NameComponents) -> Option<&CppTypeOverride> {
+        if self.type_overrides.is_empty() {
+            return None;
+        }
+
+        let full_name = cs_name_components.clone().remove_generics().combine_all();
+        self.type_overrides.get(&full_name)
+    }
+
     pub fn namespace_cpp(&self, string: &str) -> String {
         let final_ns = if string.is_empty() {
-            "GlobalNamespace".to_owned()
+            self.global_namespace.clone()
         } else {
             string.replace(['<', '>', '`', '/'], "_").replace('.', "::")
         };
+        let final_ns = format!("{}{final_ns}", self.namespace_prefix);
 
         match self.use_anonymous_namespace {
             true => format!("::{final_ns}"),
@@ -93,19 +309,104 @@ impl CppGenerationConfig {
                 format!("_cordl_{string}")
             }
 
-            // Coincidentally the same as path_name
-            _ => string.replace(['<', '`', '>', '/', '.', '|', ',', '(', ')', '[', ']', '-'], "_"),
+            _ => self.sanitize_to_cpp_name(string),
         }
     }
     /// for converting C++ names into just a single C++ word
+    /// Characters that can't be in an identifier become `_`, e.g. `<>c__DisplayClass1_0`
+    /// Names that would still be invalid, starting with a digit or empty, are prefixed
     pub fn sanitize_to_cpp_name(&self, string: &str) -> String {
-        // Coincidentally the same as path_name
-        string.replace(['<', '`', '>', '/', '.', ':', '|', ',', '(', ')', '*'], "_")
+        let sanitized: String = string
+            .chars()
+            .map(|c| match c.is_alphanumeric() || c == '_' {
+                true => c,
+                false => '_',
+            })
+            .collect();
+
+        match sanitized.chars().next() {
+            None => "_cordl_empty_name".to_string(),
+            Some(c) if c.is_numeric() => format!("_cordl_{sanitized}"),
+            Some(_) => sanitized,
+        }
+    }
+    /// Primitives `primitive_overrides` can rename
+    pub const OVERRIDABLE_PRIMITIVES: [Il2CppTypeEnum; 16] = [
+        Il2CppTypeEnum::I1,
+        Il2CppTypeEnum::U1,
+        Il2CppTypeEnum::I2,
+        Il2CppTypeEnum::U2,
+        Il2CppTypeEnum::I4,
+        Il2CppTypeEnum::U4,
+        Il2CppTypeEnum::I8,
+        Il2CppTypeEnum::U8,
+        Il2CppTypeEnum::I,
+        Il2CppTypeEnum::U,
+        Il2CppTypeEnum::R4,
+        Il2CppTypeEnum::R8,
+        Il2CppTypeEnum::Void,
+        Il2CppTypeEnum::Boolean,
+        Il2CppTypeEnum::Char,
+        Il2CppTypeEnum::String,
+    ];
+
+    /// C++ name of the primitive `ty` from `primitive_overrides`, if overridden
+    pub fn primitive_override(&self, ty: Il2CppTypeEnum) -> Option<&str> {
+        self.primitive_overrides
+            .get(&format!("{ty:?}"))
+            .map(String::as_str)
+    }
+    /// C++ name of the primitive `ty`, `default` unless overridden
+    pub fn primitive_name(&self, ty: Il2CppTypeEnum, default: &str) -> String {
+        self.primitive_override(ty).unwrap_or(default).to_string()
     }
     pub fn namespace_path(&self, string: &str) -> String {
-        string.replace(['<', '>', '`', '/'], "_").replace('.', "/")
+        if string.is_empty() {
+            return format!("{}{}", self.namespace_prefix, self.global_namespace);
+        }
+
+        let path = string.replace(['<', '>', '`', '/'], "_").replace('.', "/");
+        format!("{}{path}", self.namespace_prefix)
+    }
+    /// `namespace` within the namespace of the assembly `image`
+    pub fn assembly_namespace_cpp(&self, image: &str, namespace: &str) -> String {
+        let image = self.sanitize_to_cpp_name(image).replace('-', "_");
+
+        match namespace.strip_prefix("::") {
+            Some(namespace) => format!("::{image}::{namespace}"),
+            None => format!("{image}::{namespace}"),
+        }
     }
     pub fn path_name(&self, string: &str) -> String {
         string.replace(['<', '>', '`', '.', '/', ',', '(', ')'], "_")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::CppGenerationConfig;
+
+    #[test]
+    fn sanitize_leading_digit() {
+        let config = CppGenerationConfig::new(Path::new("codegen"));
+        assert_eq!(config.sanitize_to_cpp_name("123"), "_cordl_123");
+    }
+
+    #[test]
+    fn sanitize_empty() {
+        let config = CppGenerationConfig::new(Path::new("codegen"));
+        assert_eq!(config.sanitize_to_cpp_name(""), "_cordl_empty_name");
+    }
+
+    #[test]
+    fn modules_reject_anonymous_namespace() {
+        let mut config = CppGenerationConfig::new(Path::new("codegen"));
+        config.modules = true;
+        assert!(config.validate().is_ok());
+
+        config.use_anonymous_namespace = true;
+        assert!(config.validate().is_err());
+    }
+}
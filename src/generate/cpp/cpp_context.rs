@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::{
     collections::{HashMap, HashSet},
     fs::{create_dir_all, remove_file, File},
@@ -13,10 +13,8 @@ use log::trace;
 use pathdiff::diff_paths;
 
 use crate::generate::context::TypeContext;
-use crate::generate::cpp::config::STATIC_CONFIG;
-use crate::generate::cpp::cpp_members::{CppForwardDeclare, CppInclude};
+use crate::generate::cpp::cpp_members::{CppForwardDeclare, CppForwardDeclareGroup, CppInclude};
 use crate::generate::cpp::cpp_type::CORDL_NO_INCLUDE_IMPL_DEFINE;
-
 use crate::generate::cs_type_tag::CsTypeTag;
 use crate::generate::metadata::CordlMetadata;
 use crate::generate::type_extensions::TypeDefinitionExtensions;
@@ -26,7 +24,7 @@ use crate::helpers::sorting::DependencyGraph;
 use super::config::CppGenerationConfig;
 use super::cpp_members::CppUsingAlias;
 use super::cpp_name_resolver::{IL2CPP_OBJECT_TYPE, VALUE_WRAPPER_TYPE};
-use super::cpp_type::CppType;
+use super::cpp_type::{CppType, CppTypeRequirements};
 
 // Holds the contextual information for creating a C++ file
 // Will hold various metadata, such as includes, type definitions, and extraneous writes
@@ -38,11 +36,23 @@ pub struct CppContext {
     // combined header
     pub fundamental_path: PathBuf,
 
+    // closed generic instantiations, see `CppGenerationConfig::split_generic_instantiations`
+    pub generics_path: PathBuf,
+
+    // the paths above relative to `CppGenerationConfig::include_root`
+    pub typedef_include: PathBuf,
+    pub type_impl_include: PathBuf,
+    pub fundamental_include: PathBuf,
+    pub generics_include: PathBuf,
+
     // Types to write, typedef
     pub typedef_types: HashMap<CsTypeTag, CppType>,
 
     // Namespace -> alias
     pub typealias_types: HashSet<(String, CppUsingAlias)>,
+
+    // Field layout CSV, written next to the fundamental header
+    pub layout_report: Option<String>,
 }
 
 /// `CppContext` provides methods to manage and generate C++ type definitions and implementations
@@ -62,12 +72,13 @@ pub struct CppContext {
 /// # Example
 ///
 /// ```rust
-/// let cpp_context = CppContext::make(context_tag, &type_context, &metadata, &config);
+/// let cpp_context = CppContext::make(context_tag, &type_context, &metadata, &config)?;
 /// cpp_context.write(&config)?;
 /// ```
 ///
 /// # Errors
 ///
+/// The `make` method can return an error if the header paths aren't relative to the include root.
 /// The `write` method can return an error if file operations fail, such as creating or removing files and directories.
 ///
 /// # Internal Functions
@@ -95,7 +106,7 @@ impl CppContext {
         context: &TypeContext,
         metadata: &CordlMetadata,
         config: &CppGenerationConfig,
-    ) -> CppContext {
+    ) -> color_eyre::Result<CppContext> {
         let tdi = context_tag.get_tdi();
         let t = &metadata.metadata.global_metadata.type_definitions[tdi];
 
@@ -104,12 +115,12 @@ impl CppContext {
         let ns = &components.namespace.unwrap_or_default();
         let name = &components.name;
 
-        let ns_path = config.namespace_path(ns);
-        let path = if ns_path.is_empty() {
-            "GlobalNamespace/".to_string()
-        } else {
-            ns_path + "/"
+        let image = metadata.type_image_name(tdi);
+        let path = match config.split_assemblies {
+            true => format!("{}/{}/", config.path_name(image), config.namespace_path(ns)),
+            false => config.namespace_path(ns) + "/",
         };
+        let is_ambiguous = config.split_assemblies && metadata.ambiguous_types.contains(&tdi);
         let path_name = match t.declaring_type_index != u32::MAX {
             true => {
                 let name = config.path_name(name);
@@ -120,16 +131,34 @@ impl CppContext {
             false => config.path_name(name),
         };
 
+        let typedef_path = config
+            .header_path
+            .join(format!("{path}zzzz__{path_name}_def.hpp"));
+        let type_impl_path = config
+            .header_path
+            .join(format!("{path}zzzz__{path_name}_impl.hpp"));
+        let fundamental_path = config.header_path.join(format!("{path}{path_name}.hpp"));
+        let generics_path = config
+            .header_path
+            .join(format!("{path}{path_name}.generics.hpp"));
+
         let mut x = CppContext {
-            typedef_path: config
-                .header_path
-                .join(format!("{path}zzzz__{path_name}_def.hpp")),
-            type_impl_path: config
-                .header_path
-                .join(format!("{path}zzzz__{path_name}_impl.hpp")),
-            fundamental_path: config.header_path.join(format!("{path}{path_name}.hpp")),
+            typedef_include: CppInclude::relative_to_root(&typedef_path, &config.include_root)?,
+            type_impl_include: CppInclude::relative_to_root(&type_impl_path, &config.include_root)?,
+            fundamental_include: CppInclude::relative_to_root(
+                &fundamental_path,
+                &config.include_root,
+            )?,
+            generics_include: CppInclude::relative_to_root(&generics_path, &config.include_root)?,
+            typedef_path,
+            type_impl_path,
+            fundamental_path,
+            generics_path,
             typedef_types: Default::default(),
             typealias_types: Default::default(),
+            layout_report: config
+                .layout_report
+                .then(|| Self::make_layout_report(context)),
         };
 
         for (tag, ty) in &context.typedef_types {
@@ -137,6 +166,10 @@ impl CppContext {
 
             let mut cpp_ty = CppType::make_cpp_type(*tag, ty, config);
             cpp_ty.nested_fixup(context_tag, ty, metadata, config);
+            if is_ambiguous {
+                cpp_ty.cpp_name_components.namespace =
+                    Some(config.assembly_namespace_cpp(image, &cpp_ty.cpp_namespace()));
+            }
 
             if metadata.blacklisted_types.contains(&tdi) {
                 let result = match t.is_value_type() {
@@ -163,7 +196,63 @@ impl CppContext {
             x.typedef_types.insert(*tag, cpp_ty);
         }
 
-        x
+        Ok(x)
+    }
+
+    /// Creates a CSV of the field layout of every type in the context.
+    /// Gaps between fields and up to the instance size are listed as padding.
+    fn make_layout_report(context: &TypeContext) -> String {
+        let mut csv = "type,field,offset,size,kind,instance_size,packing\n".to_string();
+
+        for ty in context
+            .typedef_types
+            .values()
+            .sorted_by(|a, b| a.cs_name_components.cmp(&b.cs_name_components))
+        {
+            let type_name = ty.cs_name_components.combine_all();
+            let instance_size = ty.size_info.as_ref().map(|s| s.instance_size);
+            let packing = ty
+                .size_info
+                .as_ref()
+                .and_then(|s| s.packing)
+                .map(|p| p.to_string())
+                .unwrap_or_default();
+
+            let mut row = |field: &str, offset: u32, size: usize, kind: &str| {
+                csv += &format!(
+                    "\"{type_name}\",\"{field}\",0x{offset:x},0x{size:x},{kind},0x{:x},{packing}\n",
+                    instance_size.unwrap_or_default()
+                );
+            };
+
+            let fields = ty
+                .fields
+                .iter()
+                .filter(|f| f.instance)
+                .filter_map(|f| Some((f.offset?, f)))
+                .sorted_by_key(|(offset, _)| *offset);
+
+            // end of the furthest field so far, explicit layouts may overlap
+            let mut end: Option<u32> = None;
+            for (offset, field) in fields {
+                if let Some(end) = end.filter(|end| *end < offset) {
+                    row("", end, (offset - end) as usize, "padding");
+                }
+
+                row(&field.name, offset, field.size, "field");
+
+                let field_end = offset + field.size as u32;
+                end = Some(end.map_or(field_end, |end| end.max(field_end)));
+            }
+
+            if let (Some(end), Some(instance_size)) = (end, instance_size)
+                && end < instance_size
+            {
+                row("", end, (instance_size - end) as usize, "padding");
+            }
+        }
+
+        csv
     }
 
     /// Inserts a new C++ type into the context.
@@ -171,6 +260,53 @@ impl CppContext {
         self.typedef_types.insert(cpp_type.self_tag, cpp_type);
     }
 
+    /// Whether every type is replaced by a hand written or prebuilt type,
+    /// in which case the context has nothing to write
+    pub fn is_overridden(&self, config: &CppGenerationConfig) -> bool {
+        !self.typedef_types.is_empty()
+            && self
+                .typedef_types
+                .values()
+                .all(|t| config.type_override(&t.cs_name_components).is_some())
+    }
+
+    /// Closed generic instantiations to write into the generics header.
+    /// Instantiations the other types of this context depend on, directly or through
+    /// other instantiations, stay in the typedef header as it can't include the generics header
+    fn split_generic_instantiations(&self) -> HashSet<CsTypeTag> {
+        let requirements = self
+            .typedef_types
+            .values()
+            .map(|t| {
+                let is_closed_generic = t.generic_instantiations_args_types.is_some();
+                (t.self_tag, (is_closed_generic, &t.requirements))
+            })
+            .collect();
+
+        split_generic_instantiations(
+            &requirements,
+            &CppInclude::new_exact(&self.generics_include),
+        )
+    }
+
+    /// Creates the generics header, which includes the typedef header of the open templates
+    fn create_generics_header(&self, config: &CppGenerationConfig) -> color_eyre::Result<Writer> {
+        let mut generics_writer = Writer::new(
+            File::create(self.generics_path.as_path())?,
+            config.writer_style,
+        );
+
+        writeln!(generics_writer, "#pragma once")?;
+        writeln!(
+            generics_writer,
+            "// IWYU pragma private; include \"{}\"",
+            self.fundamental_include.display()
+        )?;
+        CppInclude::new_exact(&self.typedef_include).write(&mut generics_writer)?;
+
+        Ok(generics_writer)
+    }
+
     /// Writes the C++ type definitions and implementations to the appropriate files.
     pub fn write(&self, config: &CppGenerationConfig) -> color_eyre::Result<()> {
         // Write typedef file first
@@ -191,36 +327,42 @@ impl CppContext {
             )?;
         }
 
-        let base_path = &config.header_path;
-
         trace!("Writing {:?}", self.typedef_path.as_path());
-        let mut typedef_writer = Writer {
-            stream: BufWriter::new(File::create(self.typedef_path.as_path())?),
-            indent: 0,
-            newline: true,
-        };
-        let mut typeimpl_writer = Writer {
-            stream: BufWriter::new(File::create(self.type_impl_path.as_path())?),
-            indent: 0,
-            newline: true,
-        };
-        let mut fundamental_writer = Writer {
-            stream: BufWriter::new(File::create(self.fundamental_path.as_path())?),
-            indent: 0,
-            newline: true,
+        let mut typedef_writer = Writer::new(
+            File::create(self.typedef_path.as_path())?,
+            config.writer_style,
+        );
+        let mut typeimpl_writer = Writer::new(
+            File::create(self.type_impl_path.as_path())?,
+            config.writer_style,
+        );
+        let mut fundamental_writer = Writer::new(
+            File::create(self.fundamental_path.as_path())?,
+            config.writer_style,
+        );
+
+        // other contexts include this for any closed generic of this context
+        let has_generics_header = config.split_generic_instantiations
+            && self
+                .typedef_types
+                .values()
+                .any(|t| t.generic_instantiations_args_types.is_some());
+        let split_generics = match has_generics_header {
+            true => self.split_generic_instantiations(),
+            false => Default::default(),
         };
+        let mut generics_writer = has_generics_header
+            .then(|| self.create_generics_header(config))
+            .transpose()?;
 
         writeln!(typedef_writer, "#pragma once")?;
         writeln!(typeimpl_writer, "#pragma once")?;
         writeln!(fundamental_writer, "#pragma once")?;
 
         // add IWYU
-        let typedef_include_path = diff_paths(&self.typedef_path, base_path)
-            .context("Failed to get typedef include path")?;
-        let _typeimpl_include_path = diff_paths(&self.type_impl_path, base_path)
-            .context("Failed to get typeimpl include path")?;
-        let fundamental_include_path = diff_paths(&self.fundamental_path, base_path)
-            .context("Failed to get fundamental include path")?;
+        let typedef_include_path = &self.typedef_include;
+        let fundamental_include_path = &self.fundamental_include;
+        let generics_include_path = &self.generics_include;
 
         let fundamental_include_pragma = format!(
             "// IWYU pragma private; include \"{}\"",
@@ -234,7 +376,7 @@ impl CppContext {
         // this is so confusing but basically gets the relative folder
         // navigation for `_config.hpp`
         let dest_path = diff_paths(
-            &STATIC_CONFIG.dst_header_internals_file,
+            &config.dst_header_internals_file,
             self.typedef_path.parent().unwrap(),
         )
         .unwrap();
@@ -244,14 +386,12 @@ impl CppContext {
             .write(&mut typedef_writer)?;
         CppInclude::new_exact(dest_path).write(&mut typedef_writer)?;
 
-        // after including cordl internals
-        // macro module init
-        writeln!(typedef_writer, "CORDL_MODULE_INIT")?;
-
         // alphabetical sorted
         let typedef_types = self
             .typedef_types
             .values()
+            // replaced by hand written types
+            .filter(|t| config.type_override(&t.cs_name_components).is_none())
             .sorted_by(|a, b| a.cpp_name_components.cmp(&b.cpp_name_components))
             // Enums go after stubs
             .sorted_by(|a, b| {
@@ -336,11 +476,23 @@ impl CppContext {
         // Write includes for typedef
         typedef_types
             .iter()
+            .filter(|t| !split_generics.contains(&t.self_tag))
             .flat_map(|t| &t.requirements.required_def_includes)
             .unique()
             .sorted()
             .try_for_each(|i| i.write(&mut typedef_writer))?;
 
+        // Write includes for generics
+        if let Some(generics_writer) = &mut generics_writer {
+            typedef_types
+                .iter()
+                .filter(|t| split_generics.contains(&t.self_tag))
+                .flat_map(|t| &t.requirements.required_def_includes)
+                .unique()
+                .sorted()
+                .try_for_each(|i| i.write(generics_writer))?;
+        }
+
         // Write includes for typeimpl
         typedef_types
             .iter()
@@ -349,23 +501,22 @@ impl CppContext {
             .sorted()
             .try_for_each(|i| i.write(&mut typeimpl_writer))?;
 
-        // add module declarations
-        writeln!(
-            typedef_writer,
-            "CORDL_MODULE_EXPORT({})",
-            self.fundamental_path.file_stem().unwrap().to_string_lossy()
-        )?;
-
         // anonymous namespace
-        if STATIC_CONFIG.use_anonymous_namespace {
-            writeln!(typedef_writer, "CORDL_MODULE_EXPORT_STRUCT namespace {{")?;
-            writeln!(typeimpl_writer, "CORDL_MODULE_EXPORT_STRUCT namespace {{")?;
+        if config.use_anonymous_namespace {
+            writeln!(typedef_writer, "namespace {{")?;
+            writeln!(typeimpl_writer, "namespace {{")?;
+            if let Some(generics_writer) = &mut generics_writer {
+                writeln!(generics_writer, "namespace {{")?;
+            }
         }
 
         // write forward declares
         // and includes for impl
         {
             CppInclude::new_exact(&typedef_include_path).write(&mut typeimpl_writer)?;
+            if has_generics_header {
+                CppInclude::new_exact(&generics_include_path).write(&mut typeimpl_writer)?;
+            }
 
             let forward_declare_and_includes = || {
                 typedef_types
@@ -383,7 +534,7 @@ impl CppContext {
                     Ok(())
                 })?;
 
-            forward_declare_and_includes()
+            let forward_declares = forward_declare_and_includes()
                 .map(|(fd, _inc)| fd)
                 .unique()
                 .sorted_by(|a, b| {
@@ -405,18 +556,20 @@ impl CppContext {
 
                     a_str.cmp(&b_str)
                 })
-                .try_for_each(|fd| fd.write(&mut typedef_writer))?;
+                .cloned();
+            CppForwardDeclareGroup::group(forward_declares)
+                .iter()
+                .try_for_each(|group| group.write(&mut typedef_writer))?;
 
             writeln!(typedef_writer, "// Forward declare root types")?;
             //Forward declare all types
-            typedef_root_types
+            // TODO: Check forward declare is not of own type
+            let root_forward_declares = typedef_root_types
                 .iter()
-                .map(|t| CppForwardDeclare::from_cpp_type(t))
-                // TODO: Check forward declare is not of own type
-                .try_for_each(|fd| {
-                    // Forward declare and include
-                    fd.write(&mut typedef_writer)
-                })?;
+                .map(|t| CppForwardDeclare::from_cpp_type(t));
+            CppForwardDeclareGroup::group(root_forward_declares)
+                .iter()
+                .try_for_each(|group| group.write(&mut typedef_writer))?;
 
             writeln!(typedef_writer, "// Write type traits")?;
             typedef_root_types
@@ -429,41 +582,104 @@ impl CppContext {
                 })?;
         }
 
-        for t in &typedef_root_types_sorted {
-            t.write_def(&mut typedef_writer)?;
+        // excluded kinds only keep the forward declaration above
+        for t in typedef_root_types_sorted
+            .iter()
+            .filter(|t| config.type_kinds.includes(t))
+        {
+            match &mut generics_writer {
+                Some(generics_writer) if split_generics.contains(&t.self_tag) => {
+                    t.write_def(generics_writer)?
+                }
+                _ => t.write_def(&mut typedef_writer)?,
+            }
             t.write_impl(&mut typeimpl_writer)?;
         }
 
         // end anonymous namespace
-        if STATIC_CONFIG.use_anonymous_namespace {
+        if config.use_anonymous_namespace {
             writeln!(typedef_writer, "}} // end anonymous namespace")?;
             writeln!(typeimpl_writer, "}} // end anonymous namespace")?;
+            if let Some(generics_writer) = &mut generics_writer {
+                writeln!(generics_writer, "}} // end anonymous namespace")?;
+            }
         }
 
         // write macros
         typedef_types
             .iter()
+            .filter(|t| config.type_kinds.includes(t))
             .try_for_each(|t| Self::write_il2cpp_arg_macros(t, &mut typedef_writer))?;
 
         // Fundamental
         {
             CppInclude::new_exact(typedef_include_path).write(&mut fundamental_writer)?;
+            if has_generics_header {
+                CppInclude::new_exact(generics_include_path).write(&mut fundamental_writer)?;
+            }
 
             // if guard for intellisense
             writeln!(fundamental_writer, "#ifndef {CORDL_NO_INCLUDE_IMPL_DEFINE}")?;
-            CppInclude::new_exact(diff_paths(&self.type_impl_path, base_path).unwrap())
-                .write(&mut fundamental_writer)?;
+            CppInclude::new_exact(&self.type_impl_include).write(&mut fundamental_writer)?;
             writeln!(fundamental_writer, "#endif")?;
 
             // end IWYU
             writeln!(fundamental_writer, "// IWYU pragma: end_exports")?;
         }
 
+        if let Some(layout_report) = &self.layout_report {
+            std::fs::write(self.fundamental_path.with_extension("csv"), layout_report)?;
+        }
+
+        if config.modules {
+            self.write_module_interface(config)?;
+        }
+
         Ok(())
     }
 
+    /// Writes a C++20 module interface unit next to the fundamental header,
+    /// e.g. `import UnityEngine.Transform;` for `UnityEngine/Transform.hpp`.
+    /// The header is included in the global module fragment and its types are exported
+    /// with using declarations, since types depend on each other cyclically
+    /// which module imports can't express
+    fn write_module_interface(&self, config: &CppGenerationConfig) -> color_eyre::Result<()> {
+        let fundamental_include_path = diff_paths(&self.fundamental_path, &config.header_path)
+            .context("Failed to get fundamental include path")?;
+
+        let module_name = fundamental_include_path
+            .with_extension("")
+            .iter()
+            .map(|component| config.sanitize_to_cpp_name(&component.to_string_lossy()))
+            .join(".");
+
+        // specializations are exported through their template
+        let exported_types = self
+            .typedef_types
+            .values()
+            .filter(|t| config.type_override(&t.cs_name_components).is_none())
+            .filter(|t| t.generic_instantiations_args_types.is_none())
+            .map(|t| (t.cpp_namespace(), t.cpp_name().clone()))
+            .sorted()
+            .into_group_map();
+
+        let mut writer = Writer::new(
+            File::create(self.fundamental_path.with_extension("cppm"))?,
+            config.writer_style,
+        );
+        write_module_interface_unit(
+            &mut writer,
+            &module_name,
+            &CppInclude::new_exact(&self.fundamental_include),
+            exported_types,
+        )
+    }
+
     /// Writes IL2CPP argument macros for the given C++ type.
-    fn write_il2cpp_arg_macros(ty: &CppType, writer: &mut Writer) -> color_eyre::Result<()> {
+    pub(crate) fn write_il2cpp_arg_macros(
+        ty: &CppType,
+        writer: &mut Writer,
+    ) -> color_eyre::Result<()> {
         let is_generic_instantiation = ty.generic_instantiations_args_types.is_some();
         if is_generic_instantiation {
             return Ok(());
@@ -534,3 +750,230 @@ impl CppContext {
         Ok(())
     }
 }
+
+/// Closed generic instantiations no other type depends on, `requirements` are keyed by type
+/// and flagged whether it is a closed generic.
+/// Dependencies are followed through `depending_types`, a type including `generics_include`
+/// can't be traced to the instantiations it needs so they all stay in the typedef header
+fn split_generic_instantiations(
+    requirements: &HashMap<CsTypeTag, (bool, &CppTypeRequirements)>,
+    generics_include: &CppInclude,
+) -> HashSet<CsTypeTag> {
+    let mut kept = HashSet::new();
+    let mut pending = requirements
+        .values()
+        .filter(|(is_closed_generic, _)| !is_closed_generic)
+        .map(|(_, requirements)| *requirements)
+        .collect_vec();
+    let mut checked = HashSet::new();
+
+    while let Some(type_requirements) = pending.pop() {
+        if !checked.insert(type_requirements.self_tag) {
+            continue;
+        }
+        if type_requirements
+            .required_def_includes
+            .contains(generics_include)
+        {
+            return Default::default();
+        }
+
+        for tag in &type_requirements.depending_types {
+            let Some((true, dep_requirements)) = requirements.get(tag) else {
+                continue;
+            };
+
+            kept.insert(*tag);
+            pending.push(dep_requirements);
+        }
+    }
+
+    requirements
+        .iter()
+        .filter(|(tag, (is_closed_generic, _))| *is_closed_generic && !kept.contains(*tag))
+        .map(|(tag, _)| *tag)
+        .collect()
+}
+
+/// `module_name` exporting `exported_types` (namespace -> names) declared by `header`
+fn write_module_interface_unit(
+    writer: &mut Writer,
+    module_name: &str,
+    header: &CppInclude,
+    exported_types: HashMap<String, Vec<String>>,
+) -> color_eyre::Result<()> {
+    writeln!(writer, "module;")?;
+    header.write(writer)?;
+    writeln!(writer, "export module {module_name};")?;
+
+    for (namespace, names) in exported_types.into_iter().sorted() {
+        writeln!(writer, "export namespace {namespace} {{")?;
+        writer.indent();
+        for name in names {
+            writeln!(writer, "using ::{namespace}::{name};")?;
+        }
+        writer.dedent();
+        writeln!(writer, "}} // namespace {namespace}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use brocolib::global_metadata::TypeDefinitionIndex;
+
+    use crate::generate::{
+        cs_type_tag::GenericInstantiation,
+        writer::{IndentStyle, WriterStyle},
+    };
+
+    use super::*;
+
+    fn requirements(self_tag: CsTypeTag, depending_types: &[CsTypeTag]) -> CppTypeRequirements {
+        CppTypeRequirements {
+            self_tag,
+            forward_declares: Default::default(),
+            required_def_includes: Default::default(),
+            required_impl_includes: Default::default(),
+            depending_types: depending_types.iter().copied().collect(),
+        }
+    }
+
+    fn closed_generic(tdi: u32, inst: usize) -> CsTypeTag {
+        CsTypeTag::GenericInstantiation(GenericInstantiation {
+            tdi: TypeDefinitionIndex::new(tdi),
+            inst,
+        })
+    }
+
+    #[test]
+    fn closed_generic_is_split() {
+        let open = CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(1));
+        let closed = closed_generic(1, 0);
+
+        let open_requirements = requirements(open, &[]);
+        let closed_requirements = requirements(closed, &[open]);
+        let types = HashMap::from([
+            (open, (false, &open_requirements)),
+            (closed, (true, &closed_requirements)),
+        ]);
+
+        let split =
+            split_generic_instantiations(&types, &CppInclude::new_exact("List.generics.hpp"));
+        assert_eq!(split, HashSet::from([closed]));
+    }
+
+    #[test]
+    fn depended_generic_stays() {
+        let open = CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(1));
+        let user = CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(2));
+        let closed = closed_generic(1, 0);
+        let nested_closed = closed_generic(1, 1);
+
+        let open_requirements = requirements(open, &[]);
+        let user_requirements = requirements(user, &[closed]);
+        let closed_requirements = requirements(closed, &[open, nested_closed]);
+        let nested_closed_requirements = requirements(nested_closed, &[open]);
+        let types = HashMap::from([
+            (open, (false, &open_requirements)),
+            (user, (false, &user_requirements)),
+            (closed, (true, &closed_requirements)),
+            (nested_closed, (true, &nested_closed_requirements)),
+        ]);
+
+        let split =
+            split_generic_instantiations(&types, &CppInclude::new_exact("List.generics.hpp"));
+        assert!(split.is_empty());
+    }
+
+    #[test]
+    fn generics_include_keeps_all() {
+        let open = CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(1));
+        let closed = closed_generic(1, 0);
+        let generics_include = CppInclude::new_exact("List.generics.hpp");
+
+        let mut open_requirements = requirements(open, &[]);
+        open_requirements
+            .required_def_includes
+            .insert(generics_include.clone());
+        let closed_requirements = requirements(closed, &[open]);
+        let types = HashMap::from([
+            (open, (false, &open_requirements)),
+            (closed, (true, &closed_requirements)),
+        ]);
+
+        assert!(split_generic_instantiations(&types, &generics_include).is_empty());
+    }
+
+    #[test]
+    fn generics_header_includes_typedef() -> color_eyre::Result<()> {
+        let dir = std::env::temp_dir().join("cordl_generics_header_test");
+        create_dir_all(&dir)?;
+        let config = CppGenerationConfig::new(&dir);
+
+        let context = CppContext {
+            typedef_path: dir.join("zzzz__List_def.hpp"),
+            type_impl_path: dir.join("zzzz__List_impl.hpp"),
+            fundamental_path: dir.join("List.hpp"),
+            generics_path: dir.join("List.generics.hpp"),
+            typedef_include: PathBuf::from("System/zzzz__List_def.hpp"),
+            type_impl_include: PathBuf::from("System/zzzz__List_impl.hpp"),
+            fundamental_include: PathBuf::from("System/List.hpp"),
+            generics_include: PathBuf::from("System/List.generics.hpp"),
+            typedef_types: Default::default(),
+            typealias_types: Default::default(),
+            layout_report: None,
+        };
+
+        drop(context.create_generics_header(&config)?);
+
+        let header = std::fs::read_to_string(&context.generics_path)?;
+        assert!(header.starts_with("#pragma once\n"));
+        assert!(header.contains("#include \"System/zzzz__List_def.hpp\"\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn module_interface_exports_types_by_namespace() -> color_eyre::Result<()> {
+        let path = std::env::temp_dir().join("cordl_module_interface_test.cppm");
+        let style = WriterStyle {
+            indent: IndentStyle::Spaces(2),
+            ..Default::default()
+        };
+        let mut writer = Writer::new(File::create(&path)?, style);
+
+        let exported_types = HashMap::from([
+            (
+                "UnityEngine".to_string(),
+                vec!["Transform".to_string(), "Transform_Enumerator".to_string()],
+            ),
+            ("GlobalNamespace".to_string(), vec!["Player".to_string()]),
+        ]);
+        write_module_interface_unit(
+            &mut writer,
+            "UnityEngine.Transform",
+            &CppInclude::new_exact("UnityEngine/Transform.hpp"),
+            exported_types,
+        )?;
+        drop(writer);
+
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "module;
+#include \"UnityEngine/Transform.hpp\"
+export module UnityEngine.Transform;
+export namespace GlobalNamespace {
+  using ::GlobalNamespace::Player;
+} // namespace GlobalNamespace
+export namespace UnityEngine {
+  using ::UnityEngine::Transform;
+  using ::UnityEngine::Transform_Enumerator;
+} // namespace UnityEngine
+"
+        );
+
+        Ok(())
+    }
+}
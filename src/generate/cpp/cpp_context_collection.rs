@@ -3,29 +3,44 @@ use std::{
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    time::Instant,
 };
 
 use rayon::prelude::*;
 
 use color_eyre::eyre::bail;
 use itertools::Itertools;
-use log::{info, trace};
-use pathdiff::diff_paths;
+use log::{info, trace, warn};
 use rayon::iter::ParallelIterator;
 
 use crate::generate::{
-    cpp::config::STATIC_CONFIG, cs_context_collection::TypeContextCollection, cs_type::CsType,
-    cs_type_tag::CsTypeTag, metadata::CordlMetadata,
+    cs_context_collection::TypeContextCollection,
+    cs_type::CsType,
+    cs_type_tag::CsTypeTag,
+    diagnostics::{TimingStage, TypeTimings},
+    metadata::{CordlMetadata, MetadataVersion},
+    writer::{Writable, Writer},
 };
+use crate::helpers::sorting::DependencyGraph;
 
 unsafe impl Send for CsTypeTag {}
 unsafe impl Send for CppContext {}
 
 use super::{
-    config::CppGenerationConfig, cpp_context::CppContext, cpp_members::CppInclude,
-    cpp_name_resolver::CppNameResolver, cpp_type::CppType,
+    config::CppGenerationConfig,
+    cpp_context::CppContext,
+    cpp_members::{CppForwardDeclare, CppForwardDeclareGroup, CppInclude},
+    cpp_name_resolver::CppNameResolver,
+    cpp_type::CppType,
 };
 
+/// Written into the internals directory, see `write_version_header`
+const VERSION_HEADER: &str = "cordl_version.hpp";
+/// Written into the internals directory, see `write_unity_object_header`
+const UNITY_OBJECT_HEADER: &str = "unity-object.hpp";
+/// Written into the header directory, see `write_amalgamation`
+const AMALGAMATION_HEADER: &str = "cordl_all.hpp";
+
 #[derive(Default)]
 pub struct CppContextCollection {
     // Should always be a TypeDefinitionIndex
@@ -41,16 +56,17 @@ impl CppContextCollection {
         collection: TypeContextCollection,
         metadata: &CordlMetadata,
         config: &CppGenerationConfig,
-    ) -> CppContextCollection {
+    ) -> color_eyre::Result<CppContextCollection> {
         let mut cpp_collection = CppContextCollection::default();
 
         info!("Making CppContextCollection from TypeContextCollection");
         for (tag, context) in collection.get() {
             cpp_collection
                 .all_contexts
-                .insert(*tag, CppContext::make(*tag, context, metadata, config));
+                .insert(*tag, CppContext::make(*tag, context, metadata, config)?);
         }
         cpp_collection.alias_context = collection.alias_context;
+        cpp_collection.dedupe_type_names();
 
         info!("Filling typedefs in CppContextCollection");
         for (_, context) in collection.all_contexts {
@@ -59,7 +75,53 @@ impl CppContextCollection {
             }
         }
 
-        cpp_collection
+        Ok(cpp_collection)
+    }
+
+    /// Sanitized names can collide, e.g. `a.b` and `a_b` both become `a_b`,
+    /// so all but one type per name and scope get a numbered suffix.
+    /// Runs before filling so references resolve to the new names
+    fn dedupe_type_names(&mut self) {
+        let types = self
+            .all_contexts
+            .values()
+            .flat_map(|c| c.typedef_types.values())
+            // closed generics are named like their open definition and follow its rename
+            .filter(|t| matches!(t.self_tag, CsTypeTag::TypeDefinitionIndex(_)))
+            .map(|t| {
+                let scope = (
+                    t.cpp_name_components.namespace.clone(),
+                    t.cpp_name_components.declaring_types.clone(),
+                );
+                let keeps_cs_name = t.cpp_name_components.name == t.cs_name_components.name;
+
+                (
+                    scope,
+                    t.cpp_name_components.name.clone(),
+                    t.self_tag,
+                    keeps_cs_name,
+                )
+            });
+
+        let renames = dedupe_names(types);
+        if renames.is_empty() {
+            return;
+        }
+
+        for t in self
+            .all_contexts
+            .values_mut()
+            .flat_map(|c| c.typedef_types.values_mut())
+        {
+            let tdi_tag = CsTypeTag::TypeDefinitionIndex(t.self_tag.get_tdi());
+            if let Some(name) = renames.get(&tdi_tag) {
+                warn!(
+                    "Renaming {} to {name}, its sanitized name collides in its namespace",
+                    t.cs_name_components.combine_all()
+                );
+                t.cpp_name_components.name = name.clone();
+            }
+        }
     }
 
     fn do_fill_cpp_type(
@@ -84,6 +146,7 @@ impl CppContextCollection {
         let name_resolver = CppNameResolver {
             cordl_metadata: metadata,
             collection: self,
+            config,
         };
 
         cpp_type.fill(cs_type, &name_resolver, config);
@@ -209,12 +272,20 @@ impl CppContextCollection {
         &mut self.all_contexts
     }
 
-    pub fn write_all(&self, config: &CppGenerationConfig) -> color_eyre::Result<()> {
+    /// Writes every context, dropping each one as soon as it is written to keep peak memory down
+    /// The collection is empty afterwards, so anything else that reads it must run first
+    pub fn write_all(
+        &mut self,
+        config: &CppGenerationConfig,
+        timings: &TypeTimings,
+    ) -> color_eyre::Result<()> {
         let amount = self.all_contexts.len() as f64;
         self.all_contexts
-            .iter()
+            .drain()
+            // their types are included from elsewhere
+            .filter(|(_, c)| !c.is_overridden(config))
             .enumerate()
-            .try_for_each(|(i, (_, c))| {
+            .try_for_each(|(i, (tag, c))| {
                 trace!(
                     "Writing {:.4}% ({}/{}) {}",
                     (i as f64 / amount * 100.0),
@@ -222,18 +293,42 @@ impl CppContextCollection {
                     amount,
                     c.fundamental_path.display(),
                 );
-                c.write(config)
+                let start = Instant::now();
+                c.write(config)?;
+                timings.record(
+                    TimingStage::Write,
+                    || match c.get_types().get(&tag) {
+                        Some(t) => t.cs_name_components.combine_all(),
+                        None => c.fundamental_path.display().to_string(),
+                    },
+                    start.elapsed(),
+                );
+                Ok(())
             })
     }
 
-    pub fn write_namespace_headers(&self) -> color_eyre::Result<()> {
+    /// Writes a header per namespace directory including all of its types.
+    /// Consumers defining `CORDL_EXPECTED_METADATA_VERSION` get an error
+    /// when it differs from the metadata version the headers were generated from
+    pub fn write_namespace_headers(
+        &self,
+        config: &CppGenerationConfig,
+        metadata_version: MetadataVersion,
+    ) -> color_eyre::Result<()> {
+        let version_include = CppInclude::relative_to_root(
+            &config.dst_internals_path.join(VERSION_HEADER),
+            &config.include_root,
+        )?
+        .to_string_lossy()
+        .replace('\\', "/");
+
         self.all_contexts
             .iter()
             .into_group_map_by(|(_, c)| c.fundamental_path.parent())
             .into_iter()
             .try_for_each(|(dir, contexts)| -> color_eyre::Result<()> {
-                let namespace = if dir.unwrap() == STATIC_CONFIG.header_path {
-                    "GlobalNamespace"
+                let namespace = if dir.unwrap() == config.header_path {
+                    config.global_namespace.as_str()
                 } else {
                     dir.unwrap().file_name().unwrap().to_str().unwrap()
                 };
@@ -242,6 +337,8 @@ impl CppContextCollection {
                     .iter()
                     // ignore empty contexts
                     .filter(|(_, c)| !c.typedef_types.is_empty())
+                    // not written
+                    .filter(|(_, c)| !c.is_overridden(config))
                     // ignore weird named types
                     .filter(|(_, c)| {
                         !c.fundamental_path
@@ -253,8 +350,7 @@ impl CppContextCollection {
                     })
                     // add includes
                     .map(|(_, c)| {
-                        let stripped_path =
-                            diff_paths(&c.fundamental_path, &STATIC_CONFIG.header_path).unwrap();
+                        let stripped_path = &c.fundamental_include;
 
                         let stripped_path_friendly = if cfg!(windows) {
                             stripped_path.to_string_lossy().replace('\\', "/")
@@ -277,30 +373,275 @@ impl CppContextCollection {
 
                 let mut file = File::create(path)?;
 
-                writeln!(
-                    file,
-                    "#ifdef __cpp_modules
-                    module;
-                    #endif
-                "
-                )?;
-                writeln!(file, "#pragma once")?;
+                write_namespace_header_guard(&mut file, &version_include, metadata_version)?;
                 file.write_all(str.as_bytes())?;
 
-                writeln!(file)?;
-                writeln!(
-                    file,
-                    "#ifdef __cpp_modules
-                    export module {namespace};
-                    #endif
-                "
-                )?;
-
                 Ok(())
             })?;
         Ok(())
     }
 
+    /// Writes the version marker header that every namespace header includes
+    pub fn write_version_header(
+        &self,
+        config: &CppGenerationConfig,
+        version_marker: &str,
+    ) -> color_eyre::Result<()> {
+        let path = config.dst_internals_path.join(VERSION_HEADER);
+        info!("Writing version marker {version_marker} to {path:?}");
+
+        let mut file = File::create(path)?;
+        writeln!(file, "#pragma once")?;
+        writeln!(file)?;
+        // same wrapping as the headers in cordl_internals/
+        writeln!(file, "namespace {{")?;
+        writeln!(file, "namespace cordl_internals {{")?;
+        writeln!(
+            file,
+            "/// cordl version and il2cpp metadata these bindings were generated from"
+        )?;
+        writeln!(
+            file,
+            "inline constexpr char const* cordl_version = \"{version_marker}\";"
+        )?;
+        writeln!(file, "}} // namespace cordl_internals")?;
+        writeln!(file, "}} // end anonymous namespace")?;
+
+        Ok(())
+    }
+
+    /// Writes the header declaring `CORDL_UNITY_OBJECT`,
+    /// which the internals use to name `UnityEngine.Object` in its possibly prefixed namespace
+    pub fn write_unity_object_header(
+        &self,
+        config: &CppGenerationConfig,
+    ) -> color_eyre::Result<()> {
+        let path = config.dst_internals_path.join(UNITY_OBJECT_HEADER);
+        let namespace = config.namespace_cpp("UnityEngine");
+        let namespace = namespace.trim_start_matches("::");
+
+        let mut file = File::create(path)?;
+        writeln!(file, "#pragma once")?;
+        writeln!(file)?;
+        writeln!(file, "namespace {namespace} {{")?;
+        writeln!(file, "class Object;")?;
+        writeln!(file, "}} // namespace {namespace}")?;
+        writeln!(file)?;
+        writeln!(file, "#define CORDL_UNITY_OBJECT ::{namespace}::Object")?;
+
+        Ok(())
+    }
+
+    /// Writes `cordl_compat.hpp`, re-exporting each configured type into its old namespace
+    pub fn write_compat_shims(&self, config: &CppGenerationConfig) -> color_eyre::Result<()> {
+        let path = config.header_path.join("cordl_compat.hpp");
+        info!("Writing compat shims to {path:?}");
+
+        let mut file = File::create(&path)?;
+        writeln!(file, "#pragma once")?;
+        writeln!(file)?;
+
+        for (cs_name, old_namespace) in &config.compat_shims {
+            let found = self.all_contexts.values().find_map(|context| {
+                context
+                    .typedef_types
+                    .values()
+                    .find(|t| {
+                        t.cs_name_components.clone().remove_generics().combine_all() == *cs_name
+                    })
+                    .map(|t| (context, t))
+            });
+
+            let Some((context, ty)) = found else {
+                warn!("Compat shim type {cs_name} not found, skipping");
+                continue;
+            };
+
+            let include = CppInclude::new_context_fundamental(context, config);
+            let cpp_name = ty
+                .cpp_name_components
+                .remove_pointer()
+                .remove_generics()
+                .combine_all();
+
+            writeln!(file, "#include \"{}\"", include.include.display())?;
+            writeln!(file, "namespace {old_namespace} {{")?;
+            writeln!(file, "using {cpp_name};")?;
+            writeln!(file, "}} // namespace {old_namespace}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes every type into the single header `cordl_all.hpp` instead of a header per context.
+    /// Includes of generated headers are left out, only external includes are kept.
+    /// All types are forward declared first, then defined in dependency order across contexts,
+    /// then implemented once every definition is complete
+    pub fn write_amalgamation(&self, config: &CppGenerationConfig) -> color_eyre::Result<()> {
+        let path = config.header_path.join(AMALGAMATION_HEADER);
+        info!("Writing amalgamated header to {path:?}");
+
+        let contexts = self
+            .all_contexts
+            .values()
+            .filter(|c| !c.is_overridden(config))
+            .collect_vec();
+
+        // generated headers, which are all part of the amalgamation
+        let context_includes: HashSet<CppInclude> = contexts
+            .iter()
+            .flat_map(|c| {
+                [
+                    CppInclude::new_context_typedef(c, config),
+                    CppInclude::new_context_typeimpl(c, config),
+                    CppInclude::new_context_fundamental(c, config),
+                    CppInclude::new_context_generics(c, config),
+                ]
+            })
+            .collect();
+
+        let types = contexts
+            .iter()
+            .flat_map(|c| c.typedef_types.values())
+            // replaced by hand written types
+            .filter(|t| config.type_override(&t.cs_name_components).is_none())
+            .sorted_by(|a, b| a.cpp_name_components.cmp(&b.cpp_name_components))
+            .collect_vec();
+        let types_by_tag: HashMap<CsTypeTag, &CppType> =
+            types.iter().map(|t| (t.self_tag, *t)).collect();
+
+        // specializations depend on their generic definition's dependents too
+        let generic_insts_by_tdi = types
+            .iter()
+            .filter_map(|t| match t.self_tag {
+                CsTypeTag::TypeDefinitionIndex(_) => None,
+                CsTypeTag::GenericInstantiation(gen_inst) => Some((gen_inst.tdi, &t.self_tag)),
+            })
+            .into_group_map();
+
+        let mut ts = DependencyGraph::<CsTypeTag, _>::new(|a, b| a.cmp(b));
+        for cpp_type in &types {
+            ts.add_root_dependency(&cpp_type.self_tag);
+
+            for dep in cpp_type.requirements.depending_types.iter().sorted() {
+                ts.add_dependency(&cpp_type.self_tag, dep);
+
+                if let CsTypeTag::TypeDefinitionIndex(tdi) = dep {
+                    for generic_dep in generic_insts_by_tdi.get(tdi).into_iter().flatten() {
+                        ts.add_dependency(&cpp_type.self_tag, generic_dep);
+                    }
+                }
+            }
+        }
+        let types_sorted = ts
+            .topological_sort()
+            .into_iter()
+            .filter_map(|t| types_by_tag.get(t).copied())
+            .filter(|t| config.type_kinds.includes(t))
+            .collect_vec();
+
+        let mut writer = Writer::new(File::create(&path)?, config.writer_style);
+        writeln!(writer, "#pragma once")?;
+
+        CppInclude::new_exact("beatsaber-hook/shared/utils/typedefs.h").write(&mut writer)?;
+        CppInclude::new_exact(CppInclude::relative_to_root(
+            &config.dst_header_internals_file,
+            &config.include_root,
+        )?)
+        .write(&mut writer)?;
+
+        types
+            .iter()
+            .flat_map(|t| {
+                t.requirements
+                    .required_def_includes
+                    .iter()
+                    .chain(&t.requirements.required_impl_includes)
+            })
+            .filter(|i| !context_includes.contains(i))
+            .unique()
+            .sorted()
+            .try_for_each(|i| i.write(&mut writer))?;
+
+        if config.use_anonymous_namespace {
+            writeln!(writer, "namespace {{")?;
+        }
+
+        writeln!(writer, "// Forward declare all types")?;
+        CppForwardDeclareGroup::group(types.iter().map(|t| CppForwardDeclare::from_cpp_type(t)))
+            .iter()
+            .try_for_each(|group| group.write(&mut writer))?;
+
+        writeln!(writer, "// Write type traits")?;
+        types
+            .iter()
+            .filter(|t| t.generic_instantiations_args_types.is_none())
+            .try_for_each(|t| t.write_type_trait(&mut writer))?;
+
+        for t in &types_sorted {
+            t.write_def(&mut writer)?;
+        }
+        for t in &types_sorted {
+            t.write_impl(&mut writer)?;
+        }
+
+        if config.use_anonymous_namespace {
+            writeln!(writer, "}} // end anonymous namespace")?;
+        }
+
+        types_sorted
+            .iter()
+            .try_for_each(|t| CppContext::write_il2cpp_arg_macros(t, &mut writer))?;
+
+        Ok(())
+    }
+
+    /// Writes `cordl_manifest.txt`, listing each generated type with its C++ name and header
+    /// so later runs can use these headers as a prebuilt set, see `PrebuiltManifest`
+    pub fn write_manifest(&self, config: &CppGenerationConfig) -> color_eyre::Result<()> {
+        let path = config.header_path.join("cordl_manifest.txt");
+        info!("Writing prebuilt manifest to {path:?}");
+
+        let mut file = File::create(&path)?;
+        writeln!(file, "# CS_NAME CPP_NAME INCLUDE")?;
+
+        let entries = self
+            .all_contexts
+            .values()
+            .filter(|context| !context.is_overridden(config))
+            .flat_map(|context| {
+                context
+                    .typedef_types
+                    .values()
+                    // specializations are named through their generic definition
+                    .filter(|t| t.generic_instantiations_args_types.is_none())
+                    .filter(|t| config.type_override(&t.cs_name_components).is_none())
+                    .map(move |t| (context, t))
+            })
+            .map(|(context, t)| {
+                let cs_name = t.cs_name_components.clone().remove_generics().combine_all();
+                let cpp_name = t
+                    .cpp_name_components
+                    .remove_pointer()
+                    .remove_generics()
+                    .combine_all();
+                let include = CppInclude::new_context_fundamental(context, config);
+
+                (cs_name, cpp_name, include.include)
+            })
+            .sorted();
+
+        for (cs_name, cpp_name, include) in entries {
+            writeln!(
+                file,
+                "{cs_name} {cpp_name} {}",
+                include.to_string_lossy().replace('\\', "/")
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn cyclic_include_check(&self) -> color_eyre::Result<()> {
         info!("Checking for cyclic includes");
 
@@ -325,10 +666,10 @@ impl CppContextCollection {
                 }
 
                 // find a context from the header
-                let Some(next_context) = collection
-                    .values()
-                    .find(|next_context| next_context.typedef_path.ends_with(&header.include))
-                else {
+                let Some(next_context) = collection.values().find(|next_context| {
+                    next_context.typedef_path.ends_with(&header.include)
+                        || next_context.generics_path.ends_with(&header.include)
+                }) else {
                     println!("No context found for include: {:?}", header.include);
                     continue;
                 };
@@ -372,3 +713,152 @@ impl CppContextCollection {
         Ok(())
     }
 }
+
+/// New names of the types whose name collides with another in the same scope.
+/// Types that keep their C# name are preferred to keep it, then the lowest tag,
+/// the others get the first free `{name}_{n}`
+fn dedupe_names<S: Eq + std::hash::Hash + Clone>(
+    types: impl Iterator<Item = (S, String, CsTypeTag, bool)>,
+) -> HashMap<CsTypeTag, String> {
+    let by_scope = types.into_group_map_by(|(scope, ..)| scope.clone());
+
+    let mut renames = HashMap::new();
+    for types in by_scope.into_values() {
+        let mut used: HashSet<String> = types.iter().map(|(_, name, ..)| name.clone()).collect();
+
+        let colliding = types
+            .into_iter()
+            .into_group_map_by(|(_, name, ..)| name.clone())
+            .into_iter()
+            .filter(|(_, types)| types.len() > 1)
+            .sorted_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (name, types) in colliding {
+            let renamed = types
+                .into_iter()
+                .sorted_by_key(|(_, _, tag, keeps_cs_name)| (!keeps_cs_name, *tag))
+                .skip(1);
+
+            for (_, _, tag, _) in renamed {
+                let new_name = (1..)
+                    .map(|n| format!("{name}_{n}"))
+                    .find(|new_name| !used.contains(new_name))
+                    .unwrap();
+
+                used.insert(new_name.clone());
+                renames.insert(tag, new_name);
+            }
+        }
+    }
+
+    renames
+}
+
+/// Writes the start of a namespace header.
+/// Only preprocessor checks live here, anything declared comes from the version header
+/// so it stays in the anonymous `cordl_internals` namespace
+fn write_namespace_header_guard(
+    writer: &mut impl Write,
+    version_include: &str,
+    metadata_version: MetadataVersion,
+) -> std::io::Result<()> {
+    writeln!(writer, "#pragma once")?;
+    writeln!(writer, "#include \"{version_include}\"")?;
+    writeln!(
+        writer,
+        "#if defined(CORDL_EXPECTED_METADATA_VERSION) && CORDL_EXPECTED_METADATA_VERSION != {version}
+#error \"cordl headers were generated for il2cpp metadata {version_str}, which differs from CORDL_EXPECTED_METADATA_VERSION\"
+#endif",
+        version = metadata_version.number(),
+        version_str = metadata_version.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use brocolib::global_metadata::TypeDefinitionIndex;
+
+    use super::*;
+
+    fn tag(tdi: u32) -> CsTypeTag {
+        CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(tdi))
+    }
+
+    #[test]
+    fn sanitized_collision_is_renamed() {
+        // `a.b` and `a_b` both sanitize to `a_b`
+        let types = [
+            ("ns", "a_b".to_string(), tag(1), false),
+            ("ns", "a_b".to_string(), tag(2), true),
+        ];
+
+        let renames = dedupe_names(types.into_iter());
+        assert_eq!(renames, HashMap::from([(tag(1), "a_b_1".to_string())]));
+    }
+
+    #[test]
+    fn rename_skips_taken_names() {
+        let types = [
+            ("ns", "a_b".to_string(), tag(1), true),
+            ("ns", "a_b".to_string(), tag(2), false),
+            ("ns", "a_b_1".to_string(), tag(3), true),
+        ];
+
+        let renames = dedupe_names(types.into_iter());
+        assert_eq!(renames, HashMap::from([(tag(2), "a_b_2".to_string())]));
+    }
+
+    #[test]
+    fn other_scopes_dont_collide() {
+        let types = [
+            ("a", "a_b".to_string(), tag(1), true),
+            ("b", "a_b".to_string(), tag(2), false),
+        ];
+
+        assert!(dedupe_names(types.into_iter()).is_empty());
+    }
+
+    #[test]
+    fn version_header_is_in_anonymous_namespace() -> color_eyre::Result<()> {
+        let dir = std::env::temp_dir().join("cordl_version_header_test");
+        let config = CppGenerationConfig::new(&dir);
+        std::fs::create_dir_all(&config.dst_internals_path)?;
+
+        CppContextCollection::default().write_version_header(&config, "cordl 1.0 v31")?;
+
+        let header = std::fs::read_to_string(config.dst_internals_path.join(VERSION_HEADER))?;
+        assert!(header.contains(
+            "namespace {
+namespace cordl_internals {
+/// cordl version and il2cpp metadata these bindings were generated from
+inline constexpr char const* cordl_version = \"cordl 1.0 v31\";
+} // namespace cordl_internals
+} // end anonymous namespace
+"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn namespace_header_guards_metadata_version() -> std::io::Result<()> {
+        let mut header = Vec::new();
+        write_namespace_header_guard(
+            &mut header,
+            "cordl_internals/cordl_version.hpp",
+            MetadataVersion::V29,
+        )?;
+
+        let header = String::from_utf8(header).unwrap();
+        assert!(
+            header.starts_with("#pragma once\n#include \"cordl_internals/cordl_version.hpp\"\n")
+        );
+        assert!(header.contains(
+            "#if defined(CORDL_EXPECTED_METADATA_VERSION) && CORDL_EXPECTED_METADATA_VERSION != 29\n"
+        ));
+        assert!(header.contains("generated for il2cpp metadata v29"));
+        assert!(!header.contains("namespace"));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::data::type_resolver::{ResolvedTypeData, TypeUsage};
 use crate::generate::cpp::cpp_type::CORDL_ACCESSOR_FIELD_PREFIX;
 
-use crate::generate::cs_members::CsField;
+use crate::generate::cs_members::{CsField, CsValue};
 use crate::generate::cs_type_tag::CsTypeTag;
 use crate::generate::metadata::CordlMetadata;
 use crate::generate::type_extensions::{
@@ -17,7 +17,7 @@ use brocolib::runtime_metadata::Il2CppTypeEnum;
 
 use brocolib::global_metadata::TypeDefinitionIndex;
 
-use super::config::CppGenerationConfig;
+use super::config::{CppGenerationConfig, PackingStyle};
 use super::cpp_members::{
     CppFieldDecl, CppFieldImpl, CppInclude, CppNestedStruct, CppNestedUnion, CppNonMember,
     CppStaticAssert, CppTemplate, WritableDebug,
@@ -90,16 +90,19 @@ pub fn handle_static_fields(
             instance: false,
             return_type: get_return_type,
 
+            docs: None,
             brief: None,
             body: None, // TODO:
             // Const if instance for now
             is_const: false,
             is_constexpr: field_info.instance || field_info.is_const,
             is_inline: true,
+            is_nodiscard: true,
             is_virtual: false,
             is_implicit_operator: false,
             is_explicit_operator: false,
-            is_no_except: false, // TODO:
+            // static field access goes through il2cpp
+            is_no_except: false,
             parameters: vec![],
             prefix_modifiers: vec![],
             suffix_modifiers: vec![],
@@ -111,15 +114,18 @@ pub fn handle_static_fields(
             instance: false,
             return_type: "void".to_string(),
 
+            docs: None,
             brief: None,
             body: None,      //TODO:
             is_const: false, // TODO: readonly fields?
             is_constexpr: field_info.instance || field_info.is_const,
             is_inline: true,
+            is_nodiscard: false,
             is_virtual: false,
             is_implicit_operator: false,
             is_explicit_operator: false,
-            is_no_except: false, // TODO:
+            // static field access goes through il2cpp
+            is_no_except: false,
             parameters: vec![CppParam {
                 def_value: None,
                 modifiers: "".to_string(),
@@ -155,7 +161,7 @@ pub fn handle_static_fields(
             instance: field_info.instance,
             getter: getter_decl.cpp_name.clone().into(),
             setter: setter_decl.cpp_name.clone().into(),
-            indexable: false,
+            index_count: 0,
             brief_comment: Some(format!(
                 "Field {f_name}, offset 0x{f_offset:x}, size 0x{f_size:x} "
             )),
@@ -240,7 +246,7 @@ pub(crate) fn handle_const_fields(
                     brief_comment: Some(format!(
                         "Field {f_name} offset 0x{f_offset:x} size 0x{f_size:x}"
                     )),
-                    value: Some(def_value.to_string()),
+                    value: Some(def_value.to_cpp_string(config)),
                     ..cpp_field_template
                 };
 
@@ -248,6 +254,19 @@ pub(crate) fn handle_const_fields(
                     .declarations
                     .push(CppMember::FieldDecl(field_decl).into());
             }
+            // blobs can only be bit cast to trivially copyable value types,
+            // reference types would need an object allocated at runtime
+            _ if matches!(def_value, CsValue::Object(_) | CsValue::ValueType(_))
+                && !f_type.valuetype =>
+            {
+                cpp_type.declarations.push(
+                    CppMember::CppLine(CppLine::make(format!(
+                        "// Skipped constant {f_name} of reference type {}",
+                        cpp_field_template.field_ty
+                    )))
+                    .into(),
+                );
+            }
             _ => {
                 // other type
                 let field_decl = CppFieldDecl {
@@ -258,8 +277,26 @@ pub(crate) fn handle_const_fields(
                     brief_comment: Some(format!("Field {f_name} value: {def_value:?}")),
                     ..cpp_field_template.clone()
                 };
+                let value = match def_value {
+                    // value types are trivially copyable
+                    CsValue::Object(_) | CsValue::ValueType(_) => {
+                        cpp_type
+                            .requirements
+                            .add_def_include(None, CppInclude::new_system("array"));
+                        cpp_type
+                            .requirements
+                            .add_def_include(None, CppInclude::new_system("bit"));
+
+                        format!(
+                            "::std::bit_cast<{}>({})",
+                            cpp_field_template.field_ty,
+                            def_value.to_cpp_string(config)
+                        )
+                    }
+                    _ => def_value.to_cpp_string(config),
+                };
                 let field_impl = CppFieldImpl {
-                    value: def_value.to_string(),
+                    value,
                     const_expr: true,
                     declaring_type: cpp_type.cpp_name_components.remove_pointer().combine_all(),
                     declaring_type_template: declaring_cpp_template.clone(),
@@ -284,7 +321,7 @@ pub(crate) fn handle_const_fields(
 
                         cpp_type.requirements.add_impl_include(
                             field_cpp_type,
-                            CppInclude::new_context_typeimpl(field_cpp_context),
+                            CppInclude::new_context_typeimpl(field_cpp_context, config),
                         );
                     }
                 }
@@ -326,11 +363,21 @@ pub(crate) fn handle_instance_fields(
         })
     };
 
+    // explicit layout fields also emit padding and alignment members named after them,
+    // so rename before unionizing if any of those collide
+    let is_explicit_layout = t.is_explicit_layout();
+    let name_collides = |field_name: &str| match is_explicit_layout {
+        true => offset_struct_member_names(field_name)
+            .iter()
+            .any(|n| property_exists(n)),
+        false => property_exists(field_name),
+    };
+
     let resulting_fields = instance_field_decls
         .into_iter()
         .map(|d| {
             let mut f = d;
-            if property_exists(&f.cpp_name) {
+            if name_collides(&f.cpp_name) {
                 f.cpp_name = format!("_cordl_{}", &f.cpp_name);
 
                 // make private if a property with this name exists
@@ -342,9 +389,13 @@ pub(crate) fn handle_instance_fields(
         .collect_vec();
 
     // explicit layout types are packed into single unions
-    if t.is_explicit_layout() {
+    if is_explicit_layout {
         // oh no! the fields are unionizing! don't tell elon musk!
-        let u = pack_fields_into_single_union(&resulting_fields);
+        let u = pack_fields_into_single_union(
+            &resulting_fields,
+            cpp_type.is_value_type || cpp_type.is_enum_type,
+            cpp_type.packing_style,
+        );
         cpp_type.declarations.push(CppMember::NestedUnion(u).into());
     } else {
         // TODO: Make field offset asserts for explicit layouts!
@@ -357,7 +408,7 @@ pub(crate) fn handle_instance_fields(
     };
 }
 
-fn add_field_offset_asserts(cpp_type: &mut CppType, fields: &[CppFieldDecl]) {
+pub(crate) fn add_field_offset_asserts(cpp_type: &mut CppType, fields: &[CppFieldDecl]) {
     // let cpp_name = if let Some(cpp_template) = &cpp_type.cpp_template {
     //     // We don't handle generic instantiations since we can't tell if a ge
     //     let mut name_components = cpp_type.cpp_name_components.clone();
@@ -424,7 +475,7 @@ pub(crate) fn handle_valuetype_fields(
     }
 
     // instance fields for explicit layout value types are special
-    if t.is_explicit_layout() {
+    if t.is_explicit_layout() && !config.layout_only {
         for field_info in fields.iter().filter(|f| f.instance) {
             // don't get a template that has no names
             let template = cpp_type
@@ -496,7 +547,7 @@ pub(crate) fn prop_decl_from_fieldinfo(
         instance: cs_field.instance,
         getter: Some(getter_name),
         setter: Some(setter_name),
-        indexable: false,
+        index_count: 0,
         brief_comment: Some(format!(
             "Field {f_name}, offset 0x{f_offset:x}, size 0x{f_size:x} "
         )),
@@ -510,6 +561,39 @@ fn method_names_from_fieldinfo(f_cpp_name: &str) -> (String, String) {
     (getter_name, setter_name)
 }
 
+/// Field getter returning `return_type`, a reference to the field
+fn field_getter_decl(
+    cpp_name: String,
+    return_type: String,
+    is_const: bool,
+    is_constexpr: bool,
+    is_no_except: bool,
+) -> CppMethodDecl {
+    CppMethodDecl {
+        cpp_name,
+        instance: true,
+        return_type,
+
+        docs: None,
+        brief: None,
+        body: None, // TODO:
+        is_const,
+        is_constexpr,
+        is_inline: true,
+        // discarding a getter's result is always a mistake
+        is_nodiscard: true,
+        is_virtual: false,
+        is_implicit_operator: false,
+        is_explicit_operator: false,
+
+        is_no_except,
+        parameters: vec![],
+        prefix_modifiers: vec![],
+        suffix_modifiers: vec![],
+        template: None,
+    }
+}
+
 pub(crate) fn prop_methods_from_fieldinfo(
     field: &CsField,
     field_template: Option<CppTemplate>,
@@ -547,6 +631,8 @@ pub(crate) fn prop_methods_from_fieldinfo(
     let const_get_return_type = format!("{field_ty_cpp_name} const&");
 
     let declaring_is_ref = cpp_type.is_reference_type;
+    // ref type accessors null check and write barrier, value type accessors are plain member access
+    let is_no_except = !declaring_is_ref;
 
     // for ref types we emit an instance null check that is dependent on a compile time define,
     // that way we can prevent nullptr access and instead throw, if the user wants this
@@ -575,65 +661,39 @@ pub(crate) fn prop_methods_from_fieldinfo(
         }
     };
 
-    let getter_decl = CppMethodDecl {
-        cpp_name: getter_name.clone(),
-        instance: true,
-        return_type: get_return_type,
-
-        brief: None,
-        body: None, // TODO:
-        // Const if instance for now
-        is_const: false,
-        is_constexpr: !f_type.is_static() || f_type.is_constant(),
-        is_inline: true,
-        is_virtual: false,
-        is_implicit_operator: false,
-        is_explicit_operator: false,
-
-        is_no_except: false, // TODO:
-        parameters: vec![],
-        prefix_modifiers: vec![],
-        suffix_modifiers: vec![],
-        template: None,
-    };
-
-    let const_getter_decl = CppMethodDecl {
-        cpp_name: getter_name,
-        instance: true,
-        return_type: const_get_return_type,
-
-        brief: None,
-        body: None, // TODO:
-        // Const if instance for now
-        is_const: true,
-        is_constexpr: !f_type.is_static() || f_type.is_constant(),
-        is_inline: true,
-        is_virtual: false,
-        is_implicit_operator: false,
-        is_explicit_operator: false,
-
-        is_no_except: false, // TODO:
-        parameters: vec![],
-        prefix_modifiers: vec![],
-        suffix_modifiers: vec![],
-        template: None,
-    };
+    let is_constexpr = !f_type.is_static() || f_type.is_constant();
+    let getter_decl = field_getter_decl(
+        getter_name.clone(),
+        get_return_type,
+        false,
+        is_constexpr,
+        is_no_except,
+    );
+    let const_getter_decl = field_getter_decl(
+        getter_name,
+        const_get_return_type,
+        true,
+        is_constexpr,
+        is_no_except,
+    );
 
     let setter_decl = CppMethodDecl {
         cpp_name: setter_name,
         instance: true,
         return_type: "void".to_string(),
 
+        docs: None,
         brief: None,
         body: None,      //TODO:
         is_const: false, // TODO: readonly fields?
         is_constexpr: !f_type.is_static() || f_type.is_constant(),
         is_inline: true,
+        is_nodiscard: false,
         is_virtual: false,
         is_implicit_operator: false,
         is_explicit_operator: false,
 
-        is_no_except: false, // TODO:
+        is_no_except,
         parameters: vec![CppParam {
             def_value: None,
             modifiers: "".to_string(),
@@ -721,6 +781,17 @@ pub(crate) fn handle_referencetype_fields(
         return;
     }
 
+    // layout mirrors have no accessors, so the backing fields keep their names
+    if config.layout_only {
+        let fields = fields
+            .iter()
+            .map(|f| make_cpp_field_decl(cpp_type, f, name_resolver, config))
+            .collect_vec();
+
+        handle_instance_fields(cpp_type, &fields, metadata, tdi);
+        return;
+    }
+
     for field_info in fields.iter().filter(|f| f.instance) {
         // don't get a template that has no names
         let template = cpp_type
@@ -765,15 +836,28 @@ pub(crate) fn handle_referencetype_fields(
 }
 
 // inspired by what il2cpp does for explicitly laid out types
-pub(crate) fn pack_fields_into_single_union(fields: &[CppFieldDecl]) -> CppNestedUnion {
+pub(crate) fn pack_fields_into_single_union(
+    fields: &[CppFieldDecl],
+    is_value_type: bool,
+    packing_style: PackingStyle,
+) -> CppNestedUnion {
     // get the min offset to use as a base for the packed structs
     let min_offset = fields.iter().map(|f| f.offset.unwrap()).min().unwrap_or(0);
 
+    // the union is where the type's own fields start,
+    // which is 0 for value types and right after the parent for reference types.
+    // every field is padded from there on its own, so overlapping fields at different offsets
+    // each read at their true offset
+    let base_offset = match is_value_type {
+        true => 0,
+        false => min_offset,
+    };
+
     let packed_structs = fields
         .iter()
         .cloned()
         .map(|field| {
-            let structs = field_into_offset_structs(min_offset, field);
+            let structs = field_into_offset_structs(base_offset, field, packing_style);
 
             vec![structs.0, structs.1]
         })
@@ -793,9 +877,21 @@ pub(crate) fn pack_fields_into_single_union(fields: &[CppFieldDecl]) -> CppNeste
     }
 }
 
+/// Member names `field_into_offset_structs` emits for a field:
+/// the field, its padding, its alignment padding and its alignment copy
+fn offset_struct_member_names(field_name: &str) -> [String; 4] {
+    [
+        field_name.to_string(),
+        format!("{field_name}_padding"),
+        format!("{field_name}_padding_forAlignment"),
+        format!("{field_name}_forAlignment"),
+    ]
+}
+
 pub(crate) fn field_into_offset_structs(
-    _min_offset: u32,
+    base_offset: u32,
     field: CppFieldDecl,
+    packing_style: PackingStyle,
 ) -> (CppNestedStruct, CppNestedStruct) {
     // il2cpp basically turns each field into 2 structs within a union:
     // 1 which is packed with size 1, and padded with offset to fit to the end
@@ -805,14 +901,15 @@ pub(crate) fn field_into_offset_structs(
         panic!("don't call field_into_offset_structs with non instance fields!")
     };
 
-    let padding = actual_offset;
+    let padding = actual_offset - base_offset;
 
-    let packed_padding_cpp_name = format!("{}_padding[0x{padding:x}]", field.cpp_name);
-    let alignment_padding_cpp_name =
-        format!("{}_padding_forAlignment[0x{padding:x}]", field.cpp_name);
-    let alignment_cpp_name = format!("{}_forAlignment", field.cpp_name);
+    let [_, packed_padding_name, alignment_padding_name, alignment_cpp_name] =
+        offset_struct_member_names(&field.cpp_name);
+    let packed_padding_cpp_name = format!("{packed_padding_name}[0x{padding:x}]");
+    let alignment_padding_cpp_name = format!("{alignment_padding_name}[0x{padding:x}]");
 
     let packed_padding_field = CppFieldDecl {
+        docs: None,
         brief_comment: Some(format!("Padding field 0x{padding:x}")),
         const_expr: false,
         cpp_name: packed_padding_cpp_name,
@@ -825,6 +922,7 @@ pub(crate) fn field_into_offset_structs(
     };
 
     let alignment_padding_field = CppFieldDecl {
+        docs: None,
         brief_comment: Some(format!("Padding field 0x{padding:x} for alignment")),
         const_expr: false,
         cpp_name: alignment_padding_cpp_name,
@@ -847,32 +945,44 @@ pub(crate) fn field_into_offset_structs(
         ..field
     };
 
+    // zero sized arrays aren't standard C++, fields at the base need no padding
+    let paddings = match padding {
+        0 => (None, None),
+        _ => (Some(packed_padding_field), Some(alignment_padding_field)),
+    };
+
     let packed_struct = CppNestedStruct {
         declaring_name: "".into(),
         base_type: None,
-        declarations: vec![
-            CppMember::FieldDecl(packed_padding_field).into(),
-            CppMember::FieldDecl(packed_field).into(),
-        ],
+        declarations: paddings
+            .0
+            .into_iter()
+            .chain([packed_field])
+            .map(|f| CppMember::FieldDecl(f).into())
+            .collect(),
         brief_comment: None,
         is_class: false,
         is_enum: false,
         is_private: false,
         packing: Some(1),
+        packing_style,
     };
 
     let alignment_struct = CppNestedStruct {
         declaring_name: "".into(),
         base_type: None,
-        declarations: vec![
-            CppMember::FieldDecl(alignment_padding_field).into(),
-            CppMember::FieldDecl(alignment_field).into(),
-        ],
+        declarations: paddings
+            .1
+            .into_iter()
+            .chain([alignment_field])
+            .map(|f| CppMember::FieldDecl(f).into())
+            .collect(),
         brief_comment: None,
         is_class: false,
         is_enum: false,
         is_private: false,
         packing: None,
+        packing_style,
     };
 
     (packed_struct, alignment_struct)
@@ -902,10 +1012,117 @@ pub fn make_cpp_field_decl(
         instance: field.instance,
         readonly: field.readonly,
         const_expr: field.is_const,
-        value: field.value.as_ref().map(|v| v.to_string()),
+        value: field.value.as_ref().map(|v| v.to_cpp_string(config)),
+        docs: field.docs.clone(),
         brief_comment: field.brief_comment.clone(),
         is_private: false,
     };
 
     field_decl
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use crate::generate::writer::{Writable, Writer};
+
+    use super::{
+        field_getter_decl, pack_fields_into_single_union, CppFieldDecl, CppMember, PackingStyle,
+    };
+
+    fn field(name: &str, offset: u32) -> CppFieldDecl {
+        CppFieldDecl {
+            cpp_name: name.to_string(),
+            field_ty: "int32_t".to_string(),
+            offset: Some(offset),
+            instance: true,
+            readonly: false,
+            const_expr: false,
+            value: None,
+            docs: None,
+            brief_comment: None,
+            is_private: false,
+        }
+    }
+
+    /// Names of the fields in each struct of the union
+    fn union_member_names(fields: &[CppFieldDecl], is_value_type: bool) -> Vec<Vec<String>> {
+        pack_fields_into_single_union(fields, is_value_type, PackingStyle::Pragma)
+            .declarations
+            .iter()
+            .map(|d| {
+                let CppMember::NestedStruct(s) = d.as_ref() else {
+                    panic!("union member isn't a struct: {d:?}");
+                };
+
+                s.declarations
+                    .iter()
+                    .map(|m| match m.as_ref() {
+                        CppMember::FieldDecl(f) => f.cpp_name.clone(),
+                        m => panic!("struct member isn't a field: {m:?}"),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn value_type_union_pads_from_zero() {
+        let names = union_member_names(&[field("a", 0), field("b", 4)], true);
+
+        assert_eq!(names[0], ["a"]);
+        assert_eq!(names[1], ["a_forAlignment"]);
+        assert_eq!(names[2], ["b_padding[0x4]", "b"]);
+        assert_eq!(names[3], ["b_padding_forAlignment[0x4]", "b_forAlignment"]);
+    }
+
+    #[test]
+    fn reference_type_union_pads_from_first_field() {
+        let names = union_member_names(&[field("a", 0x10), field("b", 0x18)], false);
+
+        assert_eq!(names[0], ["a"]);
+        assert_eq!(names[2], ["b_padding[0x8]", "b"]);
+    }
+
+    #[test]
+    fn attribute_packs_offset_struct() -> color_eyre::Result<()> {
+        let union = pack_fields_into_single_union(&[field("a", 0)], true, PackingStyle::Attribute);
+
+        let path = std::env::temp_dir().join("cordl_packed_union_test.hpp");
+        let mut writer = Writer::new(File::create(&path)?, Default::default());
+        union.declarations[0].write(&mut writer)?;
+        drop(writer);
+
+        let written = std::fs::read_to_string(path)?;
+        assert!(written.contains("struct __attribute__((packed, aligned(1)))"));
+        assert!(!written.contains("#pragma pack"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn getters_are_nodiscard() -> color_eyre::Result<()> {
+        let getter = field_getter_decl(
+            "__cordl_internal_get_x".to_string(),
+            "int32_t const&".to_string(),
+            true,
+            true,
+            true,
+        );
+        assert!(getter.is_nodiscard);
+
+        let path = std::env::temp_dir().join("cordl_nodiscard_getter_test.hpp");
+        let mut writer = Writer::new(File::create(&path)?, Default::default());
+        getter.write(&mut writer)?;
+        drop(writer);
+
+        // attributes come before any other specifier
+        let written = std::fs::read_to_string(path)?;
+        assert!(
+            written.starts_with("[[nodiscard]] constexpr int32_t const& __cordl_internal_get_x()")
+        );
+
+        Ok(())
+    }
+}
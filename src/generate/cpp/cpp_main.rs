@@ -1,7 +1,7 @@
-use std::process::Command;
+use std::{path::Path, process::Command};
 
 use brocolib::{global_metadata::TypeDefinitionIndex, runtime_metadata::TypeData};
-use color_eyre::{eyre::Result, Section};
+use color_eyre::eyre::Result;
 use filesize::PathExt;
 use itertools::Itertools;
 use log::{error, info, warn};
@@ -11,7 +11,7 @@ use walkdir::DirEntry;
 use crate::{
     generate::{
         cpp::{
-            config::STATIC_CONFIG,
+            config::CppGenerationConfig,
             cpp_context_collection::CppContextCollection,
             cpp_members::CppMember,
             handlers::{object, unity, value_type},
@@ -25,10 +25,13 @@ use crate::{
 pub fn run_cpp(
     cs_collection: TypeContextCollection,
     metadata: &CordlMetadata,
+    config: &CppGenerationConfig,
     format: bool,
 ) -> color_eyre::Result<()> {
+    config.validate()?;
+
     let mut cpp_context_collection =
-        CppContextCollection::from_cs_collection(cs_collection, metadata, &STATIC_CONFIG);
+        CppContextCollection::from_cs_collection(cs_collection, metadata, config)?;
 
     info!("Registering handlers!");
     // il2cpp_internals::register_il2cpp_types(&mut metadata)?;
@@ -38,26 +41,38 @@ pub fn run_cpp(
 
     // let e = cpp_context_collection.cyclic_include_check()?;
 
-    if STATIC_CONFIG.header_path.exists() {
-        std::fs::remove_dir_all(&STATIC_CONFIG.header_path)?;
+    if config.header_path.exists() {
+        std::fs::remove_dir_all(&config.header_path)?;
     }
-    std::fs::create_dir_all(&STATIC_CONFIG.header_path)?;
+    std::fs::create_dir_all(&config.header_path)?;
 
     info!(
         "Copying config to codegen folder {:?}",
-        STATIC_CONFIG.dst_internals_path
+        config.dst_internals_path
     );
 
-    std::fs::create_dir_all(&STATIC_CONFIG.dst_internals_path)?;
+    std::fs::create_dir_all(&config.dst_internals_path)?;
 
     // extract contents of the cordl internals folder into destination
-    INTERNALS_DIR.extract(&STATIC_CONFIG.dst_internals_path)?;
+    INTERNALS_DIR.extract(&config.dst_internals_path)?;
+    cpp_context_collection.write_version_header(config, &metadata.version_marker())?;
+    cpp_context_collection.write_unity_object_header(config)?;
 
     const write_all: bool = true;
-    if write_all {
+    if config.amalgamate {
+        info!("Writing amalgamated header");
+        cpp_context_collection.write_amalgamation(config)?;
+    } else if write_all {
         info!("Writing all");
-        cpp_context_collection.write_all(&STATIC_CONFIG)?;
-        cpp_context_collection.write_namespace_headers()?;
+        // namespace headers first, writing the contexts drains the collection
+        cpp_context_collection.write_namespace_headers(config, metadata.metadata_version)?;
+        if !config.compat_shims.is_empty() {
+            cpp_context_collection.write_compat_shims(config)?;
+        }
+        if config.write_manifest {
+            cpp_context_collection.write_manifest(config)?;
+        }
+        cpp_context_collection.write_all(config, &metadata.timings)?;
     } else {
         // for t in &metadata.type_definitions {
         //     // Handle the generation for a single type
@@ -89,7 +104,7 @@ pub fn run_cpp(
             .find(|(_, c)| c.get_types().iter().any(|(_, t)| t.cpp_template.is_some()))
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("List Generic type");
         cpp_context_collection
             .get()
@@ -101,7 +116,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("Value type");
         cpp_context_collection
             .get()
@@ -113,7 +128,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         // info!("Nested type");
         // cpp_context_collection
         //     .get()
@@ -152,7 +167,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("Default param");
         cpp_context_collection
             .get()
@@ -171,7 +186,7 @@ pub fn run_cpp(
             .nth(2)
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("Enum type");
         cpp_context_collection
             .get()
@@ -179,7 +194,7 @@ pub fn run_cpp(
             .find(|(_, c)| c.get_types().iter().any(|(_, t)| t.is_enum_type))
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("UnityEngine.Object");
         cpp_context_collection
             .get()
@@ -191,7 +206,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("BeatmapSaveDataHelpers");
         cpp_context_collection
             .get()
@@ -203,7 +218,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("HMUI.ViewController");
         cpp_context_collection
             .get()
@@ -215,7 +230,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("UnityEngine.Component");
         cpp_context_collection
             .get()
@@ -227,7 +242,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("UnityEngine.GameObject");
         cpp_context_collection
             .get()
@@ -239,7 +254,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("MainFlowCoordinator");
         cpp_context_collection
             .get()
@@ -251,7 +266,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("OVRPlugin");
         cpp_context_collection
             .get()
@@ -263,7 +278,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("HMUI.IValueChanger");
         cpp_context_collection
             .get()
@@ -275,7 +290,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.ValueType");
         cpp_context_collection
             .get()
@@ -287,7 +302,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.ValueTuple_2");
         cpp_context_collection
             .get()
@@ -299,7 +314,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Decimal");
         cpp_context_collection
             .get()
@@ -311,7 +326,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Enum");
         cpp_context_collection
             .get()
@@ -323,7 +338,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Multicast");
         cpp_context_collection
             .get()
@@ -335,7 +350,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Delegate");
         cpp_context_collection
             .get()
@@ -347,7 +362,7 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("BeatmapSaveDataVersion3.BeatmapSaveData.EventBoxGroup`1");
         cpp_context_collection
             .get()
@@ -359,25 +374,30 @@ pub fn run_cpp(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         // for (_, context) in cpp_context_collection.get() {
         //     context.write().unwrap();
         // }
     }
 
     if format {
-        format_files()?;
+        format_files(config)?;
     }
 
     Ok(())
 }
 
-fn format_files() -> color_eyre::Result<()> {
+fn format_files(config: &CppGenerationConfig) -> color_eyre::Result<()> {
     info!("Formatting!");
 
+    let clang_format = config
+        .clang_format
+        .as_deref()
+        .unwrap_or(Path::new("clang-format"));
+
     use walkdir::WalkDir;
 
-    let files: Vec<DirEntry> = WalkDir::new(&STATIC_CONFIG.header_path)
+    let files: Vec<DirEntry> = WalkDir::new(&config.header_path)
         .into_iter()
         .filter(|f| f.as_ref().is_ok_and(|f| f.path().is_file()))
         .try_collect()?;
@@ -415,22 +435,32 @@ fn format_files() -> color_eyre::Result<()> {
                 file_num + 1,
                 path.display()
             );
-            let mut command = Command::new("clang-format");
+            let mut command = Command::new(clang_format);
             command.arg("-i").arg(path);
 
-            let spawn = command
-                .output()
-                .suggestion("You may be missing clang-format. Ensure it is on PATH")?;
+            // the headers are usable unformatted, so a failure only skips this file
+            let spawn = match command.output() {
+                Ok(spawn) => spawn,
+                Err(e) => {
+                    error!(
+                        "Unable to run {} on {}: {e}. Ensure it is on PATH",
+                        clang_format.display(),
+                        path.display()
+                    );
+                    return Ok(());
+                }
+            };
 
             if !spawn.stderr.is_empty() {
                 error!(
                     "Error {} {}",
                     path.display(),
-                    String::from_utf8(spawn.stderr)?
+                    String::from_utf8_lossy(&spawn.stderr)
                 );
             }
-
-            spawn.status.exit_ok()?;
+            if !spawn.status.success() {
+                error!("Failed to format {}: {}", path.display(), spawn.status);
+            }
 
             Ok(())
         })?;
@@ -1,3 +1,4 @@
+use color_eyre::eyre::ContextCompat;
 use itertools::Itertools;
 use pathdiff::diff_paths;
 
@@ -16,7 +17,7 @@ use std::{
 };
 
 use super::{
-    config::STATIC_CONFIG,
+    config::{CppGenerationConfig, PackingStyle},
     cpp_context::CppContext,
     cpp_type::{CppType, CORDL_REFERENCE_TYPE_CONSTRAINT},
 };
@@ -103,9 +104,9 @@ impl CppLine {
 pub trait WritableDebug: Writable + Debug {}
 impl<T: Writable + Debug> WritableDebug for T {}
 
+/// Forward declares sharing a namespace, written in a single `namespace X { ... }` block
 #[derive(Debug, Eq, Hash, PartialEq, Clone)]
 pub struct CppForwardDeclareGroup {
-    // TODO: Make this group lots into a single namespace
     pub namespace: Option<String>,
     pub items: Vec<CppForwardDeclare>,
     pub group_items: Vec<CppForwardDeclareGroup>,
@@ -204,6 +205,8 @@ pub struct CppFieldDecl {
     pub readonly: bool,
     pub const_expr: bool,
     pub value: Option<String>,
+    /// Written instead of `brief_comment` if present
+    pub docs: Option<String>,
     pub brief_comment: Option<String>,
     pub is_private: bool,
 }
@@ -239,8 +242,8 @@ pub struct CppPropertyDecl {
     pub instance: bool,
     pub getter: Option<String>,
     pub setter: Option<String>,
-    /// Whether this property is one that's indexable (accessor methods take an index argument)
-    pub indexable: bool,
+    /// Number of index arguments the accessor methods take, 0 if not indexable
+    pub index_count: usize,
     pub brief_comment: Option<String>,
 }
 
@@ -283,11 +286,16 @@ pub struct CppMethodDecl {
     pub is_virtual: bool,
     pub is_constexpr: bool,
     pub is_const: bool,
+    /// Only for members that never call into il2cpp, which can throw managed exceptions
     pub is_no_except: bool,
     pub is_implicit_operator: bool,
     pub is_explicit_operator: bool,
     pub is_inline: bool,
+    /// emit `[[nodiscard]]`
+    pub is_nodiscard: bool,
 
+    /// Written instead of `brief` if present
+    pub docs: Option<String>,
     pub brief: Option<String>,
     pub body: Option<Vec<Arc<dyn WritableDebug>>>,
 }
@@ -307,6 +315,7 @@ impl PartialEq for CppMethodDecl {
             && self.is_no_except == other.is_no_except
             && self.is_implicit_operator == other.is_implicit_operator
             && self.is_inline == other.is_inline
+            && self.docs == other.docs
             && self.brief == other.brief
             // can't gurantee body is equal
             && self.body.is_some() == other.body.is_some()
@@ -575,6 +584,7 @@ pub struct CppNestedStruct {
     pub is_private: bool,
     pub brief_comment: Option<String>,
     pub packing: Option<u8>,
+    pub packing_style: PackingStyle,
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
@@ -602,6 +612,29 @@ impl From<CppConstructorDecl> for CppConstructorImpl {
     }
 }
 
+impl CppForwardDeclareGroup {
+    /// Groups forward declares by namespace, keeping their order within a namespace
+    /// so generic definitions stay ahead of their specializations
+    pub fn group(forward_declares: impl IntoIterator<Item = CppForwardDeclare>) -> Vec<Self> {
+        forward_declares
+            .into_iter()
+            .enumerate()
+            .sorted_by(|(a_i, a), (b_i, b)| {
+                a.cpp_namespace
+                    .cmp(&b.cpp_namespace)
+                    .then_with(|| a_i.cmp(b_i))
+            })
+            .chunk_by(|(_, fd)| fd.cpp_namespace.clone())
+            .into_iter()
+            .map(|(namespace, items)| Self {
+                namespace,
+                items: items.map(|(_, fd)| fd).collect(),
+                group_items: vec![],
+            })
+            .collect()
+    }
+}
+
 impl CppForwardDeclare {
     pub fn from_cpp_type(cpp_type: &CppType) -> Self {
         Self::from_cpp_type_long(cpp_type, false)
@@ -617,7 +650,7 @@ impl CppForwardDeclare {
         // );
 
         // literals should only be added for generic specializations
-        let literals = if force_generics {
+        let literals = if force_generics || cpp_type.generic_instantiations_args_types.is_some() {
             cpp_type.cpp_name_components.generics.clone()
         } else {
             None
@@ -660,25 +693,41 @@ impl CppParam {
 }
 
 impl CppInclude {
-    // smelly use of config but whatever
-    pub fn new_context_typedef(context: &CppContext) -> Self {
+    pub fn new_context_typedef(context: &CppContext, config: &CppGenerationConfig) -> Self {
         Self {
-            include: diff_paths(&context.typedef_path, &STATIC_CONFIG.header_path).unwrap(),
+            include: context.typedef_include.clone(),
             system: false,
         }
     }
-    pub fn new_context_typeimpl(context: &CppContext) -> Self {
+    pub fn new_context_typeimpl(context: &CppContext, config: &CppGenerationConfig) -> Self {
         Self {
-            include: diff_paths(&context.type_impl_path, &STATIC_CONFIG.header_path).unwrap(),
+            include: context.type_impl_include.clone(),
             system: false,
         }
     }
-    pub fn new_context_fundamental(context: &CppContext) -> Self {
+    pub fn new_context_fundamental(context: &CppContext, config: &CppGenerationConfig) -> Self {
         Self {
-            include: diff_paths(&context.fundamental_path, &STATIC_CONFIG.header_path).unwrap(),
+            include: context.fundamental_include.clone(),
             system: false,
         }
     }
+    pub fn new_context_generics(context: &CppContext, config: &CppGenerationConfig) -> Self {
+        Self {
+            include: context.generics_include.clone(),
+            system: false,
+        }
+    }
+
+    /// Make `path` relative to the include root, used for non-system includes
+    pub fn relative_to_root(path: &Path, include_root: &Path) -> color_eyre::Result<PathBuf> {
+        diff_paths(path, include_root).with_context(|| {
+            format!(
+                "Unable to make include {} relative to {}",
+                path.display(),
+                include_root.display()
+            )
+        })
+    }
 
     pub fn new_system<P: AsRef<Path>>(str: P) -> Self {
         Self {
@@ -3,13 +3,36 @@ use std::io::Write;
 
 use crate::generate::writer::{SortLevel, Sortable, Writable, Writer};
 
+use super::config::PackingStyle;
 use super::cpp_members::{
     CppCommentedString, CppConstructorDecl, CppConstructorImpl, CppFieldDecl, CppFieldImpl,
-    CppForwardDeclare, CppInclude, CppLine, CppMember, CppMethodDecl, CppMethodImpl,
-    CppMethodSizeStruct, CppNestedStruct, CppNestedUnion, CppNonMember, CppParam, CppPropertyDecl,
-    CppStaticAssert, CppTemplate, CppUsingAlias,
+    CppForwardDeclare, CppForwardDeclareGroup, CppInclude, CppLine, CppMember, CppMethodDecl,
+    CppMethodImpl, CppMethodSizeStruct, CppNestedStruct, CppNestedUnion, CppNonMember, CppParam,
+    CppPropertyDecl, CppStaticAssert, CppTemplate, CppUsingAlias,
 };
 
+/// Doxygen block comment for documentation taken from the xml docs
+pub(crate) fn write_docs(writer: &mut Writer, docs: &str) -> color_eyre::Result<()> {
+    writeln!(writer, "/**")?;
+    for line in docs.lines() {
+        // don't let the docs close the comment early
+        writeln!(writer, " * {}", line.replace("*/", "* /"))?;
+    }
+    writeln!(writer, " */")?;
+    Ok(())
+}
+
+/// `[[deprecated]]` with `message` as its reason, if any
+pub(crate) fn deprecated_attribute(message: &str) -> String {
+    match message.is_empty() {
+        true => "[[deprecated]]".to_string(),
+        false => format!(
+            "[[deprecated(\"{}\")]]",
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        ),
+    }
+}
+
 impl Writable for CppTemplate {
     fn write(&self, writer: &mut Writer) -> color_eyre::Result<()> {
         writeln!(
@@ -26,12 +49,46 @@ impl Writable for CppTemplate {
     }
 }
 
+impl Writable for CppForwardDeclareGroup {
+    fn write(&self, writer: &mut Writer) -> color_eyre::Result<()> {
+        if let Some(namespace) = &self.namespace {
+            writeln!(writer, "namespace {namespace} {{")?;
+        }
+
+        for item in &self.items {
+            item.write_declaration(writer)?;
+        }
+        for group in &self.group_items {
+            group.write(writer)?;
+        }
+
+        if self.namespace.is_some() {
+            writeln!(writer, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Writable for CppForwardDeclare {
     fn write(&self, writer: &mut Writer) -> color_eyre::Result<()> {
         if let Some(namespace) = &self.cpp_namespace {
             writeln!(writer, "namespace {namespace} {{")?;
         }
 
+        self.write_declaration(writer)?;
+
+        if self.cpp_namespace.is_some() {
+            writeln!(writer, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CppForwardDeclare {
+    /// The declaration without its namespace
+    fn write_declaration(&self, writer: &mut Writer) -> color_eyre::Result<()> {
         if let Some(templates) = &self.templates {
             templates.write(writer)?;
         }
@@ -58,10 +115,6 @@ impl Writable for CppForwardDeclare {
             }
         )?;
 
-        if self.cpp_namespace.is_some() {
-            writeln!(writer, "}}")?;
-        }
-
         Ok(())
     }
 }
@@ -117,7 +170,9 @@ impl Sortable for CppUsingAlias {
 
 impl Writable for CppFieldDecl {
     fn write(&self, writer: &mut Writer) -> color_eyre::Result<()> {
-        if let Some(comment) = &self.brief_comment {
+        if let Some(docs) = &self.docs {
+            write_docs(writer, docs)?;
+        } else if let Some(comment) = &self.brief_comment {
             writeln!(writer, "/// @brief {comment}")?;
         }
 
@@ -204,7 +259,9 @@ impl Sortable for CppFieldImpl {
 impl Writable for CppMethodDecl {
     // declaration
     fn write(&self, writer: &mut Writer) -> color_eyre::Result<()> {
-        if let Some(brief) = &self.brief {
+        if let Some(docs) = &self.docs {
+            write_docs(writer, docs)?;
+        } else if let Some(brief) = &self.brief {
             writeln!(writer, "/// @brief {brief}")?;
         }
 
@@ -240,6 +297,11 @@ impl Writable for CppMethodDecl {
             .map(|s| s.as_str())
             .collect_vec();
 
+        // attributes must come before any other specifier
+        if self.is_nodiscard {
+            prefix_modifiers.insert(0, "[[nodiscard]]");
+        }
+
         if !self.instance {
             prefix_modifiers.push("static");
         }
@@ -573,10 +635,8 @@ impl Writable for CppPropertyDecl {
         let suffixes = suffix_modifiers.join(" ");
 
         // i.e. list->get_Item(int32_t index) takes an index argument, this way you can go list->Item[t]
-        let brackets = match self.indexable {
-            true => "[]",
-            false => "",
-        };
+        // one pair per index, e.g. grid->Item[x][y] for get_Item(int32_t x, int32_t y)
+        let brackets = "[]".repeat(self.index_count);
 
         if let Some(comment) = &self.brief_comment {
             writeln!(writer, "/// @brief {comment}")?;
@@ -696,16 +756,22 @@ impl Writable for CppNestedStruct {
             writeln!(writer, "/// @brief {brief}")?;
         }
 
-        if let Some(packing) = self.packing {
-            writeln!(writer, "#pragma pack(push, tp, {packing})")?;
-        }
-
         let mut struct_declaration = match self.is_class {
             true => "class",
             false => "struct",
         }
         .to_string();
 
+        match (self.packing, self.packing_style) {
+            (Some(packing), PackingStyle::Pragma) => {
+                writeln!(writer, "#pragma pack(push, tp, {packing})")?
+            }
+            (Some(packing), PackingStyle::Attribute) => {
+                struct_declaration += &format!(" __attribute__((packed, aligned({packing})))")
+            }
+            (None, _) => (),
+        }
+
         let mut base_type_fixed = self.base_type.clone().map(|s| format!("public {s}"));
         if self.is_enum {
             base_type_fixed = self.base_type.clone();
@@ -724,7 +790,7 @@ impl Writable for CppNestedStruct {
         self.declarations.iter().try_for_each(|d| d.write(writer))?;
 
         writeln!(writer, "}};")?;
-        if self.packing.is_some() {
+        if self.packing.is_some() && self.packing_style == PackingStyle::Pragma {
             writeln!(writer, "#pragma pack(pop, tp)")?;
         }
         if self.is_private {
@@ -13,19 +13,20 @@ impl CppNameComponents {
     // TODO: Add setting for adding :: prefix
     // however, this cannot be allowed in all cases
     pub fn combine_all(&self) -> String {
-        let combined_declaring_types = self.declaring_types.as_ref().map(|d| d.join("::"));
-
         // will be empty if no namespace or declaring types
-        let prefix = combined_declaring_types
+        let prefix = self
+            .declaring_types
             .as_ref()
-            .or(self.namespace.as_ref())
+            .map(|d| d.join("::"))
+            .or(self.namespace.clone())
             .map(|s| {
-                if s.is_empty() {
-                    "::".to_string()
-                } else {
-                    format!("::{s}::")
-                }
+                // collapse empty segments so we never emit `::::` or a dangling `::`
+                s.split("::")
+                    .filter(|segment| !segment.is_empty())
+                    .fold(String::new(), |acc, segment| format!("{acc}::{segment}"))
             })
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("{s}::"))
             .unwrap_or_default();
 
         let mut completed = format!("{prefix}{}", self.name);
@@ -3,10 +3,14 @@ use itertools::Itertools;
 
 use crate::{
     data::type_resolver::{ResolvedType, ResolvedTypeData, TypeUsage},
-    generate::{metadata::CordlMetadata, type_extensions::TypeDefinitionExtensions},
+    generate::{
+        metadata::CordlMetadata,
+        type_extensions::{GenericParameterExtensions, TypeDefinitionExtensions},
+    },
 };
 
 use super::{
+    config::CppGenerationConfig,
     cpp_context_collection::CppContextCollection,
     cpp_members::{CppForwardDeclare, CppInclude},
     cpp_name_components::CppNameComponents,
@@ -22,6 +26,7 @@ pub const IL2CPP_OBJECT_TYPE: &str = "Il2CppObject";
 pub struct CppNameResolver<'a, 'b> {
     pub cordl_metadata: &'a CordlMetadata<'b>,
     pub collection: &'a CppContextCollection,
+    pub config: &'a CppGenerationConfig,
 }
 
 impl<'b> CppNameResolver<'_, 'b> {
@@ -33,6 +38,38 @@ impl<'b> CppNameResolver<'_, 'b> {
         hard_include: bool,
     ) -> CppNameComponents {
         let metadata = self.cordl_metadata;
+
+        // layout mirrors only need the pointer, not the pointee
+        if type_usage == TypeUsage::Field
+            && self.config.layout_only
+            && !metadata.metadata_registration.types[ty.ty].valuetype
+            && !matches!(
+                ty.data,
+                ResolvedTypeData::GenericArg(..) | ResolvedTypeData::GenericMethodArg(..)
+            )
+        {
+            return CppNameComponents {
+                name: "void".into(),
+                is_pointer: true,
+                ..Default::default()
+            };
+        }
+
+        // ref structs point into memory they don't own next to a length,
+        // e.g. `Span<T>` holds a `ref T` or `ByReference<T>`, so mirror it as a raw `T*`
+        if type_usage == TypeUsage::Field
+            && declaring_cpp_type.is_byref_like
+            && let Some(pointee) = self.interior_pointee(ty)
+        {
+            let pointee = self.resolve_name(declaring_cpp_type, pointee, type_usage, false);
+
+            return CppNameComponents {
+                name: pointee.combine_all(),
+                is_pointer: true,
+                ..Default::default()
+            };
+        }
+
         match &ty.data {
             ResolvedTypeData::Array(array_type) => {
                 let generic =
@@ -87,11 +124,21 @@ impl<'b> CppNameResolver<'_, 'b> {
                 //     .get(&method_index)
                 //     .and_then(|v| v.get(method_arg as usize));
 
-                generic_param.name(metadata.metadata).to_string().into()
+                generic_param.unshadowed_name(metadata.metadata).into()
             }
             ResolvedTypeData::Ptr(resolved_type) => {
                 let generic_formatted =
                     self.resolve_name(declaring_cpp_type, resolved_type, type_usage, hard_include);
+
+                // fields keep the exact layout of the C# pointer, e.g. `void*` or `int32_t*`
+                if type_usage == TypeUsage::Field && self.config.raw_field_pointers {
+                    return CppNameComponents {
+                        name: generic_formatted.combine_all(),
+                        is_pointer: true,
+                        ..Default::default()
+                    };
+                }
+
                 CppNameComponents {
                     namespace: Some("cordl_internals".into()),
                     generics: Some(vec![generic_formatted.combine_all()]),
@@ -139,6 +186,10 @@ impl<'b> CppNameResolver<'_, 'b> {
                     _ => (),
                 };
 
+                if let Some(cpp_name) = self.config.primitive_override(*il2_cpp_type_enum) {
+                    return CppNameComponents::from(cpp_name.to_string());
+                }
+
                 let s: String = match il2_cpp_type_enum {
                     Il2CppTypeEnum::I1 => "int8_t".to_string(),
                     Il2CppTypeEnum::I2 => "int16_t".to_string(),
@@ -148,6 +199,8 @@ impl<'b> CppNameResolver<'_, 'b> {
                     Il2CppTypeEnum::U2 => "uint16_t".to_string(),
                     Il2CppTypeEnum::U4 => "uint32_t".to_string(),
                     Il2CppTypeEnum::U8 => "uint64_t".to_string(),
+                    Il2CppTypeEnum::I => "intptr_t".to_string(),
+                    Il2CppTypeEnum::U => "uintptr_t".to_string(),
 
                     Il2CppTypeEnum::R4 => "float_t".to_string(),
                     Il2CppTypeEnum::R8 => "double_t".to_string(),
@@ -199,6 +252,28 @@ impl<'b> CppNameResolver<'_, 'b> {
         }
     }
 
+    /// Pointee of a ref struct's interior pointer, i.e. of `ref T` or `ByReference<T>`
+    fn interior_pointee<'t>(&self, ty: &'t ResolvedType) -> Option<&'t ResolvedType> {
+        let metadata = self.cordl_metadata.metadata;
+
+        match &ty.data {
+            ResolvedTypeData::ByRef(pointee) | ResolvedTypeData::ByRefConst(pointee) => {
+                Some(pointee)
+            }
+            ResolvedTypeData::GenericInst(generic, args) => {
+                let ResolvedTypeData::Type(tag) = &generic.data else {
+                    return None;
+                };
+                let td = &metadata.global_metadata.type_definitions[tag.get_tdi()];
+                let is_by_reference =
+                    td.namespace(metadata) == "System" && td.name(metadata) == "ByReference`1";
+
+                args.first().map(|(arg, _)| arg).filter(|_| is_by_reference)
+            }
+            _ => None,
+        }
+    }
+
     fn resolve_type(
         &self,
         resolved_tag: &crate::generate::cs_type_tag::CsTypeTag,
@@ -210,6 +285,24 @@ impl<'b> CppNameResolver<'_, 'b> {
         if *resolved_tag == declaring_cpp_type.self_tag {
             return self.resolve_redirect(declaring_cpp_type, type_usage);
         }
+
+        // hand written types replace the generated one entirely
+        let td = &metadata.metadata.global_metadata.type_definitions[resolved_tag.get_tdi()];
+        if let Some(type_override) = self
+            .config
+            .type_override(&td.get_name_components(metadata.metadata))
+        {
+            declaring_cpp_type
+                .requirements
+                .add_def_include(None, CppInclude::new_exact(&type_override.include));
+
+            return CppNameComponents {
+                name: type_override.cpp_name.clone(),
+                is_pointer: !td.is_value_type(),
+                ..Default::default()
+            };
+        }
+
         let resolved_context_root_tag = self.collection.get_context_root_tag(*resolved_tag);
         let self_context_root_tag = self
             .collection
@@ -243,20 +336,26 @@ impl<'b> CppNameResolver<'_, 'b> {
             match hard_include {
                 // can add include
                 true => {
-                    declaring_cpp_type.requirements.add_def_include(
-                        Some(incl_ty),
-                        CppInclude::new_context_typedef(incl_context),
-                    );
+                    // closed generics are defined in the generics header, which includes the typedef
+                    let def_include = match incl_ty.generic_instantiations_args_types.is_some()
+                        && self.config.split_generic_instantiations
+                    {
+                        true => CppInclude::new_context_generics(incl_context, self.config),
+                        false => CppInclude::new_context_typedef(incl_context, self.config),
+                    };
+                    declaring_cpp_type
+                        .requirements
+                        .add_def_include(Some(incl_ty), def_include);
                     declaring_cpp_type.requirements.add_impl_include(
                         Some(incl_ty),
-                        CppInclude::new_context_typeimpl(incl_context),
+                        CppInclude::new_context_typeimpl(incl_context, self.config),
                     );
                 }
                 // add forward declare
                 false => {
                     declaring_cpp_type.requirements.add_forward_declare((
                         CppForwardDeclare::from_cpp_type(incl_ty),
-                        CppInclude::new_context_typedef(incl_context),
+                        CppInclude::new_context_typedef(incl_context, self.config),
                     ));
                 }
             }
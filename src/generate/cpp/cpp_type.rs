@@ -5,7 +5,10 @@ use std::{
     usize,
 };
 
-use brocolib::global_metadata::{FieldIndex, MethodIndex, TypeDefinitionIndex};
+use brocolib::{
+    global_metadata::{FieldIndex, MethodIndex, TypeDefinitionIndex},
+    runtime_metadata::Il2CppTypeEnum,
+};
 use color_eyre::eyre::Context;
 use itertools::Itertools;
 
@@ -19,11 +22,12 @@ use crate::{
     generate::{
         cpp::cpp_members::{CppMethodSizeStruct, CppStaticAssert},
         cs_members::{
-            CSMethodFlags, CsConstructor, CsField, CsMethod, CsParam, CsProperty, CsValue,
+            CSMethodFlags, CsConstructor, CsField, CsMethod, CsParam, CsParamFlags, CsProperty,
+            CsValue,
         },
         cs_type::CsType,
         cs_type_tag::CsTypeTag,
-        metadata::CordlMetadata,
+        metadata::{CordlMetadata, PointerSize},
         offsets::SizeInfo,
         type_extensions::{
             TypeDefinitionExtensions, TypeDefinitionIndexExtensions, TypeExtentions,
@@ -33,15 +37,16 @@ use crate::{
 };
 
 use super::{
-    config::CppGenerationConfig,
+    config::{CppGenerationConfig, PackingStyle},
     cpp_fields,
     cpp_members::{
         CppConstructorDecl, CppConstructorImpl, CppFieldDecl, CppForwardDeclare, CppInclude,
         CppLine, CppMember, CppMethodData, CppMethodDecl, CppMethodImpl, CppNestedStruct,
         CppNonMember, CppParam, CppPropertyDecl, CppTemplate, CppUsingAlias, WritableDebug,
     },
+    cpp_members_serialize::{deprecated_attribute, write_docs},
     cpp_name_components::CppNameComponents,
-    cpp_name_resolver::{CppNameResolver, VALUE_WRAPPER_TYPE},
+    cpp_name_resolver::CppNameResolver,
 };
 
 pub const CORDL_TYPE_MACRO: &str = "CORDL_TYPE";
@@ -62,6 +67,8 @@ pub const REFERENCE_WRAPPER_INSTANCE_NAME: &str = "::bs_hook::Il2CppWrapperType:
 
 pub const CORDL_NO_INCLUDE_IMPL_DEFINE: &str = "CORDL_NO_IMPL_INCLUDE";
 pub const CORDL_ACCESSOR_FIELD_PREFIX: &str = "___";
+/// Static member function returning the class, see `CppGenerationConfig::cached_class`
+pub const CACHED_CLASS_FN: &str = "__cordl_cached_class";
 
 pub const ENUM_PTR_TYPE: &str = "::bs_hook::EnumPtr";
 pub const VT_PTR_TYPE: &str = "::bs_hook::VTPtr";
@@ -175,6 +182,8 @@ pub struct CppType {
     pub is_enum_type: bool,
     pub is_reference_type: bool,
     pub is_interface: bool,
+    pub is_abstract: bool,
+    pub is_byref_like: bool,
 
     pub requirements: CppTypeRequirements,
     pub self_tag: CsTypeTag,
@@ -187,7 +196,14 @@ pub struct CppType {
     pub cs_name_components: NameComponents,
     pub cpp_name_components: CppNameComponents,
     pub(crate) prefix_comments: Vec<String>,
+    /// Summary from the xml documentation
+    pub docs: Option<String>,
+    /// Written as `[[deprecated]]`, see `CsType::obsolete`
+    pub deprecated: Option<String>,
     pub packing: Option<u32>,
+    pub packing_style: PackingStyle,
+    /// `alignas` for value types il2cpp aligns stricter than their fields would, e.g. SIMD types
+    pub alignment: Option<u32>,
     pub size_info: Option<SizeInfo>,
 }
 
@@ -234,14 +250,19 @@ impl CppType {
             writer.indent();
         }
 
+        let is_specialization = self.generic_instantiations_args_types.is_some();
+
         // Write type definition
         if let Some(generic_args) = &self.cpp_template {
             writeln!(writer, "// cpp template")?;
             generic_args.write(writer)?;
+        } else if is_specialization {
+            writeln!(writer, "// cpp template specialization")?;
+            writeln!(writer, "template<>")?;
         }
         writeln!(writer, "// Is value type: {}", self.is_value_type)?;
 
-        let clazz_name = self.cpp_name_components.formatted_name(false);
+        let clazz_name = self.cpp_name_components.formatted_name(is_specialization);
 
         writeln!(
             writer,
@@ -249,16 +270,41 @@ impl CppType {
             self.cs_name_components.combine_all()
         )?;
 
-        if let Some(packing) = &self.packing {
-            writeln!(writer, "#pragma pack(push, {packing})")?;
+        if let Some(docs) = &self.docs {
+            write_docs(writer, docs)?;
         }
 
+        let deprecated = self
+            .deprecated
+            .as_deref()
+            .map(|message| format!("{} ", deprecated_attribute(message)))
+            .unwrap_or_default();
+
+        let alignas = self
+            .alignment
+            .map(|alignment| format!("alignas({alignment}) "))
+            .unwrap_or_default();
+
+        let packed = match (&self.packing, self.packing_style) {
+            (Some(packing), PackingStyle::Pragma) => {
+                writeln!(writer, "#pragma pack(push, {packing})")?;
+                String::new()
+            }
+            (Some(packing), PackingStyle::Attribute) => {
+                format!("__attribute__((packed, aligned({packing}))) ")
+            }
+            (None, _) => String::new(),
+        };
+
         let inherits = self.get_inherits().collect_vec();
         match inherits.is_empty() {
-            true => writeln!(writer, "{type_kind} {CORDL_TYPE_MACRO} {clazz_name} {{")?,
+            true => writeln!(
+                writer,
+                "{type_kind} {deprecated}{alignas}{packed}{CORDL_TYPE_MACRO} {clazz_name} {{"
+            )?,
             false => writeln!(
                 writer,
-                "{type_kind} {CORDL_TYPE_MACRO} {clazz_name} : {} {{",
+                "{type_kind} {deprecated}{alignas}{packed}{CORDL_TYPE_MACRO} {clazz_name} : {} {{",
                 inherits
                     .into_iter()
                     .map(|s| format!("public {s}"))
@@ -309,7 +355,7 @@ impl CppType {
         writer.dedent();
         writeln!(writer, "}};")?;
 
-        if self.packing.is_some() {
+        if self.packing.is_some() && self.packing_style == PackingStyle::Pragma {
             writeln!(writer, "#pragma pack(pop)")?;
         }
 
@@ -335,38 +381,43 @@ impl CppType {
     }
 
     pub fn write_type_trait(&self, writer: &mut Writer) -> color_eyre::Result<()> {
+        let is_value_type = self.is_enum_type || self.is_value_type;
+
         if self.cpp_template.is_some() {
             // generic
             // macros from bs hook
-            let type_trait_macro = if self.is_enum_type || self.is_value_type {
+            let type_trait_macro = if is_value_type {
                 "MARK_GEN_VAL_T"
             } else {
                 "MARK_GEN_REF_PTR_T"
             };
-
-            writeln!(
-                writer,
-                "{type_trait_macro}({});",
-                self.cpp_name_components
-                    .clone()
-                    .remove_generics()
-                    .remove_pointer()
-                    .combine_all()
-            )?;
+            let name = self
+                .cpp_name_components
+                .clone()
+                .remove_generics()
+                .remove_pointer()
+                .combine_all();
+
+            writeln!(writer, "{type_trait_macro}({name});")?;
+            // macro from cordl_internals/concepts.hpp, reference types are false by default
+            if is_value_type {
+                writeln!(writer, "CORDL_MARK_GEN_VAL_T({name});")?;
+            }
         } else {
             // non-generic
             // macros from bs hook
-            let type_trait_macro = if self.is_enum_type || self.is_value_type {
+            let type_trait_macro = if is_value_type {
                 "MARK_VAL_T"
             } else {
                 "MARK_REF_PTR_T"
             };
+            let name = self.cpp_name_components.remove_pointer().combine_all();
 
-            writeln!(
-                writer,
-                "{type_trait_macro}({});",
-                self.cpp_name_components.remove_pointer().combine_all()
-            )?;
+            writeln!(writer, "{type_trait_macro}({name});")?;
+            // macro from cordl_internals/concepts.hpp, reference types are false by default
+            if is_value_type {
+                writeln!(writer, "CORDL_MARK_VAL_T({name});")?;
+            }
         }
 
         Ok(())
@@ -398,6 +449,7 @@ impl CppType {
         };
 
         let generic_instantiations_args_types = cs_type.generic_instantiations_args_types.clone();
+        let is_specialization = generic_instantiations_args_types.is_some();
         let method_generic_instantiation_map = cs_type.method_generic_instantiation_map.clone();
 
         CppType {
@@ -424,13 +476,23 @@ impl CppType {
             generic_instantiations_args_types,
             method_generic_instantiation_map,
 
-            cpp_template: cs_type.generic_template.clone().map(|t| t.into()),
+            // specializations are written as `template<>` instead
+            cpp_template: match is_specialization {
+                true => None,
+                false => cs_type.generic_template.clone().map(|t| t.into()),
+            },
             cpp_name_components, // TODO
             cs_name_components: cs_type.cs_name_components.clone(),
             prefix_comments: vec![],
+            docs: cs_type.docs.clone(),
+            deprecated: cs_type.obsolete.clone(),
             packing: cs_type.packing.map(|p| p as u32),
+            packing_style: config.packing_style,
+            alignment: None,
             size_info: cs_type.size_info.clone(),
             is_interface: cs_type.is_interface,
+            is_abstract: cs_type.is_abstract,
+            is_byref_like: cs_type.is_byref_like,
         }
     }
 
@@ -449,7 +511,7 @@ impl CppType {
         let mut declaring_td = declaring_tag
             .get_tdi()
             .get_type_definition(metadata.metadata);
-        let mut declaring_name = declaring_td.get_name_components(metadata.metadata).name;
+        let mut declaring_names = vec![declaring_td.get_name_components(metadata.metadata).name];
 
         while declaring_td.declaring_type_index != u32::MAX {
             let declaring_ty =
@@ -461,20 +523,31 @@ impl CppType {
                 .get_tdi()
                 .get_type_definition(metadata.metadata);
 
-            let name = declaring_td.get_name_components(metadata.metadata).name;
-            declaring_name = format!("{declaring_name}_{name}",);
+            declaring_names.push(declaring_td.get_name_components(metadata.metadata).name);
         }
 
         let context_td = context_tag.get_tdi().get_type_definition(metadata.metadata);
         let declaring_namespace = context_td.namespace(metadata.metadata);
 
-        let combined_name = format!("{}_{}", declaring_name, self.name());
+        // outermost first, matching the header name of the context
+        let combined_name = Self::flattened_nested_name(
+            declaring_names.iter().rev().map(|n| n.as_str()),
+            self.name(),
+        );
 
         self.cpp_name_components.namespace = Some(config.namespace_cpp(declaring_namespace));
         self.cpp_name_components.name = config.sanitize_to_cpp_name(&combined_name);
         self.cpp_name_components.declaring_types = None; // remove declaring types
     }
 
+    /// Name of a nested type moved out of its declaring types, e.g. `Outer_Inner_Color`
+    fn flattened_nested_name<'a>(
+        declaring_names: impl IntoIterator<Item = &'a str>,
+        name: &'a str,
+    ) -> String {
+        declaring_names.into_iter().chain([name]).join("_")
+    }
+
     pub fn fill(
         &mut self,
         cs_type: CsType,
@@ -485,20 +558,54 @@ impl CppType {
         let metadata = name_resolver.cordl_metadata;
         let t = &metadata.metadata.global_metadata.type_definitions[tdi];
 
+        self.prefix_comments
+            .extend(cs_type.placeholder_comments.iter().cloned());
+
+        self.alignment = self.required_alignment(metadata.pointer_size);
+
+        // closed generics are explicit specializations of the open template
+        // so the open template must be declared first
+        if let Some(args) = self.generic_instantiations_args_types.clone() {
+            let generics = args
+                .iter()
+                .map(|arg| {
+                    name_resolver
+                        .resolve_name(self, arg, TypeUsage::GenericArg, true)
+                        .combine_all()
+                })
+                .collect_vec();
+
+            self.cpp_name_components.generics = Some(generics);
+            self.requirements
+                .add_dependency_tag(CsTypeTag::TypeDefinitionIndex(tdi));
+        }
+
         // we depend on parents and generic args here
         // default ctor
         if t.is_value_type() || t.is_enum_type() {
-            self.create_valuetype_constructor(&cs_type.fields, name_resolver, config);
+            // copy/move ctors and operators are left implicit:
+            // fields are real members, so they copy field-wise
+            // and fields with non trivial copies (e.g. StringW) stay correct
+
+            // constructors turn the value type into a non-aggregate
+            let emit_ctors =
+                t.is_enum_type() || (config.value_type_constructors && !config.layout_only);
+
+            if emit_ctors {
+                self.create_valuetype_constructor(&cs_type.fields, name_resolver, config);
+            }
             self.create_valuetype_field_wrapper();
             if t.is_enum_type() {
                 let tdi = self.self_tag.get_tdi();
-                let t = tdi.get_type_definition(metadata.metadata);
-
-                let backing_field_idx = t.element_type_index as usize;
-                let backing_field_ty = &metadata.metadata_registration.types[backing_field_idx];
 
+                // the backing type is only named, an enum without one
+                // points its type index at itself
+                let backing_field_idx = CsType::enum_backing_type_index(metadata, tdi)
+                    .unwrap_or(t.byval_type_index as usize);
                 let backing_field_resolved_ty = ResolvedType {
-                    data: ResolvedTypeData::Primitive(backing_field_ty.ty),
+                    data: ResolvedTypeData::Primitive(
+                        cs_type.enum_backing_type.unwrap_or(Il2CppTypeEnum::I4),
+                    ),
                     ty: backing_field_idx,
                 };
 
@@ -509,7 +616,11 @@ impl CppType {
                     config,
                 );
             }
-            self.add_default_ctor(false);
+            if emit_ctors {
+                self.add_default_ctor(false);
+            }
+        } else if config.layout_only {
+            // layout mirrors are plain data, no special members
         } else if t.is_interface() {
             // self.make_interface_constructors();
 
@@ -523,24 +634,70 @@ impl CppType {
             // self.delete_default_ctor();
         }
 
+        if cs_type.to_string_method.is_some() && !t.is_interface() && !config.layout_only {
+            self.create_ostream_operator();
+        }
+
+        if self.uses_cached_class(config) && !config.layout_only {
+            self.create_cached_class();
+        }
+
+        if self.is_reference_type && !t.is_interface() && !config.layout_only {
+            self.create_cast_helpers();
+        }
+
+        // opaque native types only have a size, no members to lay out
+        let is_opaque = (t.is_value_type() && !t.is_enum_type())
+            && cs_type.fields.iter().all(|f| !f.instance)
+            && cs_type.methods.is_empty();
+
+        // checked before the fields are consumed
+        let instance_field_count = cs_type.fields.iter().filter(|f| f.instance).count();
+        let is_plain_data = self.is_value_type
+            && !self.is_enum_type
+            && cs_type.fields.iter().filter(|f| f.instance).all(|f| {
+                metadata.metadata_registration.types[f.field_ty.ty].is_plain_data(metadata.metadata)
+            });
+
         // Fill type from CS data
         self.make_fields(cs_type.fields, name_resolver, config);
-        self.make_methods(cs_type.methods, name_resolver, config);
-        self.make_properties(cs_type.properties, name_resolver, config);
-        self.make_constructors(cs_type.constructors, name_resolver, config);
+        if !config.layout_only {
+            self.make_methods(cs_type.methods, name_resolver, config);
+            self.make_properties(cs_type.properties, name_resolver, config);
+            self.make_constructors(cs_type.constructors, name_resolver, config);
+        }
 
         self.make_parent(cs_type.parent, name_resolver);
-        self.make_interfaces(cs_type.interfaces, name_resolver, config);
+        if !config.layout_only {
+            self.make_interfaces(cs_type.interfaces, name_resolver, config);
+        }
         self.make_nested_types(cs_type.nested_types, name_resolver, config);
 
         if !t.is_interface() {
             self.create_size_assert();
         }
+        // specializations name the instantiation args instead of the generic parameters
+        if self.generic_instantiations_args_types.is_none() {
+            self.interface_constraint_asserts(
+                &cs_type.generic_interface_constraints,
+                name_resolver,
+            )
+            .into_iter()
+            .for_each(|a| self.declarations.push(CppMember::CppStaticAssert(a).into()));
+        }
+        if config.trivially_copyable_asserts && is_plain_data {
+            self.create_trivially_copyable_asserts();
+        }
 
         self.add_type_index_member();
+        if config.field_count_members {
+            self.add_field_count_member(instance_field_count);
+        }
 
-        if !t.is_interface() {
-            self.create_size_padding(cs_type.size_info);
+        if is_opaque {
+            self.create_opaque_storage(cs_type.size_info);
+        } else if !t.is_interface() {
+            self.create_size_padding(cs_type.size_info, metadata);
         }
 
         let dependencies = self
@@ -574,7 +731,10 @@ impl CppType {
             cpp_fields::handle_referencetype_fields(self, &fields, name_resolver, config);
         }
 
-        cpp_fields::handle_static_fields(self, &fields, name_resolver, config);
+        // static fields are only reachable through their accessors
+        if !config.layout_only {
+            cpp_fields::handle_static_fields(self, &fields, name_resolver, config);
+        }
         cpp_fields::handle_const_fields(self, &fields, name_resolver, config);
     }
 
@@ -589,10 +749,43 @@ impl CppType {
         self.declarations.reserve(2 * (methods.len() + 1));
         self.implementations.reserve(methods.len() + 1);
 
+        let filter = &config.method_filter;
+        let mut emitted = 0;
+        let mut omitted = 0;
+        let methods = methods
+            .into_iter()
+            .filter(|method| method.name != ".cctor")
+            // invoking a constructor boxes the instance, which ref structs can't be
+            .filter(|method| !(self.is_byref_like && method.name == ".ctor"))
+            .filter(|method| {
+                // accessors and operators are kept so properties keep compiling
+                if method.method_flags.contains(CSMethodFlags::SPECIAL_NAME) {
+                    return true;
+                }
+
+                let capped = filter.max_per_type.is_some_and(|max| emitted >= max);
+                if capped || filter.is_stripped(method) {
+                    omitted += 1;
+                    return false;
+                }
+
+                emitted += 1;
+                true
+            })
+            .collect_vec();
+
+        if omitted > 0 {
+            self.declarations.push(
+                CppMember::CppLine(CppLine::make(format!(
+                    "// {omitted} methods omitted by the method filter"
+                )))
+                .into(),
+            );
+        }
+
+        self.create_enumerator_iterator(&methods, name_resolver);
+
         for method in methods {
-            if method.name == ".cctor" {
-                continue;
-            }
             self.create_method(&method, name_resolver, config, false);
         }
     }
@@ -607,8 +800,16 @@ impl CppType {
         CppParam {
             name: config.name_cpp(&p.name),
             ty: ty.combine_all(),
-            modifiers: "".to_string(), // TODO: Convert flags
-            def_value: p.def_value.as_ref().map(|v| v.to_string()),
+            modifiers: Self::param_modifiers(&p.modifiers).to_string(),
+            def_value: p.def_value.as_ref().map(|v| v.to_cpp_string(config)),
+        }
+    }
+
+    /// C++ qualifiers for parameter modifiers, by ref parameters already resolve to a reference type
+    fn param_modifiers(modifiers: &CsParamFlags) -> &'static str {
+        match modifiers.contains(CsParamFlags::VOLATILE) {
+            true => " volatile",
+            false => "",
         }
     }
 
@@ -632,12 +833,18 @@ impl CppType {
             let getter = prop.getter.map(|g| config.name_cpp(&g.1));
             let setter = prop.setter.map(|s| config.name_cpp(&s.1));
 
+            if prop.index_count == 1
+                && let Some(getter) = &getter
+            {
+                self.create_indexer_operator(getter);
+            }
+
             let prop_decl = CppPropertyDecl {
                 cpp_name: config.name_cpp(&prop.name),
                 prop_ty: prop_resolved_ty.combine_all(),
                 getter,
                 setter,
-                indexable: prop.indexable,
+                index_count: prop.index_count,
                 brief_comment: prop.brief_comment.clone(),
                 instance: prop.instance,
             };
@@ -647,6 +854,48 @@ impl CppType {
         }
     }
 
+    /// `operator[]` forwarding to the getter of a single index indexer,
+    /// e.g. `list[i]` for `list->get_Item(i)`. The getter is reused, not duplicated
+    fn create_indexer_operator(&mut self, getter: &str) {
+        let Some(getter_decl) = self.declarations.iter().find_map(|d| match d.as_ref() {
+            CppMember::MethodDecl(m)
+                if m.cpp_name == getter
+                    && m.instance
+                    && m.parameters.len() == 1
+                    && m.template.is_none() =>
+            {
+                Some(m.clone())
+            }
+            _ => None,
+        }) else {
+            return;
+        };
+
+        let index_name = &getter_decl.parameters[0].name;
+        let body = format!("return this->{getter}({index_name});");
+
+        let operator_decl = CppMethodDecl {
+            cpp_name: "operator[]".to_string(),
+            brief: Some(format!("Indexer, forwards to {getter}")),
+            docs: None,
+            body: None,
+            ..getter_decl
+        };
+        // defined with the implementations, where the return type is complete
+        let operator_impl = CppMethodImpl {
+            body: vec![Arc::new(CppLine::make(body))],
+            declaring_cpp_full_name: self.cpp_name_components.remove_pointer().combine_all(),
+            declaring_type_template: self.cpp_template.clone(),
+            brief: None,
+            ..operator_decl.clone().into()
+        };
+
+        self.declarations
+            .push(CppMember::MethodDecl(operator_decl).into());
+        self.implementations
+            .push(CppMember::MethodImpl(operator_impl).into());
+    }
+
     fn make_constructors(
         &mut self,
         constructors: Vec<CsConstructor>,
@@ -700,7 +949,7 @@ impl CppType {
 
         self.requirements.add_impl_include(
             Some(base_type_cpp_type),
-            CppInclude::new_context_typeimpl(base_type_context),
+            CppInclude::new_context_typeimpl(base_type_context, name_resolver.config),
         );
 
         self.parent = Some(parent_name.remove_pointer().combine_all());
@@ -717,7 +966,19 @@ impl CppType {
             .get_tdi()
             .get_type_definition(name_resolver.cordl_metadata.metadata);
 
+        // ref structs cannot be boxed, so no interface conversions
+        if self.is_byref_like {
+            return;
+        }
+
         for interface in interfaces {
+            // specializations don't declare the generic parameters,
+            // so generic interfaces must name the instantiation args
+            let interface = match &self.generic_instantiations_args_types {
+                Some(args) => interface.substitute_class_generics(args),
+                None => interface,
+            };
+
             // We have an interface, lets do something with it
             let interface_name_il2cpp =
                 name_resolver.resolve_name(self, &interface, TypeUsage::TypeName, true);
@@ -725,20 +986,25 @@ impl CppType {
             let interface_cpp_name = interface_name_il2cpp.remove_pointer().combine_all();
             let interface_cpp_pointer = interface_name_il2cpp.as_pointer().combine_all();
 
+            // value types are boxed, which allocates through il2cpp
+            let is_boxed = self_td.is_value_type() || self_td.is_enum_type();
+
             let operator_method_decl = CppMethodDecl {
                 body: Default::default(),
+                docs: None,
                 brief: Some(format!("Convert operator to {interface_cpp_name:?}")),
                 cpp_name: interface_cpp_pointer.clone(),
                 return_type: "".to_string(),
                 instance: true,
                 is_const: false,
                 is_constexpr: true,
-                is_no_except: !self_td.is_value_type() && !self_td.is_enum_type(),
+                is_no_except: !is_boxed,
                 is_implicit_operator: true,
                 is_explicit_operator: false,
 
                 is_virtual: false,
                 is_inline: true,
+                is_nodiscard: false,
                 parameters: vec![],
                 template: None,
                 prefix_modifiers: vec![],
@@ -759,7 +1025,7 @@ impl CppType {
                 .then(|| self.cpp_template.clone())
                 .flatten();
 
-            let convert_line = match self_td.is_value_type() || self_td.is_enum_type() {
+            let convert_line = match is_boxed {
                 true => {
                     // box
                     "static_cast<void*>(::il2cpp_utils::Box(this))".to_string()
@@ -801,7 +1067,7 @@ impl CppType {
 
     fn make_nested_types(
         &mut self,
-        nested_types: HashSet<CsTypeTag>,
+        nested_types: Vec<CsTypeTag>,
         name_resolver: &CppNameResolver,
         config: &CppGenerationConfig,
     ) {
@@ -839,18 +1105,74 @@ impl CppType {
                     nested.generic_instantiations_args_types.is_none(),
                 );
                 let fd = CppForwardDeclare::from_cpp_type(nested);
-                let inc = CppInclude::new_context_typedef(nested_context);
+                let inc = CppInclude::new_context_typedef(nested_context, config);
 
-                (alias, fd, inc)
+                // C# nested types may access private members of their declaring type,
+                // now that it is unnested it must be befriended instead
+                let friend = Self::make_nested_friend(nested);
+
+                (alias, fd, inc, friend)
             })
             .collect_vec();
 
-        for (alias, fd, inc) in aliases {
+        // inserted at the front, so reversed to keep them sorted
+        for (alias, fd, inc, friend) in aliases.into_iter().rev() {
+            self.declarations
+                .insert(0, CppMember::CppLine(friend.into()).into());
             self.declarations
                 .insert(0, CppMember::CppUsingAlias(alias).into());
             self.requirements.add_forward_declare((fd, inc));
         }
     }
+    /// `friend` declaration for an unnested nested type
+    /// Template parameters are renamed so they don't shadow the declaring type's own
+    fn make_nested_friend(nested: &CppType) -> String {
+        let kind = match nested.is_value_type {
+            true => "struct",
+            false => "class",
+        };
+        let name_components = nested.cpp_name_components.remove_pointer();
+
+        match &nested.cpp_template {
+            Some(template) => {
+                let name = name_components.remove_generics().combine_all();
+                let params = template
+                    .names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (constraint, _))| format!("{constraint} __cordl_friend_T{i}"))
+                    .join(",");
+
+                format!("template<{params}> friend {kind} {name};")
+            }
+            // specializations keep their generic args
+            None => format!("friend {kind} {};", name_components.combine_all()),
+        }
+    }
+
+    /// Return type as written in method declarations and implementations:
+    /// value types by value, reference types as pointers and byref returns as `ByRef<T>`
+    fn resolve_return_type(
+        &mut self,
+        return_type: &ResolvedType,
+        name_resolver: &CppNameResolver,
+    ) -> CppNameComponents {
+        let cpp_ret_type =
+            name_resolver.resolve_name(self, return_type, TypeUsage::ReturnType, false);
+
+        match cpp_ret_type.combine_all().as_str() {
+            "System.Enum" => {
+                self.requirements.needs_enum_include();
+                ENUM_PTR_TYPE.to_string().into()
+            }
+            "System.ValueType" => {
+                self.requirements.needs_value_include();
+                VT_PTR_TYPE.to_string().into()
+            }
+            _ => cpp_ret_type,
+        }
+    }
+
     fn create_method(
         &mut self,
         method: &CsMethod,
@@ -880,21 +1202,31 @@ impl CppType {
         // T UnityEngine.Component::GetComponent<T>() -> bs_hook::Il2CppWrapperType UnityEngine.Component::GetComponent()
         let template = method.template.clone().map(|t| t.into());
 
-        let mut cpp_ret_type =
-            name_resolver.resolve_name(self, &method.return_type, TypeUsage::ReturnType, false);
+        let cpp_ret_type = self.resolve_return_type(&method.return_type, name_resolver);
 
-        if cpp_ret_type.combine_all() == "System.Enum" {
-            self.requirements.needs_enum_include();
-            cpp_ret_type = ENUM_PTR_TYPE.to_string().into();
-        }
-
-        if cpp_ret_type.combine_all() == "System.ValueType" {
-            self.requirements.needs_value_include();
-            cpp_ret_type = VT_PTR_TYPE.to_string().into();
-        }
+        // closed instantiations of generic methods name their generic arguments
+        let generic_inst_names = method.generic_inst.as_ref().map(|args| {
+            args.iter()
+                .map(|arg| {
+                    name_resolver
+                        .resolve_name(self, arg, TypeUsage::GenericArg, false)
+                        .combine_all()
+                })
+                .collect_vec()
+        });
 
         let cpp_m_name = {
-            let cpp_m_name = config.name_cpp(m_name);
+            let cpp_m_name = match &generic_inst_names {
+                Some(names) => format!(
+                    "{}_{}",
+                    config.name_cpp(m_name),
+                    names
+                        .iter()
+                        .map(|n| config.sanitize_to_cpp_name(n).trim_matches('_').to_owned())
+                        .join("_")
+                ),
+                None => config.name_cpp(m_name),
+            };
 
             // static functions with same name and params but
             // different ret types can exist
@@ -922,10 +1254,20 @@ impl CppType {
         let is_final = method.method_flags.contains(CSMethodFlags::FINAL);
         let is_static = method.method_flags.contains(CSMethodFlags::STATIC);
 
+        // value returning methods shouldn't have their results discarded
+        let is_nodiscard = !cpp_ret_type.is_pointer && cpp_ret_type.combine_all() != "void";
+
+        // reference returns are already nullable pointers, only document it
+        let nullable_brief = match method.nullable_return {
+            true => ", may return null",
+            false => "",
+        };
+
         let method_decl = CppMethodDecl {
             body: None,
+            docs: method.docs.clone(),
             brief: format!(
-                "Method {m_name}, addr 0x{:x}, size 0x{:x}, virtual {}, abstract: {}, final {}",
+                "Method {m_name}, addr 0x{:x}, size 0x{:x}, virtual {}, abstract: {}, final {}{nullable_brief}",
                 method.method_data.addrs.unwrap_or(u64::MAX),
                 method.method_data.estimated_size.unwrap_or(usize::MAX),
                 is_virtual,
@@ -942,12 +1284,19 @@ impl CppType {
             instance: !is_static,
             template: template.clone(),
             suffix_modifiers: Default::default(),
-            prefix_modifiers: Default::default(),
+            prefix_modifiers: method
+                .obsolete
+                .iter()
+                .map(|message| deprecated_attribute(message))
+                .collect(),
+            // C++ `virtual` would add a vtable pointer to the il2cpp layout,
+            // overrides are dispatched through il2cpp in the body instead
             is_virtual: false,
             is_implicit_operator: false,
             is_explicit_operator: false,
 
             is_inline: true,
+            is_nodiscard,
         };
 
         let instance_ptr: String = if is_static {
@@ -962,10 +1311,13 @@ impl CppType {
         let param_names = CppParam::params_names(&method_decl.parameters).map(|s| s.as_str());
         let declaring_type_cpp_full_name = self.cpp_name_components.remove_pointer().combine_all();
 
-        let declaring_classof_call = format!(
-            "::il2cpp_utils::il2cpp_type_check::il2cpp_no_arg_class<{}>::get()",
-            self.cpp_name_components.combine_all()
-        );
+        let declaring_classof_call = match self.uses_cached_class(config) {
+            true => format!("{declaring_type_cpp_full_name}::{CACHED_CLASS_FN}()"),
+            false => format!(
+                "::il2cpp_utils::il2cpp_type_check::il2cpp_no_arg_class<{}>::get()",
+                self.cpp_name_components.combine_all()
+            ),
+        };
 
         let extract_self_class =
             "il2cpp_functions::object_get_class(reinterpret_cast<Il2CppObject*>(this))";
@@ -975,18 +1327,6 @@ impl CppType {
             .join(", ");
         let params_types_count = method_decl.parameters.len();
 
-        let resolve_instance_slot_lines = if let Some(slot) = method.method_data.slot {
-            vec![format!(
-                "auto* {METHOD_INFO_VAR_NAME} = THROW_UNLESS((::il2cpp_utils::ResolveVtableSlot(
-                    {extract_self_class},
-                    {declaring_classof_call},
-                    {slot}
-                )));"
-            )]
-        } else {
-            vec![]
-        };
-
         // if no params, just empty span
         // avoid allocs
         let params_types_array_cpp = match params_types_count {
@@ -996,18 +1336,25 @@ impl CppType {
             ),
         };
 
-        let method_info_lines = match &template {
-            Some(template) => {
+        // generic arguments to instantiate the method with
+        let generic_classes = match (&template, &generic_inst_names) {
+            (Some(template), _) => Some(template.just_names().cloned().collect_vec()),
+            (None, Some(names)) => Some(names.clone()),
+            (None, None) => None,
+        };
+
+        let method_info_lines = match &generic_classes {
+            Some(generic_classes) => {
                 // generic
-                let template_names = template
-                    .just_names()
+                let template_names = generic_classes
+                    .iter()
                     .map(|t| {
                         format!(
                             "::il2cpp_utils::il2cpp_type_check::il2cpp_no_arg_class<{t}>::get()"
                         )
                     })
                     .join(", ");
-                let template_count = template.names.len();
+                let template_count = generic_classes.len();
 
                 // if no template params, just empty span
                 // avoid allocs
@@ -1043,6 +1390,29 @@ impl CppType {
             }
         };
 
+        // abstract and virtual methods always get an invoking body instead of `= 0`,
+        // the object is dispatched on at runtime through its il2cpp vtable
+        let resolve_instance_slot_lines = match method.method_data.slot {
+            Some(slot) => vec![format!(
+                "auto* {METHOD_INFO_VAR_NAME} = THROW_UNLESS((::il2cpp_utils::ResolveVtableSlot(
+                    {extract_self_class},
+                    {declaring_classof_call},
+                    {slot}
+                )));"
+            )],
+            // no known slot, find the declared method once and let il2cpp find the override
+            None => vec![format!(
+                "auto* {METHOD_INFO_VAR_NAME} = THROW_UNLESS(il2cpp_functions::object_get_virtual_method(
+                    reinterpret_cast<Il2CppObject*>(this),
+                    []() {{
+                        {}
+                        return {METHOD_INFO_VAR_NAME};
+                    }}()
+                ));",
+                method_info_lines.join("\n")
+            )],
+        };
+
         let method_body_lines = [format!(
             "return ::cordl_internals::RunMethodRethrow<{}, false>({});",
             cpp_ret_type.combine_all(),
@@ -1054,7 +1424,8 @@ impl CppType {
 
         // instance methods should resolve slots if this is an interface, or if this is a virtual/abstract method, and not a final method
         // static methods can't be virtual or interface anyway so checking for that here is irrelevant
-        let should_resolve_slot = self.is_interface || ((is_virtual || is_abstract) && !is_final);
+        let should_resolve_slot =
+            !is_static && (self.is_interface || ((is_virtual || is_abstract) && !is_final));
 
         let method_body = match should_resolve_slot {
             true => resolve_instance_slot_lines
@@ -1071,6 +1442,18 @@ impl CppType {
                 .collect_vec(),
         };
 
+        // closed instantiations don't declare the generic parameters
+        let constraint_asserts = match template.is_some() {
+            true => self
+                .interface_constraint_asserts(&method.generic_interface_constraints, name_resolver),
+            false => vec![],
+        };
+        let method_body = constraint_asserts
+            .into_iter()
+            .map(|a| -> Arc<dyn WritableDebug> { Arc::new(a) })
+            .chain(method_body)
+            .collect_vec();
+
         let method_impl = CppMethodImpl {
             body: method_body,
             parameters: m_params_with_def.clone(),
@@ -1129,7 +1512,11 @@ impl CppType {
             .is_some_and(|t| !t.names.is_empty());
 
         // don't emit method size structs for generic methods
-        if template.is_none() && !has_template_args && !is_generic_method_inst {
+        if template.is_none()
+            && !has_template_args
+            && !is_generic_method_inst
+            && method.generic_inst.is_none()
+        {
             self.nonmember_implementations
                 .push(Arc::new(CppNonMember::SizeStruct(
                     CppMethodSizeStruct {
@@ -1168,91 +1555,414 @@ impl CppType {
         }
 
         if !is_generic_method_inst {
+            self.create_params_overload(method, &method_decl, name_resolver);
+            self.create_method_pointer_helper(method, &method_decl, config);
             self.declarations
                 .push(CppMember::MethodDecl(method_decl).into());
         }
     }
 
-    pub fn classof_cpp_name(&self) -> String {
-        format!(
-            "::il2cpp_utils::il2cpp_type_check::il2cpp_no_arg_class<{}>::get",
-            self.cpp_name_components.combine_all()
-        )
-    }
-
-    fn create_size_assert(&mut self) {
-        // FIXME: make this work with templated types that either: have a full template (complete instantiation), or only require a pointer (size should be stable)
-        // for now, skip templated types
-        if self.cpp_template.is_some() {
+    /// Overload taking a C# `params` tail as `std::initializer_list`,
+    /// packing it into the array and forwarding to the array overload
+    fn create_params_overload(
+        &mut self,
+        method: &CsMethod,
+        method_decl: &CppMethodDecl,
+        name_resolver: &CppNameResolver,
+    ) {
+        let Some(last_param) = method.parameters.last() else {
+            return;
+        };
+        if !last_param.modifiers.contains(CsParamFlags::PARAMS) {
             return;
         }
+        // template args can't be deduced through the forwarding call
+        if method_decl.template.is_some() {
+            return;
+        }
+        let ResolvedTypeData::Array(element) = &last_param.il2cpp_ty.data else {
+            return;
+        };
 
-        if let Some(size) = self.size_info.as_ref().map(|s| s.instance_size) {
-            let cpp_name: String = self.cpp_name_components.remove_pointer().combine_all();
+        let element_ty = name_resolver
+            .resolve_name(self, element, TypeUsage::GenericArg, false)
+            .combine_all();
+        self.requirements
+            .add_def_include(None, CppInclude::new_system("initializer_list"));
 
-            assert!(!cpp_name.trim().is_empty(), "CPP Name cannot be empty!");
+        let mut parameters = method_decl.parameters.clone();
+        let Some(params_param) = parameters.last_mut() else {
+            return;
+        };
+        let array_ty = std::mem::replace(
+            &mut params_param.ty,
+            format!("::std::initializer_list<{element_ty}>"),
+        );
 
-            let assert = CppStaticAssert {
-                condition: format!("::cordl_internals::size_check_v<{cpp_name}, 0x{size:x}>"),
-                message: Some("Size mismatch!".to_string()),
-            };
+        let params_index = parameters.len() - 1;
+        let args = CppParam::params_names(&parameters)
+            .enumerate()
+            .map(|(i, name)| match i == params_index {
+                true => format!("{array_ty}({name})"),
+                false => name.clone(),
+            })
+            .join(", ");
 
-            self.nonmember_declarations
-                .push(Arc::new(CppNonMember::CppStaticAssert(assert)));
-        } else {
-            todo!("Why does this type not have a valid size??? {self:?}");
-        }
+        let overload_decl = CppMethodDecl {
+            parameters,
+            body: Some(vec![Arc::new(CppLine::make(format!(
+                "return {}({args});",
+                method_decl.cpp_name
+            )))]),
+            docs: None,
+            brief: Some(format!(
+                "`params` overload of {}, packs the trailing arguments into an array",
+                method.name
+            )),
+            is_virtual: false,
+            ..method_decl.clone()
+        };
+
+        self.declarations
+            .push(CppMember::MethodDecl(overload_decl).into());
     }
 
-    ///
-    /// add missing size for type
-    ///
-    fn create_size_padding(&mut self, size_info: Option<SizeInfo>) {
-        // // get type metadata size
-        let Some(size_info) = size_info else {
+    /// Static `{method}_ptr` helper calling the method through its function pointer,
+    /// taking the instance as its first parameter for instance methods
+    fn create_method_pointer_helper(
+        &mut self,
+        method: &CsMethod,
+        method_decl: &CppMethodDecl,
+        config: &CppGenerationConfig,
+    ) {
+        if !config.method_pointers {
             return;
-        };
-
-        // // ignore types that aren't sized
-        if size_info.instance_size == 0 || size_info.instance_size == u32::MAX {
+        }
+        // generic code has no single address,
+        // closed instantiations only know the address of the open method
+        if method_decl.template.is_some()
+            || self.cpp_template.is_some()
+            || method.generic_inst.is_some()
+        {
             return;
         }
+        let Some(addr) = method
+            .method_data
+            .addrs
+            .filter(|addr| *addr != 0 && *addr != u64::MAX)
+        else {
+            return;
+        };
 
-        // // if the size matches what we calculated, we're fine
-        // if metadata_size.instance_size == calculated_size {
-        //     return;
-        // }
-        // let remaining_size = metadata_size.instance_size.abs_diff(calculated_size);
+        let mut parameters = method_decl.parameters.clone();
+        if method_decl.instance {
+            parameters.insert(
+                0,
+                CppParam {
+                    name: "__cordl_instance".to_string(),
+                    ty: format!(
+                        "{}*",
+                        self.cpp_name_components.remove_pointer().combine_all()
+                    ),
+                    modifiers: "".to_string(),
+                    def_value: None,
+                },
+            );
+        }
 
-        // for all types, the size il2cpp metadata says the type should be, for generics this is calculated though
-        let metadata_size_instance = size_info.instance_size;
+        // il2cpp passes the method info last, unused by non generic methods
+        let fn_ptr_ty = format!(
+            "{} (*)({})",
+            method_decl.return_type,
+            CppParam::params_types(&parameters)
+                .map(String::as_str)
+                .chain(std::iter::once("::MethodInfo const*"))
+                .join(", ")
+        );
+        let args = CppParam::params_names(&parameters)
+            .map(String::as_str)
+            .chain(std::iter::once("nullptr"))
+            .join(", ");
 
-        // align the calculated size to the next multiple of natural_alignment, similiar to what happens when clang compiles our generated code
-        // this comes down to adding our size, and removing any bits that make it more than the next multiple of alignment
-        #[cfg(feature = "il2cpp_v29")]
-        let aligned_calculated_size = match size_info.natural_alignment as u32 {
-            0 => size_info.calculated_instance_size,
-            alignment => (size_info.calculated_instance_size + alignment) & !(alignment - 1),
+        let helper_decl = CppMethodDecl {
+            cpp_name: format!("{}_ptr", method_decl.cpp_name),
+            parameters,
+            instance: false,
+            body: Some(vec![
+                Arc::new(CppLine::make(format!(
+                    "using ___internal_method_ptr_t = {fn_ptr_ty};"
+                ))),
+                Arc::new(CppLine::make(format!(
+                    "return ::cordl_internals::MethodPointer<___internal_method_ptr_t, 0x{addr:x}>()({args});"
+                ))),
+            ]),
+            docs: None,
+            brief: Some(format!(
+                "Calls {} through its function pointer at 0x{addr:x}, without reflection or virtual dispatch",
+                method.name
+            )),
+            is_virtual: false,
+            ..method_decl.clone()
         };
-        #[cfg(feature = "il2cpp_v31")]
-        let aligned_calculated_size = size_info.calculated_instance_size;
 
-        // return if calculated layout size == metadata size
-        if aligned_calculated_size == metadata_size_instance {
+        self.declarations
+            .push(CppMember::MethodDecl(helper_decl).into());
+    }
+
+    /// `begin()`/`end()` over the enumerator returned by `GetEnumerator()`, for range based for loops.
+    /// `cordl_internals::EnumeratorIterator` holds value type enumerators like `List<T>.Enumerator` by value,
+    /// so iterating never boxes them
+    fn create_enumerator_iterator(
+        &mut self,
+        methods: &[CsMethod],
+        name_resolver: &CppNameResolver,
+    ) {
+        let Some(get_enumerator) = methods.iter().find(|m| {
+            m.name == "GetEnumerator"
+                && m.instance
+                && m.parameters.is_empty()
+                && m.template.is_none()
+                && m.generic_inst.is_none()
+        }) else {
+            return;
+        };
+        if methods.iter().any(|m| m.name == "begin" || m.name == "end") {
             return;
         }
 
-        let remaining_size = metadata_size_instance.abs_diff(size_info.calculated_instance_size);
+        let enumerator_ty = self
+            .resolve_return_type(&get_enumerator.return_type, name_resolver)
+            .combine_all();
+        let iterator_ty = format!("::cordl_internals::EnumeratorIterator<{enumerator_ty}>");
 
-        // pack the remaining size to fit the packing of the type
-        let closest_packing = |size: u32| match size {
-            0 => 0,
-            1 => 1,
-            2 => 2,
-            3 => 4,
-            4 => 4,
-            _ => 8,
-        };
+        let begin_decl = CppMethodDecl {
+            cpp_name: "begin".to_string(),
+            return_type: iterator_ty.clone(),
+            parameters: vec![],
+            instance: true,
+            template: None,
+            suffix_modifiers: Default::default(),
+            prefix_modifiers: Default::default(),
+            is_virtual: false,
+            is_constexpr: false,
+            is_const: false,
+            is_no_except: false,
+            is_implicit_operator: false,
+            is_explicit_operator: false,
+            is_inline: true,
+            is_nodiscard: true,
+            docs: None,
+            brief: Some("Starts iterating the enumerator returned by GetEnumerator".to_string()),
+            body: None,
+        };
+        // defined with the implementations, where the enumerator type is complete
+        let begin_impl = CppMethodImpl {
+            body: vec![Arc::new(CppLine::make(format!(
+                "return {iterator_ty}(this->GetEnumerator());"
+            )))],
+            declaring_cpp_full_name: self.cpp_name_components.remove_pointer().combine_all(),
+            declaring_type_template: self.cpp_template.clone(),
+            brief: None,
+            ..begin_decl.clone().into()
+        };
+
+        let end_decl = CppMethodDecl {
+            cpp_name: "end".to_string(),
+            return_type: "::std::default_sentinel_t".to_string(),
+            is_constexpr: true,
+            is_const: true,
+            is_no_except: true,
+            brief: None,
+            body: Some(vec![Arc::new(CppLine::make("return {};".to_string()))]),
+            ..begin_decl.clone()
+        };
+
+        self.declarations
+            .push(CppMember::MethodDecl(begin_decl).into());
+        self.implementations
+            .push(CppMember::MethodImpl(begin_impl).into());
+        self.declarations
+            .push(CppMember::MethodDecl(end_decl).into());
+    }
+
+    /// Whether generated methods resolve the class through `CACHED_CLASS_FN`
+    fn uses_cached_class(&self, config: &CppGenerationConfig) -> bool {
+        config.cached_class && !self.is_value_type && !self.is_enum_type && !self.is_interface
+    }
+
+    /// Static accessor resolving the class once, thread safe through the function local static
+    /// A static data member can't be used as it would be initialized before il2cpp is
+    fn create_cached_class(&mut self) {
+        let body = format!(
+            "static auto* klass = {}();\nreturn klass;",
+            self.classof_cpp_name()
+        );
+
+        let cached_class_decl = CppMethodDecl {
+            cpp_name: CACHED_CLASS_FN.to_string(),
+            return_type: "Il2CppClass*".to_string(),
+            parameters: vec![],
+            instance: false,
+            template: None,
+            suffix_modifiers: Default::default(),
+            prefix_modifiers: Default::default(),
+            is_virtual: false,
+            is_constexpr: false,
+            is_const: false,
+            is_no_except: false,
+            is_implicit_operator: false,
+            is_explicit_operator: false,
+            is_inline: true,
+            is_nodiscard: true,
+            docs: None,
+            brief: Some("Class of this type, resolved once and reused by the methods".to_string()),
+            body: Some(vec![Arc::new(CppLine::make(body))]),
+        };
+
+        self.declarations
+            .push(CppMember::MethodDecl(cached_class_decl).into());
+    }
+
+    pub fn classof_cpp_name(&self) -> String {
+        format!(
+            "::il2cpp_utils::il2cpp_type_check::il2cpp_no_arg_class<{}>::get",
+            self.cpp_name_components.combine_all()
+        )
+    }
+
+    /// `where T : IFoo` constraints as `static_assert`s,
+    /// interfaces aren't C++ bases so this checks T converts to the interface
+    fn interface_constraint_asserts(
+        &mut self,
+        constraints: &[(String, ResolvedType)],
+        name_resolver: &CppNameResolver,
+    ) -> Vec<CppStaticAssert> {
+        constraints
+            .iter()
+            .map(|(name, interface)| {
+                let interface_cpp_name = name_resolver
+                    .resolve_name(self, interface, TypeUsage::TypeName, false)
+                    .remove_pointer()
+                    .combine_all();
+
+                Self::interface_constraint_assert(name, &interface_cpp_name)
+            })
+            .collect()
+    }
+
+    fn interface_constraint_assert(name: &str, interface_cpp_name: &str) -> CppStaticAssert {
+        CppStaticAssert {
+            condition: format!(
+                "::cordl_internals::implements_interface<{name}, {interface_cpp_name}>"
+            ),
+            message: Some(format!("{name} must implement {interface_cpp_name}")),
+        }
+    }
+
+    /// Alignment to emit as `alignas`, if il2cpp aligns the value type stricter than its fields.
+    /// Fields are at most pointer aligned, stricter value type fields carry their own `alignas`.
+    /// `alignas` can't lower alignment and would override `#pragma pack`, so packed types go without
+    fn required_alignment(&self, pointer_size: PointerSize) -> Option<u32> {
+        if !self.is_value_type || self.is_enum_type || self.packing.is_some() {
+            return None;
+        }
+
+        // the v31 layout only computes the minimum alignment
+        self.size_info
+            .as_ref()
+            .map(|size_info| size_info.natural_alignment.max(size_info.minimum_alignment) as u32)
+            .filter(|alignment| *alignment > pointer_size as u32)
+    }
+
+    fn create_size_assert(&mut self) {
+        // FIXME: make this work with templated types that either: have a full template (complete instantiation), or only require a pointer (size should be stable)
+        // for now, skip templated types
+        if self.cpp_template.is_some() {
+            return;
+        }
+
+        if let Some(size) = self.size_info.as_ref().map(|s| s.instance_size) {
+            let cpp_name: String = self.cpp_name_components.remove_pointer().combine_all();
+
+            assert!(!cpp_name.trim().is_empty(), "CPP Name cannot be empty!");
+
+            let assert = CppStaticAssert {
+                condition: format!("::cordl_internals::size_check_v<{cpp_name}, 0x{size:x}>"),
+                message: Some("Size mismatch!".to_string()),
+            };
+
+            self.nonmember_declarations
+                .push(Arc::new(CppNonMember::CppStaticAssert(assert)));
+        } else {
+            todo!("Why does this type not have a valid size??? {self:?}");
+        }
+    }
+
+    /// Asserts the value type can be copied bytewise out of game memory
+    fn create_trivially_copyable_asserts(&mut self) {
+        // templates have no complete type to check
+        if self.cpp_template.is_some() || self.generic_instantiations_args_types.is_some() {
+            return;
+        }
+
+        let cpp_name: String = self.cpp_name_components.remove_pointer().combine_all();
+
+        self.requirements
+            .add_def_include(None, CppInclude::new_system("type_traits"));
+
+        self.nonmember_declarations
+            .push(Arc::new(CppNonMember::CppStaticAssert(CppStaticAssert {
+                condition: format!("::std::is_trivially_copyable_v<{cpp_name}>"),
+                message: Some("Not trivially copyable!".to_string()),
+            })));
+        self.nonmember_declarations
+            .push(Arc::new(CppNonMember::CppStaticAssert(CppStaticAssert {
+                condition: format!("::std::is_standard_layout_v<{cpp_name}>"),
+                message: Some("Not standard layout!".to_string()),
+            })));
+    }
+
+    ///
+    /// add missing size for type
+    ///
+    fn create_size_padding(&mut self, size_info: Option<SizeInfo>, metadata: &CordlMetadata) {
+        // // get type metadata size
+        let Some(size_info) = size_info else {
+            return;
+        };
+
+        // // ignore types that aren't sized
+        if size_info.instance_size == 0 || size_info.instance_size == u32::MAX {
+            return;
+        }
+
+        // // if the size matches what we calculated, we're fine
+        // if metadata_size.instance_size == calculated_size {
+        //     return;
+        // }
+        // let remaining_size = metadata_size.instance_size.abs_diff(calculated_size);
+
+        // for all types, the size il2cpp metadata says the type should be, for generics this is calculated though
+        let metadata_size_instance = size_info.instance_size;
+
+        let aligned_calculated_size = metadata.metadata_version.align_calculated_size(&size_info);
+
+        // return if calculated layout size == metadata size
+        if aligned_calculated_size == metadata_size_instance {
+            return;
+        }
+
+        let remaining_size = metadata_size_instance.abs_diff(size_info.calculated_instance_size);
+
+        // pack the remaining size to fit the packing of the type
+        let closest_packing = |size: u32| match size {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 => 4,
+            4 => 4,
+            _ => 8,
+        };
 
         let packing = self
             .packing
@@ -1276,6 +1986,7 @@ impl CppType {
                 readonly: false,
                 const_expr: false,
                 value: None,
+                docs: None,
                 brief_comment: Some(format!(
                     "Size padding 0x{:x} - 0x{:x} = 0x{remaining_size:x}, packed as 0x{packed_remaining_size:x}",
                     metadata_size_instance, size_info.calculated_instance_size
@@ -1286,6 +1997,38 @@ impl CppType {
         );
     }
 
+    ///
+    /// fill an opaque type with raw bytes so it matches its il2cpp size
+    ///
+    fn create_opaque_storage(&mut self, size_info: Option<SizeInfo>) {
+        let Some(size_info) = size_info else {
+            return;
+        };
+
+        let size = size_info.instance_size;
+        if size == 0 || size == u32::MAX {
+            return;
+        }
+
+        self.declarations.push(
+            CppMember::FieldDecl(CppFieldDecl {
+                cpp_name: format!("_opaque[0x{size:x}]"),
+                field_ty: "uint8_t".into(),
+                offset: Some(0),
+                instance: true,
+                readonly: false,
+                const_expr: false,
+                value: None,
+                docs: None,
+                brief_comment: Some(format!(
+                    "Opaque type, no fields or methods in metadata, size 0x{size:x}"
+                )),
+                is_private: false,
+            })
+            .into(),
+        );
+    }
+
     fn create_ref_size(&mut self) {
         if let Some(size) = self.size_info.as_ref().map(|s| s.instance_size) {
             self.declarations.push(
@@ -1297,6 +2040,7 @@ impl CppType {
                     readonly: false,
                     const_expr: true,
                     value: Some(format!("0x{size:x}")),
+                    docs: None,
                     brief_comment: Some("The size of the true reference type".to_string()),
                     is_private: false,
                 })
@@ -1304,9 +2048,23 @@ impl CppType {
             );
 
             // here we push an instance field like uint8_t __fields[total_size - base_size] to make sure ref types are the exact size they should be
-            let inherits = self.get_inherits().collect_vec();
-            let fixup_size = match inherits.first() {
-                Some(base_type) => format!("0x{size:x} - sizeof({base_type})"),
+            let base_type = self.get_inherits().next().cloned();
+            let fixup_size = match base_type {
+                Some(base_type) => {
+                    // a base larger than the derived type would underflow the array size, fail loudly instead
+                    let cpp_name = self.cpp_name_components.remove_pointer().combine_all();
+                    self.declarations.push(
+                        CppMember::CppStaticAssert(CppStaticAssert {
+                            condition: format!("0x{size:x} >= sizeof({base_type})"),
+                            message: Some(format!(
+                                "Size of {cpp_name} is smaller than its base type {base_type}!"
+                            )),
+                        })
+                        .into(),
+                    );
+
+                    format!("0x{size:x} - sizeof({base_type})")
+                }
                 None => format!("0x{size:x}"),
             };
 
@@ -1319,6 +2077,7 @@ impl CppType {
                     readonly: false,
                     const_expr: false,
                     value: Some("".into()),
+                    docs: None,
                     brief_comment: Some(
                         "The size this ref type adds onto its base type, may evaluate to 0"
                             .to_string(),
@@ -1357,7 +2116,7 @@ impl CppType {
         &mut self,
         backing_type: ResolvedType,
         name_resolver: &CppNameResolver,
-        _config: &CppGenerationConfig,
+        config: &CppGenerationConfig,
     ) {
         let metadata = name_resolver.cordl_metadata;
 
@@ -1392,7 +2151,7 @@ impl CppType {
                     let f_name = field.name(metadata.metadata);
                     let value = CsType::field_default_value(metadata, field_index)
                         .expect("Enum without value!")
-                        .to_string();
+                        .to_cpp_string(config);
 
                     // prepend enum name with __E_ to prevent accidentally creating enum values that are reserved for builtin macros
                     format!("__E_{f_name} = {value},")
@@ -1409,6 +2168,7 @@ impl CppType {
             declarations: enum_entries.map(Rc::new).collect(),
             brief_comment: Some(format!("Nested struct {unwrapped_name}")),
             packing: None,
+            packing_style: Default::default(),
         };
         self.declarations
             .push(CppMember::NestedStruct(nested_struct).into());
@@ -1419,6 +2179,7 @@ impl CppType {
             instance: true,
             return_type: unwrapped_name,
 
+            docs: None,
             brief: Some("Conversion into unwrapped enum value".to_string()),
             body: Some(vec![Arc::new(CppLine::make(operator_body))]),
             is_const: true,
@@ -1432,6 +2193,7 @@ impl CppType {
             suffix_modifiers: vec![],
             template: None,
             is_inline: true,
+            is_nodiscard: false,
         };
         // convert to proper backing type
         let backing_operator_body = format!("return static_cast<{enum_base}>(this->value__);");
@@ -1466,6 +2228,7 @@ impl CppType {
                 readonly: false,
                 const_expr: true,
                 value: Some(format!("0x{size:x}")),
+                docs: None,
                 brief_comment: Some("The size of the true value type".to_string()),
                 is_private: false,
             })
@@ -1499,7 +2262,7 @@ impl CppType {
 
                 // Get the inner type of a Generic Inst
                 // e.g ReadOnlySpan<char> -> ReadOnlySpan<T>
-                let def_value = CsValue::Null.to_string();
+                let def_value = CsValue::Null.to_cpp_string(config);
 
                 let f_cpp_name = config.name_cpp(&field.name);
 
@@ -1586,128 +2349,11 @@ impl CppType {
             .push(CppMember::ConstructorImpl(constructor_impl).into());
     }
 
-    fn create_valuetype_default_constructors(&mut self) {
-        // create the various copy and move ctors and operators
-        let cpp_name = self.cpp_name();
-        let wrapper = format!("{VALUE_WRAPPER_TYPE}<{VALUE_TYPE_WRAPPER_SIZE}>::instance");
-
-        let move_ctor = CppConstructorDecl {
-            cpp_name: cpp_name.clone(),
-            parameters: vec![CppParam {
-                ty: cpp_name.clone(),
-                name: "".to_string(),
-                modifiers: "&&".to_string(),
-                def_value: None,
-            }],
-            template: None,
-            is_constexpr: true,
-            is_explicit: false,
-            is_default: true,
-            is_no_except: false,
-            is_delete: false,
-            is_protected: false,
-            base_ctor: None,
-            initialized_values: Default::default(),
-            brief: None,
-            body: None,
-        };
-
-        let copy_ctor = CppConstructorDecl {
-            cpp_name: cpp_name.clone(),
-            parameters: vec![CppParam {
-                ty: cpp_name.clone(),
-                name: "".to_string(),
-                modifiers: "const &".to_string(),
-                def_value: None,
-            }],
-            template: None,
-            is_constexpr: true,
-            is_explicit: false,
-            is_default: true,
-            is_no_except: false,
-            is_delete: false,
-            is_protected: false,
-            base_ctor: None,
-            initialized_values: Default::default(),
-            brief: None,
-            body: None,
-        };
-
-        let move_operator_eq = CppMethodDecl {
-            cpp_name: "operator=".to_string(),
-            return_type: format!("{cpp_name}&"),
-            parameters: vec![CppParam {
-                ty: cpp_name.clone(),
-                name: "o".to_string(),
-                modifiers: "&&".to_string(),
-                def_value: None,
-            }],
-            instance: true,
-            template: None,
-            suffix_modifiers: vec![],
-            prefix_modifiers: vec![],
-            is_virtual: false,
-            is_constexpr: true,
-            is_const: false,
-            is_no_except: true,
-            is_implicit_operator: false,
-            is_explicit_operator: false,
-
-            is_inline: false,
-            brief: None,
-            body: Some(vec![
-                Arc::new(CppLine::make(format!(
-                    "this->{wrapper} = std::move(o.{wrapper});"
-                ))),
-                Arc::new(CppLine::make("return *this;".to_string())),
-            ]),
-        };
-
-        let copy_operator_eq = CppMethodDecl {
-            cpp_name: "operator=".to_string(),
-            return_type: format!("{cpp_name}&"),
-            parameters: vec![CppParam {
-                ty: cpp_name.clone(),
-                name: "o".to_string(),
-                modifiers: "const &".to_string(),
-                def_value: None,
-            }],
-            instance: true,
-            template: None,
-            suffix_modifiers: vec![],
-            prefix_modifiers: vec![],
-            is_virtual: false,
-            is_constexpr: true,
-            is_const: false,
-            is_no_except: true,
-            is_implicit_operator: false,
-            is_explicit_operator: false,
-
-            is_inline: false,
-            brief: None,
-            body: Some(vec![
-                Arc::new(CppLine::make(format!("this->{wrapper} = o.{wrapper};"))),
-                Arc::new(CppLine::make("return *this;".to_string())),
-            ]),
-        };
-
-        self.declarations
-            .push(CppMember::ConstructorDecl(move_ctor).into());
-        self.declarations
-            .push(CppMember::ConstructorDecl(copy_ctor).into());
-        self.declarations
-            .push(CppMember::MethodDecl(move_operator_eq).into());
-        self.declarations
-            .push(CppMember::MethodDecl(copy_operator_eq).into());
-    }
-
-    fn create_ref_default_constructor(&mut self) {
+    fn create_ref_default_constructor(&mut self, metadata: &CordlMetadata) {
         let cpp_name = self.cpp_name().clone();
 
-        let cs_name = self.name().clone();
-
         // Skip if System.ValueType or System.Enum
-        if self.namespace() == "System" && (cs_name == "ValueType" || cs_name == "Enum") {
+        if self.is_value_type_base(metadata) {
             return;
         }
 
@@ -1842,16 +2488,14 @@ impl CppType {
             .into(),
         );
     }
-    fn create_ref_default_operators(&mut self) {
-        let cpp_name = self.cpp_name();
-
+    fn create_ref_default_operators(&mut self, metadata: &CordlMetadata) {
         // Skip if System.ValueType or System.Enum
-        if self.namespace() == "System"
-            && (self.cpp_name() == "ValueType" || self.cpp_name() == "Enum")
-        {
+        if self.is_value_type_base(metadata) {
             return;
         }
 
+        let cpp_name = self.cpp_name();
+
         // Delegates and such are reference types with no inheritance
         if self.get_inherits().count() > 0 {
             return;
@@ -1873,6 +2517,14 @@ impl CppType {
 
   constexpr {cpp_name}& operator=({cpp_name}&& o) noexcept = default;
   constexpr {cpp_name}& operator=({cpp_name} const& o) noexcept = default;
+
+  explicit constexpr operator bool() const noexcept {{
+    return this->{REFERENCE_WRAPPER_INSTANCE_NAME} != nullptr;
+  }};
+
+  constexpr bool operator==(std::nullptr_t) const noexcept {{
+    return this->{REFERENCE_WRAPPER_INSTANCE_NAME} == nullptr;
+  }};
                 "
                 ),
             })
@@ -1969,6 +2621,107 @@ impl CppType {
             .push(CppMember::ConstructorImpl(default_ctor_impl).into());
     }
 
+    /// Hidden friend `operator<<` which forwards to the C# `ToString()` override
+    fn create_ostream_operator(&mut self) {
+        self.requirements
+            .add_def_include(None, CppInclude::new_system("ostream"));
+        self.requirements.needs_stringw_include();
+
+        let self_ty = self.cpp_name_components.combine_all();
+        let accessor = match self.is_reference_type {
+            true => "->",
+            false => ".",
+        };
+
+        let operator_decl = CppMethodDecl {
+            cpp_name: "operator<<".into(),
+            return_type: "std::ostream&".into(),
+            parameters: vec![
+                CppParam {
+                    name: "os".into(),
+                    ty: "std::ostream&".into(),
+                    modifiers: "".into(),
+                    def_value: None,
+                },
+                CppParam {
+                    name: "self".into(),
+                    ty: self_ty,
+                    modifiers: "".into(),
+                    def_value: None,
+                },
+            ],
+            // friends are never static
+            instance: true,
+            template: None,
+            body: Some(vec![Arc::new(CppLine::make(format!(
+                "return os << static_cast<std::string>(self{accessor}ToString());"
+            )))]),
+            docs: None,
+            brief: Some("Writes the result of ToString() to the stream".into()),
+            is_const: false,
+            is_constexpr: false,
+            is_no_except: false,
+            is_virtual: false,
+            is_implicit_operator: false,
+            is_explicit_operator: false,
+            is_inline: true,
+            is_nodiscard: false,
+            prefix_modifiers: vec!["friend".into()],
+            suffix_modifiers: vec![],
+        };
+
+        self.declarations
+            .push(CppMember::MethodDecl(operator_decl).into());
+    }
+
+    /// `try_cast` and `is` mirroring C#'s `as` and `is`
+    /// `U` is the target pointer type, e.g. `try_cast<::UnityEngine::MonoBehaviour*>()`
+    fn create_cast_helpers(&mut self) {
+        self.requirements
+            .add_def_include(None, CppInclude::new_system("optional"));
+
+        let template = Some(CppTemplate::make_typenames(["U".to_string()].into_iter()));
+
+        let try_cast_decl = CppMethodDecl {
+            cpp_name: "try_cast".into(),
+            return_type: "::std::optional<U>".into(),
+            parameters: vec![],
+            instance: true,
+            template: template.clone(),
+            body: Some(vec![Arc::new(CppLine::make(
+                "return ::il2cpp_utils::try_cast<::std::remove_pointer_t<U>>(this);".to_string(),
+            ))]),
+            docs: None,
+            brief: Some("The instance as `U` if its runtime class is assignable to `U`".into()),
+            is_const: false,
+            is_constexpr: false,
+            is_no_except: false,
+            is_virtual: false,
+            is_implicit_operator: false,
+            is_explicit_operator: false,
+            is_inline: true,
+            is_nodiscard: true,
+            prefix_modifiers: vec![],
+            suffix_modifiers: vec![],
+        };
+
+        let is_decl = CppMethodDecl {
+            cpp_name: "is".into(),
+            return_type: "bool".into(),
+            template,
+            body: Some(vec![Arc::new(CppLine::make(
+                "return this->template try_cast<U>().has_value();".to_string(),
+            ))]),
+            brief: Some("Whether the runtime class is assignable to `U`".into()),
+            ..try_cast_decl.clone()
+        };
+
+        self.declarations
+            .push(CppMember::MethodDecl(try_cast_decl).into());
+        self.declarations
+            .push(CppMember::MethodDecl(is_decl).into());
+    }
+
     fn add_type_index_member(&mut self) {
         let tdi: TypeDefinitionIndex = self.self_tag.get_tdi();
 
@@ -1980,6 +2733,7 @@ impl CppType {
             readonly: true,
             const_expr: true,
             value: Some(tdi.index().to_string()),
+            docs: None,
             brief_comment: Some("IL2CPP Metadata Type Index".into()),
             is_private: false,
         };
@@ -1988,6 +2742,26 @@ impl CppType {
             .push(CppMember::FieldDecl(il2cpp_metadata_type_index).into());
     }
 
+    /// Number of instance fields in the metadata, so consumers can assert
+    /// the layout they were written against hasn't gained or lost fields
+    fn add_field_count_member(&mut self, field_count: usize) {
+        let field_count_member = CppFieldDecl {
+            cpp_name: "__cordl_field_count".into(),
+            field_ty: "::std::size_t".into(),
+            offset: None,
+            instance: false,
+            readonly: true,
+            const_expr: true,
+            value: Some(field_count.to_string()),
+            docs: None,
+            brief_comment: Some("Number of instance fields".into()),
+            is_private: false,
+        };
+
+        self.declarations
+            .push(CppMember::FieldDecl(field_count_member).into());
+    }
+
     fn delete_default_ctor(&mut self) {
         let t = &self.cpp_name_components.name;
 
@@ -2019,6 +2793,12 @@ impl CppType {
             return;
         }
 
+        // abstract types are only constructed through derived types,
+        // which still get the protected default ctor
+        if self.is_abstract {
+            return;
+        }
+
         let params_no_default = m_params
             .iter()
             .cloned()
@@ -2036,6 +2816,7 @@ impl CppType {
             parameters: params_no_default,
             template: template.cloned(),
             body: None, // TODO:
+            docs: None,
             brief: None,
             is_no_except: false,
             is_constexpr: false,
@@ -2046,6 +2827,7 @@ impl CppType {
 
             is_virtual: false,
             is_inline: true,
+            is_nodiscard: false,
             prefix_modifiers: vec![],
             suffix_modifiers: vec![],
         };
@@ -2089,14 +2871,22 @@ impl CppType {
         self.cpp_name_components
             .namespace
             .clone()
-            .unwrap_or("GlobalNamespace".to_owned())
+            .expect("C++ namespace is always set, see `CppGenerationConfig::namespace_cpp`")
+    }
+
+    /// Whether this is `System.ValueType` or `System.Enum`, compared by TDI
+    /// so it holds even when the metadata names are stripped
+    pub fn is_value_type_base(&self, metadata: &CordlMetadata) -> bool {
+        let tdi = self.self_tag.get_tdi();
+        tdi == metadata.value_type_tdi || tdi == metadata.enum_tdi
     }
 
+    /// C# namespace, empty for the global namespace
     pub fn namespace(&self) -> String {
         self.cs_name_components
             .namespace
             .clone()
-            .unwrap_or("GlobalNamespace".to_owned())
+            .unwrap_or_default()
     }
 
     pub fn cpp_name(&self) -> &std::string::String {
@@ -2108,8 +2898,11 @@ impl CppType {
     }
 }
 
-impl ToString for CsValue {
-    fn to_string(&self) -> String {
+impl CsValue {
+    /// C++ literal of the value, primitives are named as `CppGenerationConfig::primitive_name`
+    pub fn to_cpp_string(&self, config: &CppGenerationConfig) -> String {
+        let name = |ty, default| config.primitive_name(ty, default);
+
         match self {
             CsValue::String(s) => format!("u\"{s}\""),
             CsValue::Char(s) => format!("u'{s}'"),
@@ -2118,14 +2911,56 @@ impl ToString for CsValue {
                 false => "false",
             }
             .to_string(),
-            CsValue::U8(x) => format!("static_cast<uint8_t>(0x{x:x}u)"),
-            CsValue::U16(x) => format!("static_cast<uint16_t>(0x{x:x}u)"),
-            CsValue::U32(x) => format!("static_cast<uint32_t>(0x{x:x}u)"),
-            CsValue::U64(x) => format!("static_cast<uint64_t>(0x{x:x}u)"),
-            CsValue::I8(x) => format!("static_cast<int8_t>(0x{x:x})"),
-            CsValue::I16(x) => format!("static_cast<int16_t>(0x{x:x})"),
-            CsValue::I32(x) => format!("static_cast<int32_t>(0x{x:x})"),
-            CsValue::I64(x) => format!("static_cast<int64_t>(0x{x:x})"),
+            CsValue::U8(x) => format!(
+                "static_cast<{}>(0x{x:x}u)",
+                name(Il2CppTypeEnum::U1, "uint8_t")
+            ),
+            CsValue::U16(x) => format!(
+                "static_cast<{}>(0x{x:x}u)",
+                name(Il2CppTypeEnum::U2, "uint16_t")
+            ),
+            CsValue::U32(x) => format!(
+                "static_cast<{}>(0x{x:x}u)",
+                name(Il2CppTypeEnum::U4, "uint32_t")
+            ),
+            // suffixed so the literal already has the width and signedness of the value
+            CsValue::U64(x) => format!(
+                "static_cast<{}>(0x{x:x}ull)",
+                name(Il2CppTypeEnum::U8, "uint64_t")
+            ),
+            // min values can't be written as a literal without overflowing
+            CsValue::I8(i8::MIN) => format!(
+                "std::numeric_limits<{}>::min()",
+                name(Il2CppTypeEnum::I1, "int8_t")
+            ),
+            CsValue::I16(i16::MIN) => format!(
+                "std::numeric_limits<{}>::min()",
+                name(Il2CppTypeEnum::I2, "int16_t")
+            ),
+            CsValue::I32(i32::MIN) => format!(
+                "std::numeric_limits<{}>::min()",
+                name(Il2CppTypeEnum::I4, "int32_t")
+            ),
+            CsValue::I64(i64::MIN) => format!(
+                "std::numeric_limits<{}>::min()",
+                name(Il2CppTypeEnum::I8, "int64_t")
+            ),
+            CsValue::I8(x) => format!(
+                "static_cast<{}>(0x{x:x})",
+                name(Il2CppTypeEnum::I1, "int8_t")
+            ),
+            CsValue::I16(x) => format!(
+                "static_cast<{}>(0x{x:x})",
+                name(Il2CppTypeEnum::I2, "int16_t")
+            ),
+            CsValue::I32(x) => format!(
+                "static_cast<{}>(0x{x:x})",
+                name(Il2CppTypeEnum::I4, "int32_t")
+            ),
+            CsValue::I64(x) => format!(
+                "static_cast<{}>(0x{x:x}ll)",
+                name(Il2CppTypeEnum::I8, "int64_t")
+            ),
             CsValue::F32(f) => {
                 if *f == f32::INFINITY {
                     return "INFINITY".to_owned();
@@ -2138,7 +2973,10 @@ impl ToString for CsValue {
                 }
                 // make it include at least one decimal place
 
-                format!("static_cast<float_t>({f:?}f)")
+                format!(
+                    "static_cast<{}>({f:?}f)",
+                    name(Il2CppTypeEnum::R4, "float_t")
+                )
             }
             CsValue::F64(f) => {
                 if *f == f64::INFINITY {
@@ -2151,11 +2989,354 @@ impl ToString for CsValue {
                     return "NAN".to_owned();
                 }
 
-                format!("static_cast<double_t>({f:.1})")
+                format!(
+                    "static_cast<{}>({f:.1})",
+                    name(Il2CppTypeEnum::R8, "double_t")
+                )
             }
-            CsValue::Object(_bytes) => todo!(),
-            CsValue::ValueType(_bytes) => todo!(),
+            // raw blob, `std::bit_cast` to the field type at the use site
+            CsValue::Object(bytes) | CsValue::ValueType(bytes) => format!(
+                "::std::array<uint8_t, 0x{:x}>{{{}}}",
+                bytes.len(),
+                bytes.iter().map(|b| format!("0x{b:x}")).join(", ")
+            ),
             CsValue::Null => "{}".to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, path::Path};
+
+    use brocolib::global_metadata::TypeDefinitionIndex;
+
+    use crate::{
+        data::name_components::NameComponents,
+        generate::{cs_type_tag::CsTypeTag, metadata::PointerSize, writer::Writer},
+    };
+
+    use super::*;
+
+    fn size_info(size: u32, alignment: u8) -> SizeInfo {
+        SizeInfo {
+            instance_size: size,
+            native_size: size as i32,
+            calculated_instance_size: size,
+            calculated_native_size: size as i32,
+            minimum_alignment: alignment,
+            natural_alignment: alignment,
+            packing: None,
+            specified_packing: None,
+        }
+    }
+
+    /// Empty value type `::Test::{name}`
+    fn value_type(name: &str, size_info: SizeInfo, packing: Option<u32>) -> CppType {
+        let tag = CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(0));
+
+        CppType {
+            declarations: vec![],
+            nonmember_declarations: vec![],
+            implementations: vec![],
+            nonmember_implementations: vec![],
+            parent: None,
+            interfaces: vec![],
+            is_value_type: true,
+            is_enum_type: false,
+            is_reference_type: false,
+            is_interface: false,
+            is_abstract: false,
+            is_byref_like: false,
+            requirements: CppTypeRequirements {
+                self_tag: tag,
+                forward_declares: Default::default(),
+                required_def_includes: Default::default(),
+                required_impl_includes: Default::default(),
+                depending_types: Default::default(),
+            },
+            self_tag: tag,
+            generic_instantiations_args_types: None,
+            method_generic_instantiation_map: Default::default(),
+            cpp_template: None,
+            cs_name_components: NameComponents {
+                namespace: Some("Test".to_string()),
+                name: name.to_string(),
+                ..Default::default()
+            },
+            cpp_name_components: CppNameComponents {
+                namespace: Some("::Test".to_string()),
+                name: name.to_string(),
+                ..Default::default()
+            },
+            prefix_comments: vec![],
+            docs: None,
+            deprecated: None,
+            packing,
+            packing_style: PackingStyle::Pragma,
+            alignment: None,
+            size_info: Some(size_info),
+        }
+    }
+
+    /// The definition as written to a header
+    fn write_def(cpp_type: &CppType, file_name: &str) -> String {
+        let path = std::env::temp_dir().join(file_name);
+        let mut writer = Writer::new(File::create(&path).unwrap(), Default::default());
+        cpp_type.write_def(&mut writer).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn simd_value_type_is_aligned() {
+        let mut cpp_type = value_type("Vector128", size_info(0x10, 16), None);
+        cpp_type.alignment = cpp_type.required_alignment(PointerSize::Bytes8);
+        cpp_type.create_size_assert();
+
+        assert_eq!(cpp_type.alignment, Some(16));
+
+        let def = write_def(&cpp_type, "cordl_simd_alignas_test.hpp");
+        assert!(def.contains("struct alignas(16) CORDL_TYPE Vector128 {"));
+        // alignas doesn't change the size il2cpp expects
+        assert!(def.contains(
+            "static_assert(::cordl_internals::size_check_v<::Test::Vector128, 0x10>, \"Size mismatch!\");"
+        ));
+    }
+
+    fn instance_field(name: &str, ty: &str, offset: u32) -> CppFieldDecl {
+        CppFieldDecl {
+            cpp_name: name.to_string(),
+            field_ty: ty.to_string(),
+            offset: Some(offset),
+            instance: true,
+            readonly: false,
+            const_expr: false,
+            value: None,
+            docs: None,
+            brief_comment: None,
+            is_private: false,
+        }
+    }
+
+    /// Definition of a type packed to 4 in `packing_style`
+    fn write_packed(packing_style: PackingStyle, file_name: &str) -> String {
+        let mut cpp_type = value_type("Packed", size_info(0xc, 4), Some(4));
+        cpp_type.packing_style = packing_style;
+
+        let fields = [
+            instance_field("a", "uint8_t", 0),
+            instance_field("b", "int64_t", 4),
+        ];
+        cpp_fields::add_field_offset_asserts(&mut cpp_type, &fields);
+        cpp_type.declarations.extend(
+            fields
+                .into_iter()
+                .map(|f| Arc::new(CppMember::FieldDecl(f))),
+        );
+        cpp_type.create_size_assert();
+
+        write_def(&cpp_type, file_name)
+    }
+
+    fn static_asserts(def: &str) -> Vec<&str> {
+        def.lines()
+            .filter(|line| line.trim_start().starts_with("static_assert"))
+            .collect()
+    }
+
+    #[test]
+    fn packing_styles_assert_the_same_layout() {
+        let pragma = write_packed(PackingStyle::Pragma, "cordl_packing_pragma_test.hpp");
+        let attribute = write_packed(PackingStyle::Attribute, "cordl_packing_attribute_test.hpp");
+
+        assert!(pragma.contains("#pragma pack(push, 4)"));
+        assert!(pragma.contains("struct CORDL_TYPE Packed {"));
+        assert!(
+            attribute.contains("struct __attribute__((packed, aligned(4))) CORDL_TYPE Packed {")
+        );
+        assert!(!attribute.contains("#pragma pack"));
+        assert!(!attribute.contains("alignas"));
+
+        assert_eq!(static_asserts(&pragma).len(), 3);
+        assert_eq!(static_asserts(&pragma), static_asserts(&attribute));
+    }
+
+    #[test]
+    fn field_aligned_value_type_has_no_alignas() {
+        let cpp_type = value_type("Pair", size_info(0x10, 8), None);
+        assert_eq!(cpp_type.required_alignment(PointerSize::Bytes8), None);
+    }
+
+    #[test]
+    fn packed_value_type_has_no_alignas() {
+        let cpp_type = value_type("Packed", size_info(0x10, 16), Some(1));
+        assert_eq!(cpp_type.required_alignment(PointerSize::Bytes8), None);
+    }
+
+    #[test]
+    fn interface_constraint_assert_names_parameter_and_interface() {
+        let assert = CppType::interface_constraint_assert("T", "::System::IDisposable");
+
+        assert_eq!(
+            assert.condition,
+            "::cordl_internals::implements_interface<T, ::System::IDisposable>"
+        );
+        assert_eq!(
+            assert.message.as_deref(),
+            Some("T must implement ::System::IDisposable")
+        );
+    }
+
+    #[test]
+    fn volatile_parameter_is_qualified() {
+        assert_eq!(
+            CppType::param_modifiers(&CsParamFlags::VOLATILE),
+            " volatile"
+        );
+        assert_eq!(CppType::param_modifiers(&CsParamFlags::REF), "");
+
+        let param = CppParam {
+            name: "value".to_string(),
+            ty: "int32_t".to_string(),
+            modifiers: CppType::param_modifiers(&CsParamFlags::VOLATILE).to_string(),
+            def_value: None,
+        };
+        let arg = CppParam::params_as_args(&[param]).next().unwrap();
+        assert_eq!(
+            arg.split_whitespace().collect::<Vec<_>>(),
+            ["int32_t", "volatile", "value"]
+        );
+    }
+
+    #[test]
+    fn nested_name_is_flattened_outermost_first() {
+        assert_eq!(
+            CppType::flattened_nested_name(["Outer", "Inner"], "Color"),
+            "Outer_Inner_Color"
+        );
+    }
+
+    fn write_type_trait(cpp_type: &CppType, file_name: &str) -> String {
+        let path = std::env::temp_dir().join(file_name);
+        let mut writer = Writer::new(File::create(&path).unwrap(), Default::default());
+        cpp_type.write_type_trait(&mut writer).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn value_type_marks_is_value_type() {
+        let cpp_type = value_type("Vector3", size_info(0xc, 4), None);
+
+        assert_eq!(
+            write_type_trait(&cpp_type, "cordl_value_type_trait_test.hpp"),
+            "MARK_VAL_T(::Test::Vector3);\nCORDL_MARK_VAL_T(::Test::Vector3);\n"
+        );
+    }
+
+    #[test]
+    fn generic_value_type_marks_is_value_type() {
+        let mut cpp_type = value_type("KeyValuePair_2", size_info(0x10, 8), None);
+        cpp_type.cpp_template = Some(CppTemplate::make_typenames(
+            ["TKey".to_string(), "TValue".to_string()].into_iter(),
+        ));
+        cpp_type.cpp_name_components.generics = Some(vec!["TKey".into(), "TValue".into()]);
+
+        assert_eq!(
+            write_type_trait(&cpp_type, "cordl_generic_value_type_trait_test.hpp"),
+            "MARK_GEN_VAL_T(::Test::KeyValuePair_2);\nCORDL_MARK_GEN_VAL_T(::Test::KeyValuePair_2);\n"
+        );
+    }
+
+    #[test]
+    fn reference_type_keeps_is_value_type_false() {
+        let mut cpp_type = value_type("Object", size_info(0x10, 8), None);
+        cpp_type.is_value_type = false;
+        cpp_type.is_reference_type = true;
+        cpp_type.cpp_name_components.is_pointer = true;
+
+        assert_eq!(
+            write_type_trait(&cpp_type, "cordl_reference_type_trait_test.hpp"),
+            "MARK_REF_PTR_T(::Test::Object);\n"
+        );
+    }
+
+    /// `::Test::{name}`, a reference type deriving `parent`
+    fn reference_type(name: &str, size: u32, parent: Option<&str>) -> CppType {
+        let mut cpp_type = value_type(name, size_info(size, 8), None);
+        cpp_type.is_value_type = false;
+        cpp_type.is_reference_type = true;
+        cpp_type.parent = parent.map(str::to_string);
+        cpp_type
+    }
+
+    #[test]
+    fn ref_size_asserts_base_fits() {
+        // il2cpp says 0x10, a base of e.g. 0x20 would make the fixup array size underflow
+        let mut cpp_type = reference_type("Derived", 0x10, Some("::Test::Base"));
+        cpp_type.create_ref_size();
+
+        let def = write_def(&cpp_type, "cordl_ref_size_base_test.hpp");
+        let size_assert = def
+            .find("static_assert(0x10 >= sizeof(::Test::Base), \"Size of ::Test::Derived is smaller than its base type ::Test::Base!\");")
+            .expect("base size assert");
+        let fixup = def
+            .find(&format!(
+                "{REFERENCE_TYPE_FIELD_SIZE}[0x10 - sizeof(::Test::Base)]"
+            ))
+            .expect("fixup array");
+        // the assert explains the failure before the array size underflows
+        assert!(size_assert < fixup);
+    }
+
+    #[test]
+    fn ref_size_without_base_has_no_assert() {
+        let mut cpp_type = reference_type("Root", 0x10, None);
+        cpp_type.create_ref_size();
+
+        let def = write_def(&cpp_type, "cordl_ref_size_root_test.hpp");
+        assert!(def.contains(&format!("{REFERENCE_TYPE_FIELD_SIZE}[0x10]")));
+        assert!(!def.contains("sizeof("));
+    }
+
+    #[test]
+    fn signed_min_values_use_numeric_limits() {
+        let config = CppGenerationConfig::new(Path::new("out"));
+
+        assert_eq!(
+            CsValue::I32(i32::MIN).to_cpp_string(&config),
+            "std::numeric_limits<int32_t>::min()"
+        );
+        assert_eq!(
+            CsValue::I64(i64::MIN).to_cpp_string(&config),
+            "std::numeric_limits<int64_t>::min()"
+        );
+        assert_eq!(
+            CsValue::I8(i8::MIN).to_cpp_string(&config),
+            "std::numeric_limits<int8_t>::min()"
+        );
+        // one above min still fits a literal
+        assert_eq!(
+            CsValue::I32(i32::MIN + 1).to_cpp_string(&config),
+            "static_cast<int32_t>(0x80000001)"
+        );
+    }
+
+    #[test]
+    fn numeric_limits_use_primitive_overrides() {
+        let mut config = CppGenerationConfig::new(Path::new("out"));
+        config
+            .primitive_overrides
+            .insert("I4".to_string(), "::il2cpp::int32".to_string());
+
+        assert_eq!(
+            CsValue::I32(i32::MIN).to_cpp_string(&config),
+            "std::numeric_limits<::il2cpp::int32>::min()"
+        );
+    }
+}
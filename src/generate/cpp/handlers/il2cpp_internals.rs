@@ -149,6 +149,7 @@ fn reference_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         instance: true,
         return_type: format!("{il2cpp_t}*"),
 
+        docs: None,
         brief: Some(format!("Conversion into il2cpp il2cpp_t {il2cpp_t}")),
         body: Some(vec![Arc::new(CppLine::make(operator_body))]), // TODO:
         is_const: false,
@@ -158,6 +159,7 @@ fn reference_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         is_explicit_operator: false,
 
         is_inline: true,
+        is_nodiscard: false,
         is_no_except: true,
         parameters: vec![],
         prefix_modifiers: vec![],
@@ -171,6 +173,7 @@ fn reference_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         instance: true,
         return_type: format!("{il2cpp_t} const*"),
 
+        docs: None,
         brief: Some(format!("Conversion into il2cpp il2cpp_t {il2cpp_t}")),
         body: Some(vec![Arc::new(CppLine::make(const_operator_body))]), // TODO:
         is_const: true,
@@ -180,6 +183,7 @@ fn reference_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         is_explicit_operator: false,
 
         is_inline: true,
+        is_nodiscard: false,
         is_no_except: true,
         parameters: vec![],
         prefix_modifiers: vec![],
@@ -234,6 +238,7 @@ fn value_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         instance: true,
         return_type: il2cpp_t.to_string(),
 
+        docs: None,
         brief: Some(format!("Conversion into il2cpp il2cpp_t {il2cpp_t}")),
         body: Some(vec![Arc::new(CppLine::make(operator_body))]), // TODO:
         is_const: false,
@@ -243,6 +248,7 @@ fn value_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         is_explicit_operator: false,
 
         is_inline: true,
+        is_nodiscard: false,
         is_no_except: true,
         parameters: vec![],
         prefix_modifiers: vec![],
@@ -256,6 +262,7 @@ fn value_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         instance: true,
         return_type: il2cpp_t.to_string(),
 
+        docs: None,
         brief: Some(format!("Conversion into il2cpp il2cpp_t {il2cpp_t}")),
         body: Some(vec![Arc::new(CppLine::make(const_operator_body))]), // TODO:
         is_const: true,
@@ -265,6 +272,7 @@ fn value_type_convert(cpp_type: &mut CppType, il2cpp_t: &str) {
         is_explicit_operator: false,
 
         is_inline: true,
+        is_nodiscard: false,
         is_no_except: true,
         parameters: vec![],
         prefix_modifiers: vec![],
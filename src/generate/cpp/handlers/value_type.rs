@@ -7,7 +7,7 @@ use crate::generate::{
         cpp_context_collection::CppContextCollection, cpp_members::CppMember, cpp_type::CppType,
     },
     cs_type_tag::CsTypeTag,
-    metadata::{CordlMetadata, Il2cppFullName},
+    metadata::CordlMetadata,
 };
 
 use log::info;
@@ -19,17 +19,8 @@ pub fn register_value_type(
     info!("Registering System.ValueType handler!");
     info!("Registering System.Enum handler!");
 
-    let value_type_tdi = metadata
-        .name_to_tdi
-        .get(&Il2cppFullName("System", "ValueType"))
-        .expect("No System.ValueType TDI found");
-    let enum_type_tdi = metadata
-        .name_to_tdi
-        .get(&Il2cppFullName("System", "Enum"))
-        .expect("No System.ValueType TDI found");
-
-    let value_type_tag = CsTypeTag::TypeDefinitionIndex(*value_type_tdi);
-    let enum_type_tag = CsTypeTag::TypeDefinitionIndex(*enum_type_tdi);
+    let value_type_tag = CsTypeTag::TypeDefinitionIndex(metadata.value_type_tdi);
+    let enum_type_tag = CsTypeTag::TypeDefinitionIndex(metadata.enum_tdi);
 
     let value_cpp_type = cpp_context_collection
         .get_cpp_type_mut(value_type_tag)
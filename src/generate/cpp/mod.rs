@@ -1,6 +1,6 @@
 pub mod cpp_main;
 
-mod config;
+pub mod config;
 mod cpp_context;
 mod cpp_context_collection;
 mod cpp_fields;
@@ -10,3 +10,4 @@ mod cpp_name_components;
 mod cpp_name_resolver;
 mod cpp_type;
 mod handlers;
+pub mod prebuilt_manifest;
@@ -0,0 +1,127 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use color_eyre::eyre::{bail, Context};
+
+use super::config::CppTypeOverride;
+
+/// Types already generated into a prebuilt header set, e.g. mscorlib and Unity,
+/// which resolve to the prebuilt headers instead of being generated again
+///
+/// One type per line as `CS_NAME CPP_NAME INCLUDE`,
+/// e.g. `System.Object ::System::Object System/zzzz__Object_def.hpp`
+/// Includes are relative to the prebuilt include root.
+/// Empty lines and lines starting with `#` are ignored
+#[derive(Debug, Default)]
+pub struct PrebuiltManifest {
+    /// Full C# name -> prebuilt type
+    pub types: HashMap<String, CppTypeOverride>,
+}
+
+impl PrebuiltManifest {
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("prebuilt manifest not found {}", path.display()))?;
+
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> color_eyre::Result<Self> {
+        let mut types = HashMap::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let [cs_name, cpp_name, include] = line.split_whitespace().collect::<Vec<_>>()[..]
+            else {
+                bail!(
+                    "Expected `CS_NAME CPP_NAME INCLUDE` on line {}: {line}",
+                    line_number + 1
+                );
+            };
+
+            types.insert(
+                cs_name.to_string(),
+                CppTypeOverride {
+                    cpp_name: cpp_name.to_string(),
+                    include: include.into(),
+                },
+            );
+        }
+
+        Ok(Self { types })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn parses_one_type_per_line() {
+        let manifest = PrebuiltManifest::parse(
+            "# bs-cordl mscorlib\n\
+             System.Object ::System::Object System/zzzz__Object_def.hpp\n\
+             \n\
+             \t UnityEngine.Vector3   ::UnityEngine::Vector3 UnityEngine/zzzz__Vector3_def.hpp \n",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.types.len(), 2);
+        let vector = &manifest.types["UnityEngine.Vector3"];
+        assert_eq!(vector.cpp_name, "::UnityEngine::Vector3");
+        assert_eq!(
+            vector.include,
+            Path::new("UnityEngine/zzzz__Vector3_def.hpp")
+        );
+    }
+
+    #[test]
+    fn malformed_line_names_its_line_number() {
+        let error = PrebuiltManifest::parse("# header\nSystem.Object ::System::Object\n")
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("line 2"), "{error}");
+    }
+
+    #[test]
+    fn explicit_overrides_take_precedence() {
+        use crate::generate::generation::{GenerationConfig, TargetLang};
+
+        let path = std::env::temp_dir().join("cordl_prebuilt_manifest_test.txt");
+        fs::write(
+            &path,
+            "System.Object ::System::Object System/zzzz__Object_def.hpp\n\
+             UnityEngine.Vector3 ::UnityEngine::Vector3 UnityEngine/zzzz__Vector3_def.hpp\n",
+        )
+        .unwrap();
+
+        let mut config = GenerationConfig::new(TargetLang::Cpp);
+        config.prebuilt_manifest = Some(path);
+        config.type_overrides.insert(
+            "UnityEngine.Vector3".to_string(),
+            CppTypeOverride {
+                cpp_name: "::Sombrero::FastVector3".to_string(),
+                include: "sombrero/shared/FastVector3.hpp".into(),
+            },
+        );
+
+        let type_overrides = config.cpp_config(Path::new("out")).unwrap().type_overrides;
+        assert_eq!(type_overrides["System.Object"].cpp_name, "::System::Object");
+        assert_eq!(
+            type_overrides["UnityEngine.Vector3"].cpp_name,
+            "::Sombrero::FastVector3"
+        );
+    }
+
+    #[test]
+    fn missing_manifest_is_an_error() {
+        let error = PrebuiltManifest::load(Path::new("does/not/exist.txt")).unwrap_err();
+        assert!(error.to_string().contains("prebuilt manifest not found"));
+    }
+}
@@ -1,5 +1,8 @@
 use core::panic;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
 use brocolib::{
     global_metadata::TypeDefinitionIndex,
@@ -13,6 +16,7 @@ use crate::{data::type_resolver::TypeResolver, generate::cs_type::CsType};
 use super::{
     context::TypeContext,
     cs_type_tag::{CsTypeTag, GenericInstantiation},
+    diagnostics::TimingStage,
     metadata::CordlMetadata,
     type_extensions::TypeDefinitionExtensions,
 };
@@ -43,8 +47,15 @@ impl TypeContextCollection {
         let type_resolver = TypeResolver {
             cordl_metadata: metadata,
             collection: self,
+            resolving_generic_classes: Default::default(),
         };
+        let start = Instant::now();
         cpp_type.fill_from_il2cpp(&type_resolver);
+        metadata.timings.record(
+            TimingStage::Fill,
+            || cpp_type.cs_name_components.combine_all(),
+            start.elapsed(),
+        );
 
         self.filled_types.insert(tag);
         self.filling_types.remove(&tag.clone());
@@ -271,6 +282,7 @@ impl TypeContextCollection {
         let type_resolver = TypeResolver {
             cordl_metadata: metadata,
             collection: self,
+            resolving_generic_classes: Default::default(),
         };
         new_cpp_type.add_class_generic_inst(&generic_inst.types, &type_resolver);
         new_cpp_type.self_tag = generic_class_ty_data;
@@ -352,6 +364,7 @@ impl TypeContextCollection {
             let type_resolver = TypeResolver {
                 cordl_metadata: metadata,
                 collection,
+                resolving_generic_classes: Default::default(),
             };
             cpp_type.add_method_generic_inst(method_spec, &type_resolver);
             cpp_type.create_method(method_index, &type_resolver, true);
@@ -529,6 +542,47 @@ impl TypeContextCollection {
             .get_mut(&self.get_context_root_tag(context_tag))
     }
 
+    ///
+    /// Removes every context that isn't needed by the given types
+    /// Walks the dependencies of each type transitively, keeping the contexts they live in
+    ///
+    pub fn retain_dependencies_of(&mut self, roots: &[CsTypeTag]) {
+        let mut visited: HashSet<CsTypeTag> = HashSet::new();
+        let mut queue = roots.to_vec();
+
+        while let Some(tag) = queue.pop() {
+            if !visited.insert(tag) {
+                continue;
+            }
+
+            let Some(context) = self.get_context(tag) else {
+                warn!("No context found for {tag:?}, skipping");
+                continue;
+            };
+
+            // the whole context is written at once, so all of its types are needed
+            let dependencies = context
+                .get_types()
+                .values()
+                .flat_map(|t| {
+                    std::iter::once(t.self_tag)
+                        .chain(t.requirements.depending_types.iter().copied())
+                })
+                .filter(|t| !visited.contains(t))
+                .collect_vec();
+
+            queue.extend(dependencies);
+        }
+
+        let kept_contexts: HashSet<CsTypeTag> = visited
+            .into_iter()
+            .map(|t| self.get_context_root_tag(t))
+            .collect();
+
+        self.all_contexts.retain(|tag, _| kept_contexts.contains(tag));
+        self.alias_context.retain(|_, root| kept_contexts.contains(root));
+    }
+
     pub fn new() -> TypeContextCollection {
         TypeContextCollection {
             all_contexts: Default::default(),
@@ -12,6 +12,8 @@ use super::cs_type_tag::CsTypeTag;
 #[derive(Debug, Eq, Hash, PartialEq, Clone, Default, PartialOrd, Ord)]
 pub struct CsGenericTemplate {
     pub names: Vec<(CsGenericTemplateType, String)>,
+    /// Names with a `new()` constraint
+    pub default_constructible: Vec<String>,
 }
 
 #[derive(Debug, Eq, Hash, PartialEq, Clone, Default, PartialOrd, Ord)]
@@ -28,6 +30,7 @@ impl CsGenericTemplate {
                 .into_iter()
                 .map(|s| (CsGenericTemplateType::AnyType, s))
                 .collect(),
+            default_constructible: vec![],
         }
     }
     pub fn make_ref_types(names: impl Iterator<Item = String>) -> Self {
@@ -36,9 +39,19 @@ impl CsGenericTemplate {
                 .into_iter()
                 .map(|s| (CsGenericTemplateType::ReferenceType, s))
                 .collect(),
+            default_constructible: vec![],
         }
     }
 
+    pub fn with_default_constructible(mut self, names: impl Iterator<Item = String>) -> Self {
+        self.default_constructible = names.collect();
+        self
+    }
+
+    pub fn is_default_constructible(&self, name: &str) -> bool {
+        self.default_constructible.iter().any(|n| n == name)
+    }
+
     pub fn just_names(&self) -> impl Iterator<Item = &String> {
         self.names.iter().map(|(_constraint, t)| t)
     }
@@ -114,6 +127,23 @@ pub enum CsValue {
     Null,
 }
 
+impl CsValue {
+    /// Integer value widened to i128, `None` if not an integer
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            CsValue::U8(v) => Some(*v as i128),
+            CsValue::U16(v) => Some(*v as i128),
+            CsValue::U32(v) => Some(*v as i128),
+            CsValue::U64(v) => Some(*v as i128),
+            CsValue::I8(v) => Some(*v as i128),
+            CsValue::I16(v) => Some(*v as i128),
+            CsValue::I32(v) => Some(*v as i128),
+            CsValue::I64(v) => Some(*v as i128),
+            _ => None,
+        }
+    }
+}
+
 /// Explicit layout
 /// il2cpp basically turns each field into 2 structs within a union:
 /// 1 which is packed with size 1, and padded with offset to fit to the end
@@ -146,6 +176,8 @@ pub struct CsField {
 
     pub value: Option<CsValue>,
     pub brief_comment: Option<String>,
+    /// Summary from the xml documentation
+    pub docs: Option<String>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -155,8 +187,9 @@ pub struct CsProperty {
     pub instance: bool,
     pub getter: Option<(MethodIndex, String)>,
     pub setter: Option<(MethodIndex, String)>,
-    /// Whether this property is one that's indexable (accessor methods take an index argument)
-    pub indexable: bool,
+    /// Number of index arguments the accessor methods take, e.g. 2 for `this[int x, int y]`
+    /// 0 if this isn't an indexer
+    pub index_count: usize,
     pub brief_comment: Option<String>,
 }
 
@@ -166,6 +199,10 @@ bitflags! {
         const REF = 1;
         const IN = 1 << 1;
         const OUT = 1 << 2;
+        /// C# `params T[]` tail
+        const PARAMS = 1 << 3;
+        /// `modreq(IsVolatile)`, see `CsType::param_modifiers`
+        const VOLATILE = 1 << 4;
     }
 }
 
@@ -207,12 +244,23 @@ pub struct CsMethod {
     pub parameters: Vec<CsParam>,
     pub instance: bool,
     pub template: Option<CsGenericTemplate>,
+    /// Generic arguments if this is a closed instantiation of a generic method,
+    /// which is emitted next to the open method under a suffixed name
+    pub generic_inst: Option<Vec<ResolvedType>>,
     pub brief: Option<String>,
 
     pub declaring_type: CsTypeTag,
 
     pub method_data: CsMethodData,
     pub method_flags: CSMethodFlags,
+    /// Summary from the xml documentation
+    pub docs: Option<String>,
+    /// Returns a reference type that is known to legitimately be null
+    pub nullable_return: bool,
+    /// `where T : IFoo` constraints of the method's generic parameters, by parameter name
+    pub generic_interface_constraints: Vec<(String, ResolvedType)>,
+    /// `[Obsolete]` message, empty if it has none
+    pub obsolete: Option<String>,
 }
 
 // TODO: Generics
@@ -7,18 +7,19 @@ use byteorder::ReadBytesExt;
 
 use brocolib::{
     global_metadata::{
-        FieldIndex, Il2CppFieldDefinition, Il2CppTypeDefinition, MethodIndex, ParameterIndex,
-        TypeDefinitionIndex,
+        FieldIndex, Il2CppFieldDefinition, Il2CppGenericParameter, Il2CppTypeDefinition,
+        MethodIndex, ParameterIndex, TypeDefinitionIndex,
     },
     runtime_metadata::{Il2CppMethodSpec, Il2CppType, Il2CppTypeEnum, TypeData},
 };
+use color_eyre::eyre::{bail, eyre};
 use itertools::Itertools;
 use log::{debug, info, warn};
 
 use crate::{
     data::{
         name_components::NameComponents,
-        type_resolver::{ResolvedType, TypeResolver, TypeUsage},
+        type_resolver::{ResolvedType, ResolvedTypeData, TypeResolver, TypeUsage},
     },
     generate::{
         cs_members::CsField,
@@ -34,10 +35,12 @@ use super::{
         CsParamFlags, CsProperty, CsValue,
     },
     cs_type_tag::CsTypeTag,
+    custom_attributes::CustomAttributeReader,
     metadata::CordlMetadata,
     offsets::{self, SizeInfo},
     type_extensions::{
-        MethodDefintionExtensions, TypeDefinitionExtensions, TypeDefinitionIndexExtensions,
+        GenericParameterExtensions, MethodDefintionExtensions, TypeDefinitionExtensions,
+        TypeDefinitionIndexExtensions,
     },
 };
 
@@ -80,13 +83,21 @@ pub struct CsType {
     pub is_enum_type: bool,
     pub is_reference_type: bool,
     pub is_compiler_generated: bool,
+    /// `ref struct` types, these may never be boxed
+    pub is_byref_like: bool,
 
     pub requirements: CsTypeRequirements,
 
     pub enum_backing_type: Option<Il2CppTypeEnum>,
+    /// Configured default variant, see `CordlMetadata::enum_defaults`
+    pub enum_default: Option<String>,
+    /// Placeholders for unsupported metadata in lenient mode, see `CordlMetadata::unimplemented_fallback`
+    pub placeholder_comments: Vec<String>,
     pub parent: Option<ResolvedType>,
     pub interfaces: Vec<ResolvedType>,
     pub generic_template: Option<CsGenericTemplate>, // Names of templates e.g T, TKey etc.
+    /// `where T : IFoo` constraints of the generic parameters, by parameter name
+    pub generic_interface_constraints: Vec<(String, ResolvedType)>,
 
     /// contains the array of generic Il2CppType indexes
     ///
@@ -95,7 +106,17 @@ pub struct CsType {
     pub method_generic_instantiation_map: HashMap<MethodIndex, Vec<ResolvedType>>, // MethodIndex -> Generic Args
 
     pub is_interface: bool,
-    pub nested_types: HashSet<CsTypeTag>,
+    /// abstract classes and interfaces, these can't be constructed directly
+    pub is_abstract: bool,
+    /// Sorted by name so output is stable
+    pub nested_types: Vec<CsTypeTag>,
+
+    /// `ToString()` override declared by this type, if any
+    pub to_string_method: Option<MethodIndex>,
+    /// Summary from the xml documentation
+    pub docs: Option<String>,
+    /// `[Obsolete]` message, empty if it has none
+    pub obsolete: Option<String>,
 }
 
 impl CsType {
@@ -110,7 +131,7 @@ impl CsType {
         &self.cs_name_components.name
     }
 
-    pub fn get_nested_types(&self) -> &HashSet<CsTypeTag> {
+    pub fn get_nested_types(&self) -> &[CsTypeTag] {
         &self.nested_types
     }
 
@@ -202,6 +223,11 @@ impl CsType {
             CsGenericTemplate::make_typenames(
                 g.iter().map(|g| g.name(metadata.metadata).to_string()),
             )
+            .with_default_constructible(
+                g.iter()
+                    .filter(|g| g.has_default_constructor_constraint())
+                    .map(|g| g.name(metadata.metadata).to_string()),
+            )
         });
 
         let ns = t.namespace(metadata.metadata);
@@ -233,6 +259,17 @@ impl CsType {
         // Every type should have a valid metadata size, even if it is 0
         let size_info: offsets::SizeInfo = offsets::get_size_info(t, tdi, None, metadata);
 
+        let enum_default = t
+            .is_enum_type()
+            .then(|| {
+                metadata
+                    .enum_defaults
+                    .get(&cs_name_components.clone().remove_generics().combine_all())
+            })
+            .flatten()
+            .cloned();
+        let obsolete = CustomAttributeReader::new(metadata).obsolete(tdi, t.token);
+
         // best results of cordl are when specified packing is strictly what is used, but experimentation may be required
         let packing = size_info.specified_packing;
 
@@ -254,6 +291,7 @@ impl CsType {
             is_value_type: t.is_value_type(),
             is_enum_type: t.is_enum_type(),
             is_compiler_generated: t.is_compiler_generated(metadata.metadata),
+            is_byref_like: CustomAttributeReader::new(metadata).is_byref_like(tdi),
             is_reference_type: is_pointer,
             requirements: Default::default(),
 
@@ -261,13 +299,24 @@ impl CsType {
             parent: Default::default(),
 
             is_interface: t.is_interface(),
+            is_abstract: t.is_abstract_type(),
             generic_template: cpp_template,
+            generic_interface_constraints: Default::default(),
 
             generic_instantiations_args_types: Default::default(),
             method_generic_instantiation_map: Default::default(),
 
             nested_types: Default::default(),
             enum_backing_type: None,
+            enum_default,
+            placeholder_comments: Default::default(),
+            to_string_method: None,
+            docs: metadata
+                .xml_docs
+                .as_ref()
+                .and_then(|docs| docs.type_doc(metadata, t))
+                .map(str::to_owned),
+            obsolete,
         };
 
         if t.parent_index == u32::MAX {
@@ -281,7 +330,14 @@ impl CsType {
             .get(t.parent_index as usize)
             .is_none()
         {
-            panic!("NO PARENT! But valid index found: {}", t.parent_index);
+            metadata.unimplemented_fallback(
+                &full_name,
+                &format!(
+                    "{ns}::{name} has no parent but a valid parent index {}",
+                    t.parent_index
+                ),
+            );
+            return None;
         }
 
         Some(cpptype)
@@ -290,6 +346,7 @@ impl CsType {
     pub fn fill_from_il2cpp(&mut self, type_resolver: &TypeResolver) {
         self.make_parents(type_resolver);
         self.make_interfaces(type_resolver);
+        self.make_generic_constraints(type_resolver);
 
         self.make_nested_types(type_resolver);
         self.make_fields(type_resolver);
@@ -298,19 +355,52 @@ impl CsType {
 
         let metadata = type_resolver.cordl_metadata;
         let tdi = self.self_tag.get_tdi();
-        let t = Self::get_type_definition(metadata, tdi);
 
-        if t.element_type_index != u32::MAX && t.is_enum_type() {
-            let element_type = metadata
-                .metadata_registration
-                .types
-                .get(t.element_type_index as usize)
-                .unwrap();
+        self.to_string_method = self
+            .methods
+            .iter()
+            .find(|m| {
+                let method = &metadata.metadata.global_metadata.methods[m.method_index];
+
+                m.name == "ToString"
+                    && m.instance
+                    && m.parameters.is_empty()
+                    && m.template.is_none()
+                    && method.is_override_method()
+            })
+            .map(|m| m.method_index);
 
-            self.enum_backing_type = Some(element_type.ty);
+        let t = Self::get_type_definition(metadata, tdi);
+        if t.is_enum_type() {
+            let backing_type = Self::enum_backing_type_index(metadata, tdi)
+                .and_then(|backing_idx| metadata.metadata_registration.types.get(backing_idx))
+                .map(|element_type| element_type.ty);
+
+            let full_name = t.full_name(metadata.metadata, true);
+            if let Some(backing_type) = backing_type
+                && t.element_type_index == u32::MAX
+            {
+                warn!(
+                    "Enum {full_name} has no element type, inferred {backing_type:?} from values"
+                );
+            }
+
+            self.enum_backing_type =
+                Some(Self::enum_backing_type_or_int32(backing_type, &full_name));
         }
     }
 
+    /// `int32_t`, the C# default, with a warning if no backing type could be found for the enum
+    fn enum_backing_type_or_int32(
+        backing_type: Option<Il2CppTypeEnum>,
+        enum_name: &str,
+    ) -> Il2CppTypeEnum {
+        backing_type.unwrap_or_else(|| {
+            warn!("Enum {enum_name} has no backing type, falling back to int32_t");
+            Il2CppTypeEnum::I4
+        })
+    }
+
     fn make_parameters(
         &mut self,
         method: &brocolib::global_metadata::Il2CppMethodDefinition,
@@ -319,16 +409,41 @@ impl CsType {
         let metadata = type_resolver.cordl_metadata;
         let _tdi = self.self_tag.get_tdi();
 
-        method
+        let mut params = method
             .parameters(metadata.metadata)
             .iter()
             .enumerate()
+            // variadic arguments after the sentinel have no declared parameters to bind
+            .take_while(|(_, param)| {
+                metadata.metadata_registration.types[param.type_index as usize].ty
+                    != Il2CppTypeEnum::Sentinel
+            })
             .map(|(pi, param)| {
                 let param_index = ParameterIndex::new(method.parameter_start.index() + pi as u32);
 
                 self.make_parameter(param, param_index, type_resolver)
             })
-            .collect()
+            .collect_vec();
+
+        Self::fix_parameter_names(params.iter_mut().map(|p| &mut p.name));
+
+        params
+    }
+
+    /// Stripped or obfuscated metadata can leave parameter names empty or repeated,
+    /// those fall back to `arg{position}`
+    fn fix_parameter_names<'a>(names: impl IntoIterator<Item = &'a mut String>) {
+        let mut used_names = HashSet::new();
+        for (pi, name) in names.into_iter().enumerate() {
+            if name.trim().is_empty() || used_names.contains(name.as_str()) {
+                *name = format!("arg{pi}");
+            }
+            while used_names.contains(name.as_str()) {
+                name.push('_');
+            }
+
+            used_names.insert(name.clone());
+        }
     }
 
     fn make_parameter(
@@ -340,16 +455,23 @@ impl CsType {
         let metadata = type_resolver.cordl_metadata;
         let _tdi = self.self_tag.get_tdi();
 
-        let _param_type = metadata
+        let param_type = metadata
             .metadata_registration
             .types
             .get(param.type_index as usize)
             .unwrap();
 
-        let def_value = Self::param_default_value(metadata, param_index);
+        let def_value = Self::param_default_value(
+            metadata,
+            param_index,
+            &self.cs_name_components.combine_all(),
+        );
+
+        let name = param.name(metadata.metadata);
+        let modifiers = Self::param_modifiers(param_type, name);
 
         CsParam {
-            name: param.name(metadata.metadata).to_owned(),
+            name: name.to_owned(),
             def_value,
             il2cpp_ty: type_resolver.resolve_type(
                 self,
@@ -357,8 +479,31 @@ impl CsType {
                 TypeUsage::Parameter,
                 false,
             ),
-            modifiers: CsParamFlags::empty(),
+            modifiers,
+        }
+    }
+
+    /// il2cpp only keeps the number of modreq/modopt custom modifiers, not which ones.
+    /// `in` survives through the param attributes and carries `modreq(InAttribute)`,
+    /// the only modreq compilers put on a by value parameter is `IsVolatile`.
+    /// Any other modifier is ignored
+    fn param_modifiers(param_type: &Il2CppType, name: &str) -> CsParamFlags {
+        let modifiers = match param_type.byref {
+            true if param_type.is_param_in() => return CsParamFlags::IN,
+            true if param_type.is_param_out() => CsParamFlags::OUT,
+            true => CsParamFlags::REF,
+            false if param_type.num_mods > 0 => return CsParamFlags::VOLATILE,
+            false => CsParamFlags::empty(),
+        };
+
+        if param_type.num_mods > 0 {
+            debug!(
+                "Ignoring {} unknown custom modifiers on parameter {name}",
+                param_type.num_mods
+            );
         }
+
+        modifiers
     }
 
     fn make_methods(&mut self, type_resolver: &TypeResolver) {
@@ -500,7 +645,14 @@ impl CsType {
                 let field_index = FieldIndex::new(t.field_start.index() + i as u32);
                 let f_name = field.name(metadata.metadata);
 
-                let f_offset = get_offset(field, i, &mut offset_iter, field_offsets, metadata, t);
+                let f_offset = get_offset(field, i, &mut offset_iter, field_offsets, metadata, t)
+                    .map(|offset| {
+                        metadata
+                            .offset_overrides
+                            .as_ref()
+                            .and_then(|o| o.get(&t.full_name(metadata.metadata, false), f_name))
+                            .unwrap_or(offset)
+                    });
 
                 // calculate / fetch the field size
                 let f_size = get_size(field, self.generic_instantiations_args_types.as_ref(), metadata);
@@ -509,8 +661,34 @@ impl CsType {
                 // TODO: Check a flag to look for default values to speed this up
                 let def_value = Self::field_default_value(metadata, field_index);
 
+                let docs = metadata
+                    .xml_docs
+                    .as_ref()
+                    .and_then(|docs| docs.field_doc(metadata, t, f_name))
+                    .map(str::to_owned);
+
                 assert!(def_value.is_none() || (def_value.is_some() && f_type.is_param_optional()));
 
+                // static readonly primitives with a value in metadata can be constexpr like consts,
+                // anything else has to be read from the class at runtime
+                let is_metadata_const = f_type.is_static()
+                    && f_type.is_init_only()
+                    && def_value.is_some()
+                    && matches!(
+                        f_type.ty,
+                        Il2CppTypeEnum::Boolean
+                            | Il2CppTypeEnum::Char
+                            | Il2CppTypeEnum::I1
+                            | Il2CppTypeEnum::U1
+                            | Il2CppTypeEnum::I2
+                            | Il2CppTypeEnum::U2
+                            | Il2CppTypeEnum::I4
+                            | Il2CppTypeEnum::U4
+                            | Il2CppTypeEnum::I8
+                            | Il2CppTypeEnum::U8
+                            | Il2CppTypeEnum::R4
+                            | Il2CppTypeEnum::R8
+                    );
 
                 CsField {
                     name: f_name.to_owned(),
@@ -518,10 +696,11 @@ impl CsType {
                     offset: f_offset,
                     size: f_size,
                     instance: !f_type.is_static() && !f_type.is_constant(),
-                    readonly: f_type.is_constant(),
+                    readonly: f_type.is_constant() || is_metadata_const,
                     brief_comment: Some(format!("Field {f_name}, offset: 0x{:x}, size: 0x{f_size:x}, def value: {def_value:?}", f_offset.unwrap_or(u32::MAX))),
-                    is_const: f_type.is_constant() || def_value.is_some(),
+                    is_const: f_type.is_constant() || is_metadata_const,
                     value: def_value,
+                    docs,
                 }
             })
             .collect_vec();
@@ -529,6 +708,58 @@ impl CsType {
         for f in fields {
             self.fields.push(f);
         }
+
+        self.rename_shadowing_fields(metadata);
+    }
+
+    /// Instance fields hiding an instance field of a base type get the declaring type name as suffix,
+    /// e.g. `_value` of `Derived` becomes `_value_Derived`.
+    /// Both fields live in the same object, so their generated members must not collide
+    fn rename_shadowing_fields(&mut self, metadata: &CordlMetadata) {
+        let t = Self::get_type_definition(metadata, self.self_tag.get_tdi());
+
+        let mut base_field_names = HashSet::new();
+        let mut base = t;
+        while let Some(parent_ty) = metadata
+            .metadata_registration
+            .types
+            .get(base.parent_index as usize)
+        {
+            let parent_tdi = CsTypeTag::from_type_data(parent_ty.data, metadata.metadata).get_tdi();
+            base = Self::get_type_definition(metadata, parent_tdi);
+
+            base_field_names.extend(
+                base.fields(metadata.metadata)
+                    .iter()
+                    .filter(|f| {
+                        let f_type = &metadata.metadata_registration.types[f.type_index as usize];
+                        !f_type.is_static() && !f_type.is_constant()
+                    })
+                    .map(|f| f.name(metadata.metadata)),
+            );
+        }
+
+        if base_field_names.is_empty() {
+            return;
+        }
+
+        let type_name = t.name(metadata.metadata);
+        for field in self.fields.iter_mut().filter(|f| f.instance) {
+            if base_field_names.contains(field.name.as_str()) {
+                field.name = Self::unshadowed_field_name(&field.name, type_name);
+            }
+        }
+    }
+
+    /// Name for a field hiding a base type field, suffixed with its declaring type name
+    fn unshadowed_field_name(name: &str, declaring_type_name: &str) -> String {
+        // strip the generic arity, e.g. List`1
+        let type_name = declaring_type_name
+            .split('`')
+            .next()
+            .unwrap_or(declaring_type_name);
+
+        format!("{name}_{type_name}")
     }
 
     fn make_parents(&mut self, type_resolver: &TypeResolver) {
@@ -579,12 +810,28 @@ impl CsType {
     }
 
     fn make_interfaces(&mut self, type_resolver: &TypeResolver) {
-        let metadata = type_resolver.cordl_metadata;
         let tdi = self.self_tag.get_tdi();
+
+        let mut interfaces = vec![];
+        self.collect_interfaces(tdi, None, type_resolver, &mut interfaces);
+        self.interfaces = interfaces;
+    }
+
+    /// Interfaces of `tdi` and the interfaces they extend, since base interfaces
+    /// aren't required to be listed again in the metadata.
+    /// `generic_args` substitutes the generic parameters of an instantiated `tdi`
+    fn collect_interfaces(
+        &mut self,
+        tdi: TypeDefinitionIndex,
+        generic_args: Option<&[ResolvedType]>,
+        type_resolver: &TypeResolver,
+        interfaces: &mut Vec<ResolvedType>,
+    ) {
+        let metadata = type_resolver.cordl_metadata;
         let t = &metadata.metadata.global_metadata.type_definitions[tdi];
 
         for &interface_index in t.interfaces(metadata.metadata) {
-            let _int_ty = &metadata.metadata_registration.types[interface_index as usize];
+            let int_ty = &metadata.metadata_registration.types[interface_index as usize];
 
             let resolved = type_resolver.resolve_type(
                 self,
@@ -592,10 +839,85 @@ impl CsType {
                 TypeUsage::TypeName,
                 true,
             );
-            self.interfaces.push(resolved);
+            let resolved = match generic_args {
+                Some(args) => resolved.substitute_class_generics(args),
+                None => resolved,
+            };
+            if interfaces.contains(&resolved) {
+                continue;
+            }
+
+            let interface_args = match &resolved.data {
+                ResolvedTypeData::GenericInst(_, args) => {
+                    Some(args.iter().map(|(arg, _)| arg.clone()).collect_vec())
+                }
+                _ => None,
+            };
+            let interface_tdi = CsTypeTag::from_type_data(int_ty.data, metadata.metadata).get_tdi();
+
+            interfaces.push(resolved);
+            self.collect_interfaces(
+                interface_tdi,
+                interface_args.as_deref(),
+                type_resolver,
+                interfaces,
+            );
         }
     }
 
+    fn make_generic_constraints(&mut self, type_resolver: &TypeResolver) {
+        let metadata = type_resolver.cordl_metadata;
+        let tdi = self.self_tag.get_tdi();
+        let t = &metadata.metadata.global_metadata.type_definitions[tdi];
+
+        if !t.generic_container_index.is_valid() {
+            return;
+        }
+
+        let params = t
+            .generic_container(metadata.metadata)
+            .generic_parameters(metadata.metadata);
+        self.generic_interface_constraints = self.resolve_interface_constraints(
+            params
+                .iter()
+                .map(|param| (param.name(metadata.metadata).to_string(), param)),
+            type_resolver,
+        );
+    }
+
+    /// Resolves the constraints of generic parameters which are interfaces.
+    /// Class constraints are left out, the bindings already only take types deriving the class
+    fn resolve_interface_constraints<'a>(
+        &mut self,
+        params: impl Iterator<Item = (String, &'a Il2CppGenericParameter)>,
+        type_resolver: &TypeResolver,
+    ) -> Vec<(String, ResolvedType)> {
+        let metadata = type_resolver.cordl_metadata;
+
+        params
+            .flat_map(|(name, param)| {
+                param
+                    .constraint_types(metadata.metadata)
+                    .into_iter()
+                    .map(move |ty| (name.clone(), ty))
+            })
+            .filter(|(_, ty)| {
+                let constraint_ty = &metadata.metadata_registration.types[*ty];
+                matches!(
+                    constraint_ty.ty,
+                    Il2CppTypeEnum::Class | Il2CppTypeEnum::Genericinst
+                ) && CsTypeTag::from_type_data(constraint_ty.data, metadata.metadata)
+                    .get_tdi()
+                    .get_type_definition(metadata.metadata)
+                    .is_interface()
+            })
+            .map(|(name, ty)| {
+                let resolved = type_resolver.resolve_type(self, ty, TypeUsage::TypeName, false);
+                (name, resolved)
+            })
+            .collect()
+    }
+
     fn make_nested_types(&mut self, type_resolver: &TypeResolver) {
         let metadata = type_resolver.cordl_metadata;
         let tdi = self.self_tag.get_tdi();
@@ -608,11 +930,12 @@ impl CsType {
         self.nested_types = t
             .nested_types(metadata.metadata)
             .iter()
-            .map(|nested_tdi| {
-                let _nested_td = &metadata.metadata.global_metadata.type_definitions[*nested_tdi];
+            .sorted_by_key(|nested_tdi| {
+                let nested_td = &metadata.metadata.global_metadata.type_definitions[**nested_tdi];
 
-                CsTypeTag::TypeDefinitionIndex(*nested_tdi)
+                nested_td.name(metadata.metadata)
             })
+            .map(|nested_tdi| CsTypeTag::TypeDefinitionIndex(*nested_tdi))
             .collect();
     }
 
@@ -638,9 +961,15 @@ impl CsType {
                 continue;
             }
 
-            let p_type_index = match p_getter {
-                Some(g) => g.return_type as usize,
-                None => p_setter.unwrap().parameters(metadata.metadata)[0].type_index as usize,
+            // indexers take their indices first, the setter's value comes last
+            let (p_type_index, index_count) = match p_getter {
+                Some(g) => (g.return_type as usize, g.parameter_count as usize),
+                None => {
+                    let setter_params = p_setter.unwrap().parameters(metadata.metadata);
+                    let (value_param, index_params) = setter_params.split_last().unwrap();
+
+                    (value_param.type_index as usize, index_params.len())
+                }
             };
 
             let _p_type = metadata
@@ -652,8 +981,6 @@ impl CsType {
             let _abstr = p_getter.is_some_and(|p| p.is_abstract_method())
                 || p_setter.is_some_and(|p| p.is_abstract_method());
 
-            let index = p_getter.is_some_and(|p| p.parameter_count > 0);
-
             // Need to include this type
             let prop_ty = type_resolver.resolve_type(self, p_type_index, TypeUsage::Property, true);
             self.properties.push(CsProperty {
@@ -672,7 +999,7 @@ impl CsType {
                         m.name(metadata.metadata).to_string(),
                     )
                 }),
-                indexable: index,
+                index_count,
                 brief_comment: None,
                 instance: true,
             });
@@ -695,13 +1022,45 @@ impl CsType {
             return;
         }
 
-        let _m_ret_type = metadata
+        let m_ret_type = metadata
             .metadata_registration
             .types
             .get(method.return_type as usize)
             .unwrap();
 
-        let m_params_with_def: Vec<CsParam> = self.make_parameters(method, type_resolver);
+        // full name used to look up per method config, e.g. `UnityEngine.Component.GetComponent`
+        let m_full_name = format!(
+            "{}.{m_name}",
+            self.cs_name_components
+                .clone()
+                .remove_generics()
+                .combine_all()
+        );
+
+        let mut m_params_with_def: Vec<CsParam> = self.make_parameters(method, type_resolver);
+
+        // il2cpp keeps `[ParamArray]` only in custom attribute data
+        let attribute_reader = CustomAttributeReader::new(metadata);
+        let params_param = method.parameters(metadata.metadata).last().filter(|p| {
+            attribute_reader
+                .find(
+                    method.declaring_type,
+                    p.token,
+                    "System",
+                    "ParamArrayAttribute",
+                )
+                .is_some()
+        });
+        if let Some(params_param) = params_param {
+            let ty = &metadata.metadata_registration.types[params_param.type_index as usize];
+
+            match m_params_with_def.last_mut() {
+                Some(last_param) if ty.ty == Il2CppTypeEnum::Szarray && !ty.byref => {
+                    last_param.modifiers |= CsParamFlags::PARAMS;
+                }
+                _ => warn!("{m_full_name} has no array parameter to use as params"),
+            }
+        }
 
         let m_params_no_def: Vec<CsParam> = m_params_with_def
             .iter()
@@ -718,27 +1077,52 @@ impl CsType {
             .generic_container_index
             .is_valid()
             .then(|| match is_generic_method_inst {
-                true => Some(CsGenericTemplate { names: vec![] }),
+                // closed instantiations are concrete methods
+                true => None,
                 false => {
-                    let generics = method
+                    let params = method
                         .generic_container(metadata.metadata)
                         .unwrap()
-                        .generic_parameters(metadata.metadata)
+                        .generic_parameters(metadata.metadata);
+                    let generics = params
+                        .iter()
+                        .map(|param| param.unshadowed_name(metadata.metadata));
+                    let default_constructible = params
                         .iter()
-                        .map(|param| param.name(metadata.metadata).to_string());
+                        .filter(|param| param.has_default_constructor_constraint())
+                        .map(|param| param.unshadowed_name(metadata.metadata));
 
-                    Some(CsGenericTemplate::make_typenames(generics))
+                    Some(
+                        CsGenericTemplate::make_typenames(generics)
+                            .with_default_constructible(default_constructible),
+                    )
                 }
             })
             .flatten();
 
+        let generic_interface_constraints = match template.is_some() {
+            true => {
+                let params = method
+                    .generic_container(metadata.metadata)
+                    .unwrap()
+                    .generic_parameters(metadata.metadata);
+                self.resolve_interface_constraints(
+                    params
+                        .iter()
+                        .map(|param| (param.unshadowed_name(metadata.metadata), param)),
+                    type_resolver,
+                )
+            }
+            false => vec![],
+        };
+
         let _declaring_type_template = self
             .generic_template
             .as_ref()
             .is_some_and(|t| !t.names.is_empty())
             .then(|| self.generic_template.clone());
 
-        let _literal_types = is_generic_method_inst
+        let generic_inst = is_generic_method_inst
             .then(|| {
                 self.method_generic_instantiation_map
                     .get(&method_index)
@@ -765,6 +1149,9 @@ impl CsType {
         if method.is_special_name() {
             flag = flag.union(CSMethodFlags::SPECIAL_NAME);
         }
+        if method.is_abstract_method() {
+            flag = flag.union(CSMethodFlags::ABSTRACT);
+        }
 
         // don't emit method size structs for generic methods
         let is_concrete = !method.is_abstract_method();
@@ -776,6 +1163,31 @@ impl CsType {
             slot: (method.slot != u16::MAX).then_some(method.slot),
         };
 
+        let docs = metadata.xml_docs.as_ref().and_then(|docs| {
+            let declaring_td =
+                &metadata.metadata.global_metadata.type_definitions[method.declaring_type];
+            let param_types = method
+                .parameters(metadata.metadata)
+                .iter()
+                .map(|p| &metadata.metadata_registration.types[p.type_index as usize])
+                .collect_vec();
+
+            docs.method_doc(metadata, declaring_td, m_name, &param_types)
+                .map(str::to_owned)
+        });
+
+        let returns_reference = !m_ret_type.byref
+            && match m_ret_type.ty {
+                Il2CppTypeEnum::Class
+                | Il2CppTypeEnum::Object
+                | Il2CppTypeEnum::String
+                | Il2CppTypeEnum::Szarray
+                | Il2CppTypeEnum::Array => true,
+                Il2CppTypeEnum::Genericinst => !m_ret_type.valuetype,
+                _ => false,
+            };
+        let nullable_return = returns_reference && metadata.nullable_returns.contains(&m_full_name);
+
         let method_decl = CsMethod {
             brief: format!(
                 "Method {m_name}, addr 0x{:x}, size 0x{:x}, virtual {}, abstract: {}, final {}",
@@ -799,7 +1211,12 @@ impl CsType {
             parameters: m_params_no_def.clone(),
             instance: !method.is_static_method(),
             template: template.clone(),
+            generic_inst: None,
             method_data,
+            docs,
+            nullable_return,
+            generic_interface_constraints,
+            obsolete: attribute_reader.obsolete(method.declaring_type, method.token),
         };
 
         // if type is a generic
@@ -818,64 +1235,98 @@ impl CsType {
             self.constructors.push(constructor);
         }
 
-        if !is_generic_method_inst {
-            self.methods.push(method_decl);
+        match generic_inst {
+            Some(args) => {
+                let method_inst = CsMethod {
+                    return_type: method_decl.return_type.substitute_method_generics(&args),
+                    parameters: method_decl
+                        .parameters
+                        .into_iter()
+                        .map(|p| CsParam {
+                            il2cpp_ty: p.il2cpp_ty.substitute_method_generics(&args),
+                            ..p
+                        })
+                        .collect(),
+                    generic_inst: Some(args),
+                    ..method_decl
+                };
+
+                // the same instantiation may be listed more than once
+                if !self.methods.contains(&method_inst) {
+                    self.methods.push(method_inst);
+                }
+            }
+            None if !is_generic_method_inst => self.methods.push(method_decl),
+            None => warn!("No generic arguments for instantiation of {m_full_name}"),
         }
     }
 
+    /// Reads a default value from the metadata blob, see `read_default_value`
     fn default_value_blob(
         metadata: &CordlMetadata,
         ty: &Il2CppType,
         data_index: usize,
         _string_quotes: bool,
         _string_as_u16: bool,
-    ) -> CsValue {
-        let data = &metadata
+    ) -> color_eyre::Result<CsValue> {
+        let data = metadata
             .metadata
             .global_metadata
             .field_and_parameter_default_value_data
-            .as_vec()[data_index..];
+            .as_vec()
+            .get(data_index..)
+            .ok_or_else(|| eyre!("Default value data index {data_index} out of bounds"))?;
+
+        Self::read_default_value(data, ty.ty)
+    }
 
+    /// Reads a value of type `ty` from the start of `data`
+    /// Fails on truncated or malformed data instead of panicking
+    fn read_default_value(data: &[u8], ty: Il2CppTypeEnum) -> color_eyre::Result<CsValue> {
         let mut cursor = Cursor::new(data);
 
-        match ty.ty {
-            Il2CppTypeEnum::Boolean => CsValue::Bool(data[0] != 0),
-            Il2CppTypeEnum::I1 => CsValue::I8(cursor.read_i8().unwrap()),
-            Il2CppTypeEnum::I2 => CsValue::I16(cursor.read_i16::<Endian>().unwrap()),
-            Il2CppTypeEnum::I4 => CsValue::I32(cursor.read_compressed_i32::<Endian>().unwrap()),
+        let value = match ty {
+            Il2CppTypeEnum::Boolean => CsValue::Bool(cursor.read_u8()? != 0),
+            Il2CppTypeEnum::I1 => CsValue::I8(cursor.read_i8()?),
+            Il2CppTypeEnum::I2 => CsValue::I16(cursor.read_i16::<Endian>()?),
+            Il2CppTypeEnum::I4 => CsValue::I32(cursor.read_compressed_i32::<Endian>()?),
             // TODO: We assume 64 bit
-            Il2CppTypeEnum::I | Il2CppTypeEnum::I8 => {
-                CsValue::I64(cursor.read_i64::<Endian>().unwrap())
-            }
-            Il2CppTypeEnum::U1 => CsValue::U8(cursor.read_u8().unwrap()),
-            Il2CppTypeEnum::U2 => CsValue::U16(cursor.read_u16::<Endian>().unwrap()),
-            Il2CppTypeEnum::U4 => CsValue::U32(cursor.read_compressed_u32::<Endian>().unwrap()),
+            Il2CppTypeEnum::I | Il2CppTypeEnum::I8 => CsValue::I64(cursor.read_i64::<Endian>()?),
+            Il2CppTypeEnum::U1 => CsValue::U8(cursor.read_u8()?),
+            Il2CppTypeEnum::U2 => CsValue::U16(cursor.read_u16::<Endian>()?),
+            Il2CppTypeEnum::U4 => CsValue::U32(cursor.read_compressed_u32::<Endian>()?),
             // TODO: We assume 64 bit
-            Il2CppTypeEnum::U | Il2CppTypeEnum::U8 => {
-                CsValue::U64(cursor.read_u64::<Endian>().unwrap())
-            }
+            Il2CppTypeEnum::U | Il2CppTypeEnum::U8 => CsValue::U64(cursor.read_u64::<Endian>()?),
             // https://learn.microsoft.com/en-us/nimbusml/concepts/types
             // https://en.cppreference.com/w/cpp/types/floating-point
-            Il2CppTypeEnum::R4 => CsValue::F32(cursor.read_f32::<Endian>().unwrap()),
-            Il2CppTypeEnum::R8 => CsValue::F64(cursor.read_f64::<Endian>().unwrap()),
+            Il2CppTypeEnum::R4 => CsValue::F32(cursor.read_f32::<Endian>()?),
+            Il2CppTypeEnum::R8 => CsValue::F64(cursor.read_f64::<Endian>()?),
             Il2CppTypeEnum::Char => {
-                let res = String::from_utf16_lossy(&[cursor.read_u16::<Endian>().unwrap()])
+                let res = String::from_utf16_lossy(&[cursor.read_u16::<Endian>()?])
                     .escape_default()
                     .to_string();
 
                 CsValue::Char(res)
             }
             Il2CppTypeEnum::String => {
-                let stru16_len = cursor.read_compressed_i32::<Endian>().unwrap();
+                let stru16_len = cursor.read_compressed_i32::<Endian>()?;
                 if stru16_len == -1 {
-                    return CsValue::String("".to_string());
+                    return Ok(CsValue::String("".to_string()));
                 }
 
-                let mut buf = vec![0u8; stru16_len as usize];
+                let remaining = data.len() - cursor.position() as usize;
+                let len = usize::try_from(stru16_len)
+                    .ok()
+                    .filter(|len| *len <= remaining)
+                    .ok_or_else(|| {
+                        eyre!("String length {stru16_len} exceeds the {remaining} remaining bytes")
+                    })?;
+
+                let mut buf = vec![0u8; len];
 
-                cursor.read_exact(buf.as_mut_slice()).unwrap();
+                cursor.read_exact(buf.as_mut_slice())?;
 
-                let res = String::from_utf8(buf).unwrap().escape_default().to_string();
+                let res = String::from_utf8(buf)?.escape_default().to_string();
 
                 CsValue::String(res)
             }
@@ -892,13 +1343,24 @@ impl CsType {
                 CsValue::Null
             }
 
-            _ => todo!("Unsupported blob type {:#?}", ty),
-        }
+            _ => bail!("Unsupported blob type {ty:?}"),
+        };
+
+        Ok(value)
+    }
+
+    /// Default values that can't be read are left out like `Null` ones instead of failing the run
+    fn null_on_error(value: color_eyre::Result<CsValue>, owner: &str) -> CsValue {
+        value.unwrap_or_else(|e| {
+            warn!("Failed to read default value of {owner}: {e}");
+            CsValue::Null
+        })
     }
 
     fn unbox_nullable_valuetype<'a>(
         metadata: &'a CordlMetadata,
         ty: &'a Il2CppType,
+        type_name: &str,
     ) -> &'a Il2CppType {
         if let Il2CppTypeEnum::Valuetype = ty.ty {
             match ty.data {
@@ -916,13 +1378,115 @@ impl CsType {
                             .unwrap();
                     }
                 }
-                _ => todo!(),
+                _ => {
+                    metadata.unimplemented_fallback(
+                        type_name,
+                        &format!("value type default with type data {:?}", ty.data),
+                    );
+                }
             }
         }
 
         ty
     }
 
+    /// Whether the enum values look like bit flags, e.g. `None = 0, A = 1, B = 2, C = 4, AB = 3`
+    /// `FlagsAttribute` isn't read, so this holds if every value is made of the single bit values
+    /// and the values don't just count up like `A = 0, B = 1, C = 2, D = 3`
+    pub fn is_flags_like(&self) -> bool {
+        Self::values_look_like_flags(
+            self.fields
+                .iter()
+                .filter(|f| !f.instance)
+                .filter_map(|f| f.value.as_ref()?.as_integer()),
+        )
+    }
+
+    fn values_look_like_flags(values: impl IntoIterator<Item = i128>) -> bool {
+        let values = values
+            .into_iter()
+            .filter(|v| *v != 0)
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        let bits = values
+            .iter()
+            .filter(|v| v.count_ones() == 1)
+            .fold(0, |bits, v| bits | v);
+        let counts_up = values.iter().enumerate().all(|(i, v)| *v == i as i128 + 1);
+
+        values.len() > 1 && !counts_up && values.iter().all(|v| v & !bits == 0)
+    }
+
+    /// Type index of an enum's backing type
+    /// Stripped metadata may lack a valid element type, in which case the width is
+    /// inferred from the range of the enum values, see `infer_enum_backing_type`
+    pub fn enum_backing_type_index(
+        metadata: &CordlMetadata,
+        tdi: TypeDefinitionIndex,
+    ) -> Option<usize> {
+        let t = Self::get_type_definition(metadata, tdi);
+        if !t.is_enum_type() {
+            return None;
+        }
+
+        if t.element_type_index != u32::MAX {
+            return Some(t.element_type_index as usize);
+        }
+
+        let values = (0..t.field_count)
+            .map(|i| FieldIndex::new(t.field_start.index() + i as u32))
+            .filter_map(|field_index| Self::field_default_value(metadata, field_index))
+            .filter_map(|v| v.as_integer())
+            .collect_vec();
+
+        let backing_type =
+            Self::infer_enum_backing_type(&values, metadata.default_enum_backing_type);
+
+        metadata
+            .metadata_registration
+            .types
+            .iter()
+            .position(|ty| ty.ty == backing_type && !ty.byref)
+    }
+
+    /// `default` if all `values` fit, otherwise the narrowest of
+    /// `int32_t`, `uint32_t`, `int64_t` and `uint64_t` they fit in
+    pub fn infer_enum_backing_type(values: &[i128], default: Il2CppTypeEnum) -> Il2CppTypeEnum {
+        let min = values.iter().copied().min().unwrap_or_default();
+        let max = values.iter().copied().max().unwrap_or_default();
+
+        [
+            default,
+            Il2CppTypeEnum::I4,
+            Il2CppTypeEnum::U4,
+            Il2CppTypeEnum::I8,
+        ]
+        .into_iter()
+        .find(|ty| {
+            Self::integer_range(*ty).is_some_and(|(lower, upper)| min >= lower && max <= upper)
+        })
+        .unwrap_or(Il2CppTypeEnum::U8)
+    }
+
+    /// Inclusive range of an integer type, `None` for anything else
+    pub fn integer_range(ty: Il2CppTypeEnum) -> Option<(i128, i128)> {
+        let range = match ty {
+            Il2CppTypeEnum::I1 => (i8::MIN as i128, i8::MAX as i128),
+            Il2CppTypeEnum::U1 => (0, u8::MAX as i128),
+            Il2CppTypeEnum::I2 => (i16::MIN as i128, i16::MAX as i128),
+            Il2CppTypeEnum::U2 => (0, u16::MAX as i128),
+            Il2CppTypeEnum::I4 => (i32::MIN as i128, i32::MAX as i128),
+            Il2CppTypeEnum::U4 => (0, u32::MAX as i128),
+            Il2CppTypeEnum::I8 => (i64::MIN as i128, i64::MAX as i128),
+            Il2CppTypeEnum::U8 => (0, u64::MAX as i128),
+            _ => return None,
+        };
+
+        Some(range)
+    }
+
     pub fn field_default_value(
         metadata: &CordlMetadata,
         field_index: FieldIndex,
@@ -946,12 +1510,20 @@ impl CsType {
                     return CsValue::Null;
                 }
 
-                Self::default_value_blob(metadata, ty, def.data_index.index() as usize, true, true)
+                let value = Self::default_value_blob(
+                    metadata,
+                    ty,
+                    def.data_index.index() as usize,
+                    true,
+                    true,
+                );
+                Self::null_on_error(value, &format!("field {}", field_index.index()))
             })
     }
     fn param_default_value(
         metadata: &CordlMetadata,
         parameter_index: ParameterIndex,
+        type_name: &str,
     ) -> Option<CsValue> {
         metadata
             .metadata
@@ -967,7 +1539,7 @@ impl CsType {
                     .get(def.type_index as usize)
                     .unwrap();
 
-                ty = Self::unbox_nullable_valuetype(metadata, ty);
+                ty = Self::unbox_nullable_valuetype(metadata, ty, type_name);
 
                 // This occurs when the type is `null` or `default(T)` for value types
                 if !def.data_index.is_valid() {
@@ -990,11 +1562,23 @@ impl CsType {
                                     .unwrap();
                             }
                         }
-                        _ => todo!(),
+                        _ => {
+                            metadata.unimplemented_fallback(
+                                type_name,
+                                &format!("value type default with type data {:?}", ty.data),
+                            );
+                        }
                     }
                 }
 
-                Self::default_value_blob(metadata, ty, def.data_index.index() as usize, true, true)
+                let value = Self::default_value_blob(
+                    metadata,
+                    ty,
+                    def.data_index.index() as usize,
+                    true,
+                    true,
+                );
+                Self::null_on_error(value, &format!("parameter {}", parameter_index.index()))
             })
     }
 
@@ -1005,3 +1589,151 @@ impl CsType {
         &metadata.metadata.global_metadata.type_definitions[tdi]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use brocolib::{
+        global_metadata::TypeDefinitionIndex,
+        runtime_metadata::{Il2CppType, Il2CppTypeEnum, TypeData},
+    };
+
+    use crate::generate::cs_members::{CsParamFlags, CsValue};
+
+    use super::CsType;
+
+    /// `int` parameter type with `num_mods` custom modifiers
+    fn int_param(byref: bool, attrs: u16, num_mods: u8) -> Il2CppType {
+        Il2CppType {
+            data: TypeData::TypeDefinitionIndex(TypeDefinitionIndex::new(0)),
+            attrs,
+            ty: Il2CppTypeEnum::I4,
+            num_mods,
+            byref,
+            pinned: false,
+            valuetype: true,
+        }
+    }
+
+    #[test]
+    fn volatile_int_parameter() {
+        let modifiers = CsType::param_modifiers(&int_param(false, 0, 1), "value");
+        assert_eq!(modifiers, CsParamFlags::VOLATILE);
+
+        let modifiers = CsType::param_modifiers(&int_param(false, 0, 0), "value");
+        assert_eq!(modifiers, CsParamFlags::empty());
+    }
+
+    #[test]
+    fn in_parameter_keeps_its_modreq() {
+        // `in int`, whose modreq is `InAttribute`
+        let modifiers = CsType::param_modifiers(&int_param(true, 0x1, 1), "value");
+        assert_eq!(modifiers, CsParamFlags::IN);
+    }
+
+    #[test]
+    fn unknown_byref_modifier_is_ignored() {
+        let modifiers = CsType::param_modifiers(&int_param(true, 0, 1), "value");
+        assert_eq!(modifiers, CsParamFlags::REF);
+    }
+
+    #[test]
+    fn enum_backing_type_defaults_when_values_fit() {
+        let backing_type = CsType::infer_enum_backing_type(&[0, 1, -5], Il2CppTypeEnum::I4);
+        assert_eq!(backing_type, Il2CppTypeEnum::I4);
+    }
+
+    #[test]
+    fn enum_backing_type_widens_past_int32() {
+        let values = [0, i32::MIN as i128 - 1];
+        let backing_type = CsType::infer_enum_backing_type(&values, Il2CppTypeEnum::I4);
+        assert_eq!(backing_type, Il2CppTypeEnum::I8);
+    }
+
+    #[test]
+    fn missing_enum_backing_type_falls_back_to_int32() {
+        let backing_type = CsType::enum_backing_type_or_int32(None, "Foo.Bar");
+        assert_eq!(backing_type, Il2CppTypeEnum::I4);
+
+        let backing_type = CsType::enum_backing_type_or_int32(Some(Il2CppTypeEnum::U1), "Foo.Bar");
+        assert_eq!(backing_type, Il2CppTypeEnum::U1);
+    }
+
+    #[test]
+    fn enum_backing_type_uses_configured_default() {
+        let backing_type = CsType::infer_enum_backing_type(&[1, 2], Il2CppTypeEnum::U1);
+        assert_eq!(backing_type, Il2CppTypeEnum::U1);
+
+        let backing_type = CsType::infer_enum_backing_type(&[1, 300], Il2CppTypeEnum::U1);
+        assert_eq!(backing_type, Il2CppTypeEnum::I4);
+    }
+
+    #[test]
+    fn shadowing_field_gets_type_suffix() {
+        assert_eq!(
+            CsType::unshadowed_field_name("_value", "Derived"),
+            "_value_Derived"
+        );
+    }
+
+    #[test]
+    fn shadowing_field_suffix_strips_generic_arity() {
+        assert_eq!(
+            CsType::unshadowed_field_name("_items", "List`1"),
+            "_items_List"
+        );
+    }
+
+    #[test]
+    fn empty_parameter_names_fall_back_to_position() {
+        let mut names = vec!["value".to_string(), String::new(), " ".to_string()];
+        CsType::fix_parameter_names(&mut names);
+        assert_eq!(names, ["value", "arg1", "arg2"]);
+    }
+
+    #[test]
+    fn repeated_parameter_names_stay_unique() {
+        let mut names = vec!["arg1".to_string(), "arg1".to_string(), "a".to_string()];
+        CsType::fix_parameter_names(&mut names);
+        assert_eq!(names, ["arg1", "arg1_", "a"]);
+    }
+
+    #[test]
+    fn flags_values_with_combinations() {
+        assert!(CsType::values_look_like_flags([0, 1, 2, 4, 3, 7]));
+    }
+
+    #[test]
+    fn counting_values_are_not_flags() {
+        assert!(!CsType::values_look_like_flags([0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn values_outside_single_bits_are_not_flags() {
+        assert!(!CsType::values_look_like_flags([1, 2, 4, 9]));
+    }
+
+    #[test]
+    fn reads_string_default_value() {
+        // zigzag compressed length 5, then utf8
+        let data = [10, b'h', b'e', b'l', b'l', b'o'];
+
+        let value = CsType::read_default_value(&data, Il2CppTypeEnum::String).unwrap();
+        assert_eq!(value, CsValue::String("hello".to_string()));
+    }
+
+    #[test]
+    fn truncated_string_default_value_falls_back_to_null() {
+        // claims 5 bytes, only has 2
+        let data = [10, b'h', b'e'];
+
+        let value = CsType::read_default_value(&data, Il2CppTypeEnum::String);
+        assert!(value.is_err());
+        assert_eq!(CsType::null_on_error(value, "field 0"), CsValue::Null);
+    }
+
+    #[test]
+    fn truncated_primitive_default_value_is_an_error() {
+        assert!(CsType::read_default_value(&[], Il2CppTypeEnum::Boolean).is_err());
+        assert!(CsType::read_default_value(&[0, 0, 0], Il2CppTypeEnum::R8).is_err());
+    }
+}
@@ -3,12 +3,14 @@ use brocolib;
 use brocolib::runtime_metadata::TypeData;
 
 use brocolib::global_metadata::TypeDefinitionIndex;
+use color_eyre::eyre::bail;
 
 // TODO:
 /// Indices into the [`Il2CppMetadataRegistration::generic_insts`] field
 pub type GenericInstIndex = usize;
 
 // TDI -> Generic inst
+/// Identity includes `inst`, so e.g. `List<int>` and `List<string>` are distinct tags
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GenericInstantiation {
     pub tdi: TypeDefinitionIndex,
@@ -38,12 +40,16 @@ impl From<TypeData> for CsTypeTag {
     }
 }
 
-impl From<CsTypeTag> for TypeData {
-    fn from(value: CsTypeTag) -> Self {
+impl TryFrom<CsTypeTag> for TypeData {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: CsTypeTag) -> Result<Self, Self::Error> {
         match value {
-            CsTypeTag::TypeDefinitionIndex(i) => TypeData::TypeDefinitionIndex(i),
-            CsTypeTag::GenericInstantiation(gen) => TypeData::GenericClassIndex(gen.inst), // TODO:?
-            _ => panic!("Can't go from {value:?} to TypeData"),
+            CsTypeTag::TypeDefinitionIndex(i) => Ok(TypeData::TypeDefinitionIndex(i)),
+            // `inst` indexes generic_insts, not generic_classes, so it can't become a GenericClassIndex
+            CsTypeTag::GenericInstantiation(_) => {
+                bail!("Can't go from {value:?} to TypeData without the generic class")
+            }
         }
     }
 }
@@ -101,3 +107,43 @@ impl CsTypeTag {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    /// `List<T>` instantiated with generic inst `inst`
+    fn list_of(inst: GenericInstIndex) -> CsTypeTag {
+        CsTypeTag::GenericInstantiation(GenericInstantiation {
+            tdi: TypeDefinitionIndex::new(10),
+            inst,
+        })
+    }
+
+    #[test]
+    fn closed_generics_are_distinct() {
+        let list_int = list_of(1);
+        let list_string = list_of(2);
+
+        assert_ne!(list_int, list_string);
+        assert_eq!(list_int.get_tdi(), list_string.get_tdi());
+        assert_eq!(HashSet::from([list_int, list_string]).len(), 2);
+
+        let contexts = HashMap::from([(list_int, "List<int>"), (list_string, "List<string>")]);
+        assert_eq!(contexts[&list_int], "List<int>");
+        assert_eq!(contexts[&list_string], "List<string>");
+    }
+
+    #[test]
+    fn closed_generic_has_no_type_data() {
+        let tdi = TypeDefinitionIndex::new(10);
+
+        assert!(matches!(
+            TypeData::try_from(CsTypeTag::TypeDefinitionIndex(tdi)),
+            Ok(TypeData::TypeDefinitionIndex(i)) if i == tdi
+        ));
+        assert!(TypeData::try_from(list_of(1)).is_err());
+    }
+}
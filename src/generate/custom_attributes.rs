@@ -0,0 +1,431 @@
+use std::io::Cursor;
+
+use brocolib::{
+    global_metadata::{Token, TypeDefinitionIndex},
+    runtime_metadata::{Il2CppTypeEnum, TypeData},
+};
+use byteorder::ReadBytesExt;
+use color_eyre::eyre::{bail, eyre};
+use log::warn;
+
+use crate::{helpers::cursor::ReadBytesExtensions, Endian};
+
+use super::{
+    cs_type::CsType,
+    metadata::CordlMetadata,
+    type_extensions::{TypeDefinitionExtensions, TypeDefinitionIndexExtensions},
+};
+
+// il2cpp's encoding of attribute argument types, see `CustomAttributeDataReader.cpp`
+const TYPE_BOOLEAN: u8 = 0x02;
+const TYPE_CHAR: u8 = 0x03;
+const TYPE_I1: u8 = 0x04;
+const TYPE_U1: u8 = 0x05;
+const TYPE_I2: u8 = 0x06;
+const TYPE_U2: u8 = 0x07;
+const TYPE_I4: u8 = 0x08;
+const TYPE_U4: u8 = 0x09;
+const TYPE_I8: u8 = 0x0a;
+const TYPE_U8: u8 = 0x0b;
+const TYPE_R4: u8 = 0x0c;
+const TYPE_R8: u8 = 0x0d;
+const TYPE_STRING: u8 = 0x0e;
+const TYPE_OBJECT: u8 = 0x1c;
+const TYPE_SZARRAY: u8 = 0x1d;
+const TYPE_ENUM: u8 = 0x55;
+const TYPE_TYPE_INDEX: u8 = 0xff;
+
+/// A custom attribute applied to a type, method or parameter
+#[derive(Debug, Clone)]
+pub struct CustomAttribute {
+    /// The attribute type, i.e. declaring type of its constructor
+    pub ty: TypeDefinitionIndex,
+    /// Constructor arguments, only strings are decoded, `None` for anything else or null
+    pub args: Vec<Option<String>>,
+}
+
+/// Encoded type of an attribute argument
+enum ArgumentType {
+    Primitive(u8),
+    String,
+    /// Boxed value, the actual type precedes the value
+    Object,
+    Array(Box<ArgumentType>),
+    /// Enum value, encoded as its backing type
+    Enum(Box<ArgumentType>),
+    /// `System.Type` argument
+    Type,
+}
+
+/// Minimal reader for il2cpp's custom attribute data.
+/// Only attribute types and string arguments are decoded, which is all cordl needs
+/// for attributes like `[Obsolete("message")]`, `[ParamArray]` and `[IsByRefLike]`
+pub struct CustomAttributeReader<'a, 'b> {
+    metadata: &'a CordlMetadata<'b>,
+}
+
+impl<'a, 'b> CustomAttributeReader<'a, 'b> {
+    pub fn new(metadata: &'a CordlMetadata<'b>) -> Self {
+        Self { metadata }
+    }
+
+    /// `ref struct` types, marked with `IsByRefLikeAttribute` since il2cpp has no flag for them
+    pub fn is_byref_like(&self, tdi: TypeDefinitionIndex) -> bool {
+        let td = tdi.get_type_definition(self.metadata.metadata);
+
+        td.is_value_type()
+            && self
+                .find(
+                    tdi,
+                    td.token,
+                    "System.Runtime.CompilerServices",
+                    "IsByRefLikeAttribute",
+                )
+                .is_some()
+    }
+
+    /// `[Obsolete]` message of the item with `token` in the image of `tdi`, empty if it has none
+    pub fn obsolete(&self, tdi: TypeDefinitionIndex, token: Token) -> Option<String> {
+        let attribute = self.find(tdi, token, "System", "ObsoleteAttribute")?;
+
+        let message = attribute.args.into_iter().next().flatten();
+        Some(message.unwrap_or_default())
+    }
+
+    /// Finds the attribute with the given full name on the item with `token` in the image of `tdi`,
+    /// which is the declaring type for methods and parameters.
+    /// Malformed data is logged and treated as no attribute
+    pub fn find(
+        &self,
+        tdi: TypeDefinitionIndex,
+        token: Token,
+        namespace: &str,
+        name: &str,
+    ) -> Option<CustomAttribute> {
+        let metadata = self.metadata.metadata;
+
+        let attributes = self.read(tdi, token).unwrap_or_else(|e| {
+            warn!(
+                "Failed to read custom attributes of {}: {e}",
+                tdi.get_type_definition(metadata).full_name(metadata, true)
+            );
+            vec![]
+        });
+
+        attributes.into_iter().find(|attribute| {
+            let td = attribute.ty.get_type_definition(metadata);
+            td.namespace(metadata) == namespace && td.name(metadata) == name
+        })
+    }
+
+    /// All attributes on the item with `token` in the image of `tdi`
+    pub fn read(
+        &self,
+        tdi: TypeDefinitionIndex,
+        token: Token,
+    ) -> color_eyre::Result<Vec<CustomAttribute>> {
+        let gm = &self.metadata.metadata.global_metadata;
+
+        let Some(image) = self.metadata.type_images.get(&tdi) else {
+            bail!("No image for type {}", tdi.index());
+        };
+
+        // sorted by token within the image
+        let start = image.custom_attribute_start as usize;
+        let ranges = gm
+            .attribute_data_range
+            .as_vec()
+            .get(start..start + image.custom_attribute_count as usize)
+            .ok_or_else(|| eyre!("Custom attribute ranges out of bounds"))?;
+
+        let Ok(range_index) = ranges.binary_search_by_key(&token, |r| r.token) else {
+            return Ok(vec![]);
+        };
+
+        let data = gm.attribute_data.as_vec();
+        let data_start = ranges[range_index].start_offset as usize;
+        // the next range starts where this one ends, even across images
+        let data_end = gm
+            .attribute_data_range
+            .as_vec()
+            .get(start + range_index + 1)
+            .map_or(data.len(), |r| r.start_offset as usize);
+        let data = data
+            .get(data_start..data_end)
+            .ok_or_else(|| eyre!("Custom attribute data {data_start}..{data_end} out of bounds"))?;
+
+        self.read_attributes(data)
+    }
+
+    fn read_attributes(&self, data: &[u8]) -> color_eyre::Result<Vec<CustomAttribute>> {
+        let gm = &self.metadata.metadata.global_metadata;
+        let blob = BlobReader::new(data, |type_index| self.enum_backing_type(type_index));
+
+        blob.read_attributes()?
+            .into_iter()
+            .map(|(ctor, args)| {
+                let method = gm
+                    .methods
+                    .as_vec()
+                    .get(ctor as usize)
+                    .ok_or_else(|| eyre!("Attribute constructor {ctor} out of bounds"))?;
+
+                Ok(CustomAttribute {
+                    ty: method.declaring_type,
+                    args,
+                })
+            })
+            .collect()
+    }
+
+    /// Backing type of the enum with the given type index
+    fn enum_backing_type(&self, type_index: i32) -> color_eyre::Result<ArgumentType> {
+        let metadata = self.metadata;
+
+        let enum_ty = usize::try_from(type_index)
+            .ok()
+            .and_then(|i| metadata.metadata_registration.types.get(i))
+            .ok_or_else(|| eyre!("Enum type index {type_index} out of bounds"))?;
+        let TypeData::TypeDefinitionIndex(tdi) = enum_ty.data else {
+            bail!("Enum type {type_index} is not a type definition");
+        };
+
+        let backing_ty = CsType::enum_backing_type_index(metadata, tdi)
+            .map(|i| &metadata.metadata_registration.types[i])
+            .ok_or_else(|| eyre!("No backing type for enum type {type_index}"))?;
+
+        let primitive = match backing_ty.ty {
+            Il2CppTypeEnum::Boolean => TYPE_BOOLEAN,
+            Il2CppTypeEnum::Char => TYPE_CHAR,
+            Il2CppTypeEnum::I1 => TYPE_I1,
+            Il2CppTypeEnum::U1 => TYPE_U1,
+            Il2CppTypeEnum::I2 => TYPE_I2,
+            Il2CppTypeEnum::U2 => TYPE_U2,
+            Il2CppTypeEnum::I4 => TYPE_I4,
+            Il2CppTypeEnum::U4 => TYPE_U4,
+            Il2CppTypeEnum::I8 => TYPE_I8,
+            Il2CppTypeEnum::U8 => TYPE_U8,
+            ty => bail!("Unsupported enum backing type {ty:?}"),
+        };
+
+        Ok(ArgumentType::Primitive(primitive))
+    }
+}
+
+/// Decodes the attribute data of a single item, independent of the metadata
+/// apart from looking up enum backing types.
+/// Truncated or malformed data is an error, never a panic
+struct BlobReader<'d, F> {
+    cursor: Cursor<&'d [u8]>,
+    enum_backing_type: F,
+}
+
+impl<'d, F> BlobReader<'d, F>
+where
+    F: Fn(i32) -> color_eyre::Result<ArgumentType>,
+{
+    fn new(data: &'d [u8], enum_backing_type: F) -> Self {
+        Self {
+            cursor: Cursor::new(data),
+            enum_backing_type,
+        }
+    }
+
+    /// Constructor method index and decoded arguments of each attribute
+    fn read_attributes(mut self) -> color_eyre::Result<Vec<(u32, Vec<Option<String>>)>> {
+        let count = self.cursor.read_compressed_u32::<Endian>()?;
+        let ctors = (0..count)
+            .map(|_| self.cursor.read_u32::<Endian>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        ctors
+            .into_iter()
+            .map(|ctor| {
+                let arg_count = self.cursor.read_compressed_u32::<Endian>()?;
+                let field_count = self.cursor.read_compressed_u32::<Endian>()?;
+                let property_count = self.cursor.read_compressed_u32::<Endian>()?;
+
+                let args = (0..arg_count)
+                    .map(|_| self.read_argument())
+                    .collect::<color_eyre::Result<Vec<_>>>()?;
+
+                // named arguments, followed by the field or property index
+                for _ in (0..field_count).chain(0..property_count) {
+                    self.read_argument()?;
+                    if self.cursor.read_compressed_i32::<Endian>()? < 0 {
+                        // declared on a base type of the attribute
+                        self.cursor.read_compressed_u32::<Endian>()?;
+                    }
+                }
+
+                Ok((ctor, args))
+            })
+            .collect()
+    }
+
+    /// Reads an argument prefixed by its type, returning it if it's a string
+    fn read_argument(&mut self) -> color_eyre::Result<Option<String>> {
+        let ty = self.read_type()?;
+        self.read_value(&ty)
+    }
+
+    fn read_type(&mut self) -> color_eyre::Result<ArgumentType> {
+        let ty = match self.cursor.read_u8()? {
+            TYPE_STRING => ArgumentType::String,
+            TYPE_OBJECT => ArgumentType::Object,
+            TYPE_SZARRAY => ArgumentType::Array(Box::new(self.read_type()?)),
+            TYPE_TYPE_INDEX => ArgumentType::Type,
+            TYPE_ENUM => {
+                let type_index = self.cursor.read_compressed_i32::<Endian>()?;
+                ArgumentType::Enum(Box::new((self.enum_backing_type)(type_index)?))
+            }
+            primitive => ArgumentType::Primitive(primitive),
+        };
+
+        Ok(ty)
+    }
+
+    fn read_value(&mut self, ty: &ArgumentType) -> color_eyre::Result<Option<String>> {
+        match ty {
+            ArgumentType::String => {
+                let len = self.cursor.read_compressed_i32::<Endian>()?;
+                let Ok(len) = usize::try_from(len) else {
+                    // -1 for null
+                    return Ok(None);
+                };
+
+                let bytes = self.take(len)?;
+                return Ok(Some(String::from_utf8(bytes.to_vec())?));
+            }
+            ArgumentType::Object => {
+                let ty = self.read_type()?;
+                self.read_value(&ty)?;
+            }
+            ArgumentType::Array(element_ty) => {
+                let len = self.cursor.read_compressed_i32::<Endian>()?;
+                if len > 0 {
+                    // elements of `object[]` are each prefixed by their type
+                    let elements_differ = self.cursor.read_u8()? == 1;
+                    for _ in 0..len {
+                        match elements_differ {
+                            true => {
+                                let ty = self.read_type()?;
+                                self.read_value(&ty)?;
+                            }
+                            false => {
+                                self.read_value(element_ty)?;
+                            }
+                        }
+                    }
+                }
+            }
+            ArgumentType::Type => {
+                self.cursor.read_compressed_i32::<Endian>()?;
+            }
+            ArgumentType::Enum(backing_ty) => {
+                self.read_value(backing_ty)?;
+            }
+            ArgumentType::Primitive(TYPE_I4) => {
+                self.cursor.read_compressed_i32::<Endian>()?;
+            }
+            ArgumentType::Primitive(TYPE_U4) => {
+                self.cursor.read_compressed_u32::<Endian>()?;
+            }
+            ArgumentType::Primitive(primitive) => {
+                let size = primitive_size(*primitive)
+                    .ok_or_else(|| eyre!("Unsupported attribute argument type 0x{primitive:x}"))?;
+                self.take(size as usize)?;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The next `len` bytes, checked before allocating anything for them
+    fn take(&mut self, len: usize) -> color_eyre::Result<&'d [u8]> {
+        let data = *self.cursor.get_ref();
+        let start = self.cursor.position() as usize;
+        let bytes = start
+            .checked_add(len)
+            .and_then(|end| data.get(start..end))
+            .ok_or_else(|| eyre!("Attribute value of {len} bytes at {start} out of bounds"))?;
+
+        self.cursor.set_position((start + len) as u64);
+        Ok(bytes)
+    }
+}
+
+fn primitive_size(primitive: u8) -> Option<u8> {
+    let size = match primitive {
+        TYPE_BOOLEAN | TYPE_I1 | TYPE_U1 => 1,
+        TYPE_CHAR | TYPE_I2 | TYPE_U2 => 2,
+        TYPE_R4 => 4,
+        TYPE_I8 | TYPE_U8 | TYPE_R8 => 8,
+        _ => return None,
+    };
+
+    Some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(data: &[u8]) -> color_eyre::Result<Vec<(u32, Vec<Option<String>>)>> {
+        BlobReader::new(data, |_| Ok(ArgumentType::Primitive(TYPE_I4))).read_attributes()
+    }
+
+    /// `[Obsolete("old")]` with constructor method 7: attribute count, constructor,
+    /// argument, field and property count, then the string with its zigzag encoded length
+    const OBSOLETE: &[u8] = &[1, 7, 0, 0, 0, 1, 0, 0, TYPE_STRING, 6, b'o', b'l', b'd'];
+
+    #[test]
+    fn reads_string_argument() {
+        let attributes = read(OBSOLETE).unwrap();
+        assert_eq!(attributes, vec![(7, vec![Some("old".to_string())])]);
+    }
+
+    #[test]
+    fn truncated_data_is_an_error() {
+        for len in 0..OBSOLETE.len() {
+            assert!(read(&OBSOLETE[..len]).is_err(), "truncated to {len} bytes");
+        }
+    }
+
+    #[test]
+    fn oversized_string_is_an_error() {
+        // string length of `i32::MAX - 1`, which must not be allocated
+        let data = [1, 7, 0, 0, 0, 1, 0, 0, TYPE_STRING, 0xfe];
+        assert!(read(&data).is_err());
+    }
+
+    #[test]
+    fn oversized_array_is_an_error() {
+        // `byte[]` claiming far more elements than there is data
+        let data = [1, 7, 0, 0, 0, 1, 0, 0, TYPE_SZARRAY, TYPE_U1, 0xfe, 0, 1];
+        assert!(read(&data).is_err());
+    }
+
+    #[test]
+    fn unknown_argument_type_is_an_error() {
+        let data = [1, 7, 0, 0, 0, 1, 0, 0, 0x42];
+        assert!(read(&data).is_err());
+    }
+
+    #[test]
+    fn invalid_utf8_is_an_error() {
+        let data = [1, 7, 0, 0, 0, 1, 0, 0, TYPE_STRING, 4, 0xff, 0xfe];
+        assert!(read(&data).is_err());
+    }
+
+    #[test]
+    fn enum_argument_uses_backing_type() {
+        // enum argument of type index 3, encoded as its compressed `int` backing value
+        let data = [1, 7, 0, 0, 0, 1, 0, 0, TYPE_ENUM, 6, 4];
+        let attributes = read(&data).unwrap();
+        assert_eq!(attributes, vec![(7, vec![None])]);
+
+        let failing = BlobReader::new(&data[..], |i| Err(eyre!("no enum {i}"))).read_attributes();
+        assert!(failing.is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::{collections::BTreeMap, fmt, sync::Mutex, time::Duration};
+
+use itertools::Itertools;
+
+/// Number of types listed per kind in the summary
+const TOP_OFFENDERS: usize = 5;
+
+/// Fallbacks taken instead of failing the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticKind {
+    /// Type data that can't be resolved, emitted as `System.Object`
+    UnresolvedType,
+    /// Multidimensional arrays, emitted as `System.Object`
+    MultidimArray,
+    /// Other unsupported metadata, see `CordlMetadata::unimplemented_fallback`
+    Unimplemented,
+    /// References to blacklisted types, emitted as their wrapper type
+    BlacklistedReference,
+    /// Types whose computed layout doesn't match the metadata size, so they are padded
+    SizeMismatch,
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiagnosticKind::UnresolvedType => "unresolved types",
+            DiagnosticKind::MultidimArray => "multidimensional arrays downgraded",
+            DiagnosticKind::Unimplemented => "unimplemented fallbacks",
+            DiagnosticKind::BlacklistedReference => "blacklisted references",
+            DiagnosticKind::SizeMismatch => "size mismatches",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A fallback taken while generating `type_name`
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Full C# name of the type being generated
+    pub type_name: String,
+    pub message: String,
+}
+
+/// Collects diagnostics while the metadata is only borrowed immutably
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    entries: Mutex<Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    pub fn record(&self, kind: DiagnosticKind, type_name: &str, message: impl Into<String>) {
+        self.entries.lock().unwrap().push(Diagnostic {
+            kind,
+            type_name: type_name.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.entries.lock().unwrap())
+    }
+}
+
+/// Diagnostics of a run grouped by kind
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticsSummary {
+    pub by_kind: BTreeMap<DiagnosticKind, Vec<Diagnostic>>,
+}
+
+impl DiagnosticsSummary {
+    pub fn new(diagnostics: impl IntoIterator<Item = Diagnostic>) -> Self {
+        let mut by_kind: BTreeMap<DiagnosticKind, Vec<Diagnostic>> = BTreeMap::new();
+        for diagnostic in diagnostics {
+            by_kind.entry(diagnostic.kind).or_default().push(diagnostic);
+        }
+
+        Self { by_kind }
+    }
+
+    pub fn count(&self, kind: DiagnosticKind) -> usize {
+        self.by_kind.get(&kind).map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_kind.is_empty()
+    }
+
+    /// Types with the most diagnostics of `kind`, most first and by name on ties
+    pub fn top_offenders(&self, kind: DiagnosticKind, limit: usize) -> Vec<(&str, usize)> {
+        self.by_kind
+            .get(&kind)
+            .into_iter()
+            .flatten()
+            .map(|d| d.type_name.as_str())
+            .filter(|type_name| !type_name.is_empty())
+            .counts()
+            .into_iter()
+            .sorted_by(|(a_name, a_count), (b_name, b_count)| {
+                b_count.cmp(a_count).then(a_name.cmp(b_name))
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+impl fmt::Display for DiagnosticsSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (kind, diagnostics) in &self.by_kind {
+            writeln!(f, "{kind}: {}", diagnostics.len())?;
+            for (type_name, count) in self.top_offenders(*kind, TOP_OFFENDERS) {
+                writeln!(f, "  {type_name}: {count}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Per type work timed when profiling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimingStage {
+    /// `CsType::fill_from_il2cpp`
+    Fill,
+    /// Writing the type's context, which includes its nested types
+    Write,
+}
+
+impl fmt::Display for TimingStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TimingStage::Fill => "fill",
+            TimingStage::Write => "write",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeTiming {
+    pub stage: TimingStage,
+    /// Full C# name of the type
+    pub type_name: String,
+    pub duration: Duration,
+}
+
+/// Collects how long each type takes, only if enabled since it names every type
+#[derive(Debug, Default)]
+pub struct TypeTimings {
+    enabled: bool,
+    entries: Mutex<Vec<TypeTiming>>,
+}
+
+impl TypeTimings {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            entries: Default::default(),
+        }
+    }
+
+    /// `type_name` is only called if enabled
+    pub fn record(
+        &self,
+        stage: TimingStage,
+        type_name: impl FnOnce() -> String,
+        duration: Duration,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        self.entries.lock().unwrap().push(TypeTiming {
+            stage,
+            type_name: type_name(),
+            duration,
+        });
+    }
+
+    pub fn take(&self) -> Vec<TypeTiming> {
+        std::mem::take(&mut self.entries.lock().unwrap())
+    }
+}
+
+/// Slowest types of a run by stage
+#[derive(Debug, Clone, Default)]
+pub struct TimingSummary {
+    /// Slowest first
+    pub slowest: BTreeMap<TimingStage, Vec<TypeTiming>>,
+}
+
+impl TimingSummary {
+    pub fn new(timings: impl IntoIterator<Item = TypeTiming>, limit: usize) -> Self {
+        let slowest = timings
+            .into_iter()
+            .into_group_map_by(|t| t.stage)
+            .into_iter()
+            .map(|(stage, timings)| {
+                let timings = timings
+                    .into_iter()
+                    .sorted_by(|a, b| b.duration.cmp(&a.duration))
+                    .take(limit)
+                    .collect_vec();
+                (stage, timings)
+            })
+            .collect();
+
+        Self { slowest }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slowest.is_empty()
+    }
+}
+
+impl fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stage, timings) in &self.slowest {
+            writeln!(f, "slowest {stage}:")?;
+            for timing in timings {
+                writeln!(f, "  {}: {:?}", timing.type_name, timing.duration)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(entries: &[(DiagnosticKind, &str)]) -> DiagnosticsSummary {
+        let diagnostics = Diagnostics::default();
+        for (kind, type_name) in entries {
+            diagnostics.record(*kind, type_name, "message");
+        }
+
+        DiagnosticsSummary::new(diagnostics.take())
+    }
+
+    #[test]
+    fn diagnostics_are_grouped_by_kind() {
+        let summary = summary(&[
+            (DiagnosticKind::UnresolvedType, "A"),
+            (DiagnosticKind::SizeMismatch, "B"),
+            (DiagnosticKind::UnresolvedType, "B"),
+        ]);
+
+        assert_eq!(summary.count(DiagnosticKind::UnresolvedType), 2);
+        assert_eq!(summary.count(DiagnosticKind::SizeMismatch), 1);
+        assert_eq!(summary.count(DiagnosticKind::MultidimArray), 0);
+        assert!(!summary.is_empty());
+        assert!(DiagnosticsSummary::new(vec![]).is_empty());
+    }
+
+    #[test]
+    fn take_empties_the_collector() {
+        let diagnostics = Diagnostics::default();
+        diagnostics.record(DiagnosticKind::Unimplemented, "A", "message");
+
+        assert_eq!(diagnostics.take().len(), 1);
+        assert!(diagnostics.take().is_empty());
+    }
+
+    #[test]
+    fn top_offenders_are_sorted_by_count_then_name() {
+        let summary = summary(&[
+            (DiagnosticKind::UnresolvedType, "B"),
+            (DiagnosticKind::UnresolvedType, "C"),
+            (DiagnosticKind::UnresolvedType, "C"),
+            (DiagnosticKind::UnresolvedType, "A"),
+            // not attributed to a type
+            (DiagnosticKind::UnresolvedType, ""),
+        ]);
+
+        assert_eq!(
+            summary.top_offenders(DiagnosticKind::UnresolvedType, 5),
+            [("C", 2), ("A", 1), ("B", 1)]
+        );
+        assert_eq!(
+            summary.top_offenders(DiagnosticKind::UnresolvedType, 1),
+            [("C", 2)]
+        );
+    }
+
+    #[test]
+    fn summary_lists_counts_and_offenders() {
+        let summary = summary(&[
+            (DiagnosticKind::BlacklistedReference, "A"),
+            (DiagnosticKind::UnresolvedType, "B"),
+            (DiagnosticKind::UnresolvedType, "B"),
+        ]);
+
+        assert_eq!(
+            summary.to_string(),
+            "unresolved types: 2\n  B: 2\nblacklisted references: 1\n  A: 1\n"
+        );
+    }
+
+    #[test]
+    fn disabled_timings_are_not_recorded() {
+        let timings = TypeTimings::new(false);
+        timings.record(
+            TimingStage::Fill,
+            || panic!("type names are only built when enabled"),
+            Duration::from_millis(1),
+        );
+
+        assert!(timings.take().is_empty());
+    }
+
+    #[test]
+    fn slowest_types_per_stage() {
+        let timings = TypeTimings::new(true);
+        for (stage, type_name, millis) in [
+            (TimingStage::Fill, "A", 1),
+            (TimingStage::Fill, "B", 3),
+            (TimingStage::Fill, "C", 2),
+            (TimingStage::Write, "A", 5),
+        ] {
+            timings.record(
+                stage,
+                || type_name.to_string(),
+                Duration::from_millis(millis),
+            );
+        }
+
+        let summary = TimingSummary::new(timings.take(), 2);
+        let slowest = |stage| {
+            summary.slowest[&stage]
+                .iter()
+                .map(|t| t.type_name.as_str())
+                .collect_vec()
+        };
+
+        assert_eq!(slowest(TimingStage::Fill), ["B", "C"]);
+        assert_eq!(slowest(TimingStage::Write), ["A"]);
+        assert_eq!(
+            summary.to_string(),
+            "slowest fill:\n  B: 3ms\n  C: 2ms\nslowest write:\n  A: 5ms\n"
+        );
+    }
+}
@@ -0,0 +1,897 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{self, Duration},
+};
+
+use brocolib::{
+    global_metadata::TypeDefinitionIndex,
+    runtime_metadata::{Il2CppTypeEnum, TypeData},
+};
+use color_eyre::eyre::{Context, ContextCompat};
+use itertools::Itertools;
+use log::{info, trace, warn};
+
+use crate::{
+    data::{offset_overrides::OffsetOverrides, xml_docs::XmlDocs},
+    generate::{
+        cs_context_collection::TypeContextCollection,
+        cs_type_tag::CsTypeTag,
+        diagnostics::{DiagnosticKind, DiagnosticsSummary, TimingSummary, TypeTimings},
+        metadata::{self, CordlMetadata},
+        type_extensions::TypeDefinitionExtensions,
+    },
+    helpers::{
+        memory::PeakMemory,
+        progress::{Progress, ProgressCallback, ProgressReporter},
+    },
+};
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum TargetLang {
+    #[cfg(feature = "cpp")]
+    Cpp,
+    #[cfg(feature = "json")]
+    SingleJSON,
+    #[cfg(feature = "json")]
+    MultiJSON,
+    #[cfg(feature = "rust")]
+    Rust,
+}
+
+/// Everything controlling a generation run besides the input and output paths
+#[derive(Debug, Clone)]
+pub struct GenerationConfig {
+    pub target: TargetLang,
+    /// Format the generated C++ with clang-format
+    pub format: bool,
+    pub remove_verbose_comments: bool,
+    /// Resolve compiler generated types like blacklisted types instead of generating them
+    pub skip_compiler_generated: bool,
+    /// Emit placeholders for unsupported metadata instead of aborting
+    pub lenient: bool,
+    /// Record how long each type takes to fill and write, see `GenerationReport::timings`
+    pub type_timings: bool,
+    /// Measure peak resident memory after each stage, see `GenerationReport::peak_memory`
+    pub memory_report: bool,
+    pub gen_generic_methods_specializations: bool,
+    /// C# xml documentation to emit comments from
+    pub xml_docs: Option<PathBuf>,
+    /// Field offsets overriding the ones computed from metadata, see `OffsetOverrides`
+    pub offset_overrides: Option<PathBuf>,
+    /// Minimum time between progress reports
+    pub progress_interval: Duration,
+    /// Receives progress reports, logged with `info!` if unset
+    pub progress: Option<ProgressReporter>,
+    /// Number of threads for parallel generation, uses the global rayon pool if unset
+    pub threads: Option<usize>,
+    /// Only generate these types (full names) and their dependencies, all types if empty
+    pub types: Vec<String>,
+    /// Full names of methods that can return null
+    pub nullable_returns: HashSet<String>,
+    /// Default variant name keyed by full enum name
+    pub enum_defaults: HashMap<String, String>,
+    /// See `CordlMetadata::default_enum_backing_type`
+    pub default_enum_backing_type: Il2CppTypeEnum,
+    /// Prepended to the outermost namespace of every generated type,
+    /// see `CppGenerationConfig::namespace_prefix`
+    pub namespace_prefix: Option<String>,
+
+    /// Root directory generated C++ includes are relative to, defaults to the header directory
+    #[cfg(feature = "cpp")]
+    pub include_root: Option<PathBuf>,
+    /// See `CppGenerationConfig::value_type_constructors`
+    #[cfg(feature = "cpp")]
+    pub value_type_constructors: bool,
+    /// C++ namespace for types without a namespace, defaults to `GlobalNamespace`
+    #[cfg(feature = "cpp")]
+    pub global_namespace: Option<String>,
+    /// See `CppGenerationConfig::type_overrides`
+    #[cfg(feature = "cpp")]
+    pub type_overrides: HashMap<String, super::cpp::config::CppTypeOverride>,
+    /// See `CppGenerationConfig::layout_report`
+    #[cfg(feature = "cpp")]
+    pub layout_report: bool,
+    /// See `CppGenerationConfig::compat_shims`
+    #[cfg(feature = "cpp")]
+    pub compat_shims: Vec<(String, String)>,
+    /// See `CppGenerationConfig::method_pointers`
+    #[cfg(feature = "cpp")]
+    pub method_pointers: bool,
+    /// See `CppGenerationConfig::raw_field_pointers`
+    #[cfg(feature = "cpp")]
+    pub raw_field_pointers: bool,
+    /// See `CppGenerationConfig::trivially_copyable_asserts`
+    #[cfg(feature = "cpp")]
+    pub trivially_copyable_asserts: bool,
+    /// See `CppGenerationConfig::field_count_members`
+    #[cfg(feature = "cpp")]
+    pub field_count_members: bool,
+    /// See `CppGenerationConfig::writer_style`
+    #[cfg(feature = "cpp")]
+    pub writer_style: super::writer::WriterStyle,
+    /// See `CppGenerationConfig::packing_style`
+    #[cfg(feature = "cpp")]
+    pub packing_style: super::cpp::config::PackingStyle,
+    /// Manifest of prebuilt headers whose types are included instead of generated,
+    /// see `PrebuiltManifest`. Explicit `type_overrides` take precedence
+    #[cfg(feature = "cpp")]
+    pub prebuilt_manifest: Option<PathBuf>,
+    /// See `CppGenerationConfig::write_manifest`
+    #[cfg(feature = "cpp")]
+    pub write_manifest: bool,
+    /// See `CppGenerationConfig::type_kinds`
+    #[cfg(feature = "cpp")]
+    pub type_kinds: super::cpp::config::TypeKindFilter,
+    /// See `CppGenerationConfig::method_filter`
+    #[cfg(feature = "cpp")]
+    pub method_filter: super::cpp::config::MethodFilter,
+    /// See `CppGenerationConfig::split_assemblies`
+    #[cfg(feature = "cpp")]
+    pub split_assemblies: bool,
+    /// See `CppGenerationConfig::cached_class`
+    #[cfg(feature = "cpp")]
+    pub cached_class: bool,
+    /// See `CppGenerationConfig::amalgamate`
+    #[cfg(feature = "cpp")]
+    pub amalgamate: bool,
+    /// See `CppGenerationConfig::split_generic_instantiations`
+    #[cfg(feature = "cpp")]
+    pub split_generic_instantiations: bool,
+    /// See `CppGenerationConfig::clang_format`, setting it enables `format`
+    #[cfg(feature = "cpp")]
+    pub clang_format: Option<PathBuf>,
+    /// See `CppGenerationConfig::layout_only`
+    #[cfg(feature = "cpp")]
+    pub layout_only: bool,
+    /// See `CppGenerationConfig::primitive_overrides`
+    #[cfg(feature = "cpp")]
+    pub primitive_overrides: HashMap<String, String>,
+    /// See `CppGenerationConfig::modules`
+    #[cfg(feature = "cpp")]
+    pub modules: bool,
+}
+
+impl GenerationConfig {
+    pub fn new(target: TargetLang) -> Self {
+        GenerationConfig {
+            target,
+            format: false,
+            remove_verbose_comments: false,
+            skip_compiler_generated: false,
+            lenient: false,
+            type_timings: false,
+            memory_report: false,
+            gen_generic_methods_specializations: false,
+            xml_docs: None,
+            offset_overrides: None,
+            progress_interval: Duration::from_secs(1),
+            progress: None,
+            threads: None,
+            types: Default::default(),
+            nullable_returns: Default::default(),
+            enum_defaults: Default::default(),
+            default_enum_backing_type: Il2CppTypeEnum::I4,
+            namespace_prefix: None,
+            #[cfg(feature = "cpp")]
+            include_root: None,
+            #[cfg(feature = "cpp")]
+            value_type_constructors: true,
+            #[cfg(feature = "cpp")]
+            global_namespace: None,
+            #[cfg(feature = "cpp")]
+            type_overrides: Default::default(),
+            #[cfg(feature = "cpp")]
+            layout_report: false,
+            #[cfg(feature = "cpp")]
+            compat_shims: Default::default(),
+            #[cfg(feature = "cpp")]
+            method_pointers: false,
+            #[cfg(feature = "cpp")]
+            raw_field_pointers: false,
+            #[cfg(feature = "cpp")]
+            trivially_copyable_asserts: false,
+            #[cfg(feature = "cpp")]
+            field_count_members: false,
+            #[cfg(feature = "cpp")]
+            writer_style: Default::default(),
+            #[cfg(feature = "cpp")]
+            packing_style: Default::default(),
+            #[cfg(feature = "cpp")]
+            prebuilt_manifest: None,
+            #[cfg(feature = "cpp")]
+            write_manifest: false,
+            #[cfg(feature = "cpp")]
+            type_kinds: Default::default(),
+            #[cfg(feature = "cpp")]
+            method_filter: Default::default(),
+            #[cfg(feature = "cpp")]
+            split_assemblies: false,
+            #[cfg(feature = "cpp")]
+            cached_class: false,
+            #[cfg(feature = "cpp")]
+            amalgamate: false,
+            #[cfg(feature = "cpp")]
+            split_generic_instantiations: true,
+            #[cfg(feature = "cpp")]
+            clang_format: None,
+            #[cfg(feature = "cpp")]
+            layout_only: false,
+            #[cfg(feature = "cpp")]
+            primitive_overrides: Default::default(),
+            #[cfg(feature = "cpp")]
+            modules: false,
+        }
+    }
+
+    #[cfg(feature = "cpp")]
+    pub fn cpp_config(
+        &self,
+        output_dir: &Path,
+    ) -> color_eyre::Result<super::cpp::config::CppGenerationConfig> {
+        let mut config = super::cpp::config::CppGenerationConfig::new(output_dir);
+
+        if let Some(include_root) = &self.include_root {
+            config.include_root = include_root.clone();
+        }
+        if let Some(global_namespace) = &self.global_namespace {
+            config.global_namespace = global_namespace.clone();
+        }
+        config.value_type_constructors = self.value_type_constructors;
+        if let Some(manifest) = &self.prebuilt_manifest {
+            config.type_overrides =
+                super::cpp::prebuilt_manifest::PrebuiltManifest::load(manifest)?.types;
+        }
+        config.type_overrides.extend(self.type_overrides.clone());
+        config.layout_report = self.layout_report;
+        config.compat_shims = self.compat_shims.clone();
+        config.method_pointers = self.method_pointers;
+        config.raw_field_pointers = self.raw_field_pointers;
+        config.trivially_copyable_asserts = self.trivially_copyable_asserts;
+        config.field_count_members = self.field_count_members;
+        config.writer_style = self.writer_style;
+        config.packing_style = self.packing_style;
+        config.write_manifest = self.write_manifest;
+        config.type_kinds = self.type_kinds;
+        config.method_filter = self.method_filter;
+        config.split_assemblies = self.split_assemblies;
+        config.cached_class = self.cached_class;
+        if let Some(namespace_prefix) = &self.namespace_prefix {
+            config.namespace_prefix = namespace_prefix.clone();
+        }
+        config.amalgamate = self.amalgamate;
+        config.split_generic_instantiations = self.split_generic_instantiations;
+        config.clang_format = self.clang_format.clone();
+        config.layout_only = self.layout_only;
+        config.primitive_overrides = self.primitive_overrides.clone();
+        config.modules = self.modules;
+
+        Ok(config)
+    }
+}
+
+/// Summary of a generation run
+#[derive(Debug, Clone, Default)]
+pub struct GenerationReport {
+    /// Number of contexts handed to the target, i.e. one per root type
+    pub context_count: usize,
+    /// Number of types handed to the target, including nested types
+    pub type_count: usize,
+    pub blacklisted_count: usize,
+    /// Fallbacks taken during the run by kind, including the placeholders emitted in lenient mode
+    pub summary: DiagnosticsSummary,
+    /// Slowest types if `GenerationConfig::type_timings` is set
+    pub timings: TimingSummary,
+    /// Peak resident memory after each stage if `GenerationConfig::memory_report` is set
+    pub peak_memory: PeakMemory,
+}
+
+/// Number of types listed per stage in `GenerationReport::timings`
+const SLOWEST_TYPES: usize = 10;
+
+/// Generates bindings for `config.target` into `output_dir`,
+/// which for `SingleJSON` is the written file instead.
+///
+/// Does not touch any global state besides logging,
+/// so it can be called from a `build.rs` or another tool
+pub fn generate(
+    config: &GenerationConfig,
+    metadata_path: &Path,
+    libil2cpp_path: &Path,
+    output_dir: &Path,
+) -> color_eyre::Result<GenerationReport> {
+    match config.threads {
+        Some(threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?
+            .install(|| generate_in_pool(config, metadata_path, libil2cpp_path, output_dir)),
+        None => generate_in_pool(config, metadata_path, libil2cpp_path, output_dir),
+    }
+}
+
+fn generate_in_pool(
+    config: &GenerationConfig,
+    metadata_path: &Path,
+    libil2cpp_path: &Path,
+    output_dir: &Path,
+) -> color_eyre::Result<GenerationReport> {
+    let log_progress = |stage: &str, processed: usize, total: usize| {
+        info!(
+            "{stage} {:.2}% ({processed}/{total})",
+            processed as f64 / total as f64 * 100.0
+        )
+    };
+    let report_progress: &ProgressCallback = match &config.progress {
+        Some(reporter) => &*reporter.0,
+        None => &log_progress,
+    };
+
+    let global_metadata_data = fs::read(metadata_path)
+        .with_context(|| format!("il2cpp metadata not found {}", metadata_path.display()))?;
+    let elf_data = fs::read(libil2cpp_path).with_context(|| {
+        format!(
+            "libil2cpp.so shared object not found {}",
+            libil2cpp_path.display()
+        )
+    })?;
+    let il2cpp_metadata = brocolib::Metadata::parse(&global_metadata_data, &elf_data)?;
+    let mut peak_memory = PeakMemory::default();
+
+    let get_tdi = |full_name: &str| -> color_eyre::Result<TypeDefinitionIndex> {
+        let tdi = il2cpp_metadata
+            .global_metadata
+            .type_definitions
+            .as_vec()
+            .iter()
+            .position(|t| t.full_name(&il2cpp_metadata, false) == full_name)
+            .with_context(|| format!("Unable to find TDI for {full_name}"))?;
+
+        Ok(TypeDefinitionIndex::new(tdi as u32))
+    };
+
+    let unity_object_tdi_idx = get_tdi("UnityEngine.Object")?;
+    let object_tdi_idx = get_tdi("System.Object")?;
+    let str_tdi_idx = get_tdi("System.String")?;
+    let (value_type_tdi_idx, enum_tdi_idx) = metadata::base_value_type_tdis(&il2cpp_metadata)
+        .context("Unable to find System.ValueType and System.Enum, metadata has no enums")?;
+
+    let mut metadata = CordlMetadata {
+        metadata: &il2cpp_metadata,
+        code_registration: &il2cpp_metadata.runtime_metadata.code_registration,
+        metadata_registration: &il2cpp_metadata.runtime_metadata.metadata_registration,
+        method_calculations: Default::default(),
+        parent_to_child_map: Default::default(),
+        child_to_parent_map: Default::default(),
+
+        unity_object_tdi: unity_object_tdi_idx,
+        object_tdi: object_tdi_idx,
+        string_tdi: str_tdi_idx,
+        value_type_tdi: value_type_tdi_idx,
+        enum_tdi: enum_tdi_idx,
+
+        name_to_tdi: Default::default(),
+        type_images: Default::default(),
+        ambiguous_types: Default::default(),
+        blacklisted_types: Default::default(),
+        xml_docs: config.xml_docs.as_deref().map(XmlDocs::load).transpose()?,
+        offset_overrides: config
+            .offset_overrides
+            .as_deref()
+            .map(OffsetOverrides::load)
+            .transpose()?,
+        nullable_returns: config.nullable_returns.clone(),
+        enum_defaults: config.enum_defaults.clone(),
+        default_enum_backing_type: config.default_enum_backing_type,
+        lenient: config.lenient,
+        diagnostics: Default::default(),
+        timings: TypeTimings::new(config.type_timings),
+        pointer_size: metadata::PointerSize::Bytes8,
+        metadata_version: metadata::MetadataVersion::current(),
+        metadata_hash: metadata::metadata_hash(&global_metadata_data),
+        // For most il2cpp versions
+        packing_field_offset: 7,
+        size_is_default_offset: 12,
+        specified_packing_field_offset: 13,
+        packing_is_default_offset: 11,
+    };
+    let t = time::Instant::now();
+    info!("Parsing metadata methods");
+    metadata.parse();
+    info!("Finished in {}ms", t.elapsed().as_millis());
+    if config.memory_report {
+        peak_memory.record("parse");
+    }
+
+    let mut cs_context_collection = TypeContextCollection::new();
+
+    // blacklist types
+    {
+        let mut blacklist_type = |full_name: &str| {
+            let tdi = metadata
+                .metadata
+                .global_metadata
+                .type_definitions
+                .as_vec()
+                .iter()
+                .enumerate()
+                .find(|(_, t)| t.full_name(metadata.metadata, false) == full_name);
+
+            if let Some((tdi, _td)) = tdi {
+                info!("Blacklisted {full_name}");
+
+                metadata
+                    .blacklisted_types
+                    .insert(TypeDefinitionIndex::new(tdi as u32));
+            } else {
+                warn!("Unable to blacklist {full_name}")
+            }
+        };
+
+        blacklist_type("UnityEngine.XR.XRInputSubsystemDescriptor");
+        blacklist_type("UnityEngine.XR.XRMeshSubsystemDescriptor");
+        blacklist_type("UnityEngine.XR.XRDisplaySubsystem");
+        blacklist_type("UIToolkitUtilities.Controls.Table"); // TODO: Make System.Enum work properly
+                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass4_0`1");
+                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass8_0`1");
+                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass7_0`1");
+                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass5_0`1");
+                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass10_0");
+                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__6`1");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass14_0`5");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass10_0`1");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass11_0`2");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass12_0`3");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass13_0`4");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass14_0`5");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass15_0`1");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass16_0`2");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass17_0`3");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass18_0`4");
+                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass19_0`5");
+
+        // Incorrect offsets / sizes due to il2cpp bug
+        blacklist_type("UnityEngine.InputSystem.InputInteractionContext");
+        blacklist_type("UnityEngine.InputSystem.IInputInteraction");
+        blacklist_type("UnityEngine.InputSystem.LowLevel.ActionEvent");
+        blacklist_type("UnityEngine.InputSystem.Interactions.HoldInteraction");
+        blacklist_type("UnityEngine.InputSystem.Interactions.MultiTapInteraction");
+        blacklist_type("UnityEngine.InputSystem.Interactions.PressInteraction");
+        blacklist_type("UnityEngine.InputSystem.Interactions.TapInteraction");
+        blacklist_type("UnityEngine.InputSystem.Interactions.SlowTapInteraction");
+        blacklist_type("UnityEngine.InputSystem.LowLevel.UseWindowsGamingInputCommand");
+        blacklist_type("UnityEngine.InputSystem.LowLevel.EnableIMECompositionCommand");
+        blacklist_type("UnityEngine.InputSystem.LowLevel.MouseState");
+        blacklist_type("UnityEngine.InputSystem.LowLevel.QueryCanRunInBackground");
+        blacklist_type("UnityEngine.InputSystem.LowLevel.QueryEnabledStateCommand");
+        blacklist_type("UnityEngine.InputSystem.Utilities.InputActionTrace");
+        blacklist_type("UnityEngine.InputSystem.Utilities.InputActionTrace::ActionEventPtr");
+        blacklist_type("UnityEngine.InputSystem.Utilities.InputActionTrace::Enumerator");
+        blacklist_type("System.MonoLimitationAttribute");
+    }
+    {
+        let _blacklist_types = |full_name: &str| {
+            let tdis = metadata
+                .metadata
+                .global_metadata
+                .type_definitions
+                .as_vec()
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.full_name(metadata.metadata, false).contains(full_name))
+                .collect_vec();
+
+            match tdis.is_empty() {
+                true => warn!("Unable to blacklist {full_name}"),
+                false => {
+                    for (tdi, td) in tdis {
+                        info!("Blacklisted {}", td.full_name(metadata.metadata, true));
+
+                        metadata
+                            .blacklisted_types
+                            .insert(TypeDefinitionIndex::new(tdi as u32));
+                    }
+                }
+            }
+        };
+        // blacklist_types("<>c__DisplayClass");
+    }
+    if config.skip_compiler_generated {
+        let compiler_generated = metadata
+            .metadata
+            .global_metadata
+            .type_definitions
+            .as_vec()
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_compiler_generated(metadata.metadata))
+            .map(|(tdi, _)| TypeDefinitionIndex::new(tdi as u32))
+            .collect_vec();
+
+        info!(
+            "Blacklisted {} compiler generated types",
+            compiler_generated.len()
+        );
+        metadata.blacklisted_types.extend(compiler_generated);
+    }
+    {
+        // First, make all the contexts
+        info!("Making types");
+        let type_defs = metadata.metadata.global_metadata.type_definitions.as_vec();
+        let total = type_defs.len();
+        let mut progress = Progress::new(
+            "Making types",
+            total,
+            config.progress_interval,
+            report_progress,
+        );
+        for tdi_u64 in 0..total {
+            progress.tick();
+            let tdi = TypeDefinitionIndex::new(tdi_u64 as u32);
+
+            let ty_def = &metadata.metadata.global_metadata.type_definitions[tdi];
+            let _ty = &metadata.metadata_registration.types[ty_def.byval_type_index as usize];
+
+            // only make the roots
+            if ty_def.declaring_type_index != u32::MAX {
+                continue;
+            }
+
+            trace!(
+                "Making types {:.4}% ({tdi_u64}/{total})",
+                (tdi_u64 as f64 / total as f64 * 100.0)
+            );
+            cs_context_collection.make_from(&metadata, TypeData::TypeDefinitionIndex(tdi), None);
+            cs_context_collection.alias_nested_types_il2cpp(
+                tdi,
+                CsTypeTag::TypeDefinitionIndex(tdi),
+                &metadata,
+            );
+        }
+    }
+    {
+        // First, make all the contexts
+        info!("Making nested types");
+        let type_defs = metadata.metadata.global_metadata.type_definitions.as_vec();
+        let total = type_defs.len();
+        let mut progress = Progress::new(
+            "Making nested types",
+            total,
+            config.progress_interval,
+            report_progress,
+        );
+        for tdi_u64 in 0..total {
+            progress.tick();
+            let tdi = TypeDefinitionIndex::new(tdi_u64 as u32);
+
+            let ty_def = &metadata.metadata.global_metadata.type_definitions[tdi];
+
+            if ty_def.declaring_type_index == u32::MAX {
+                continue;
+            }
+
+            trace!(
+                "Making nested types {:.4}% ({tdi_u64}/{total})",
+                (tdi_u64 as f64 / total as f64 * 100.0)
+            );
+            cs_context_collection.make_nested_from(&metadata, tdi);
+        }
+    }
+
+    // {
+    //     let total = metadata.metadata_registration.generic_method_table.len() as f64;
+    //     info!("Making generic type instantiations");
+    //     for (i, generic_class) in metadata
+    //         .metadata_registration
+    //         .generic_method_table
+    //         .iter()
+    //         .enumerate()
+    //     {
+    //         trace!(
+    //             "Making generic type instantiations {:.4}% ({i}/{total})",
+    //             (i as f64 / total * 100.0)
+    //         );
+    //         let method_spec = metadata
+    //             .metadata_registration
+    //             .method_specs
+    //             .get(generic_class.generic_method_index as usize)
+    //             .unwrap();
+
+    //         cpp_context_collection.make_generic_from(method_spec, &mut metadata, config);
+    //     }
+    // }
+    // {
+    //     let total = metadata.metadata_registration.generic_method_table.len() as f64;
+    //     info!("Filling generic types!");
+    //     for (i, generic_class) in metadata
+    //         .metadata_registration
+    //         .generic_method_table
+    //         .iter()
+    //         .enumerate()
+    //     {
+    //         trace!(
+    //             "Filling generic type instantiations {:.4}% ({i}/{total})",
+    //             (i as f64 / total * 100.0)
+    //         );
+    //         let method_spec = metadata
+    //             .metadata_registration
+    //             .method_specs
+    //             .get(generic_class.generic_method_index as usize)
+    //             .unwrap();
+
+    //         cpp_context_collection.fill_generic_class_inst(
+    //             method_spec,
+    //             &mut metadata,
+    //
+    //         );
+    //     }
+    // }
+
+    if config.gen_generic_methods_specializations {
+        let total = metadata.metadata_registration.generic_method_table.len() as f64;
+        info!("Filling generic methods!");
+        for (i, generic_class) in metadata
+            .metadata_registration
+            .generic_method_table
+            .iter()
+            .enumerate()
+        {
+            trace!(
+                "Filling generic method instantiations {:.4}% ({i}/{total})",
+                (i as f64 / total * 100.0)
+            );
+            let method_spec = metadata
+                .metadata_registration
+                .method_specs
+                .get(generic_class.generic_method_index as usize)
+                .unwrap();
+
+            cs_context_collection.fill_generic_method_inst(method_spec, &mut metadata);
+        }
+    }
+
+    info!("Registering handlers!");
+    // il2cpp_internals::register_il2cpp_types(&mut metadata)?;
+
+    // TODO: uncomment
+    // unity::register_unity(&mut metadata)?;
+    // object::register_system(&mut metadata)?;
+    // value_type::register_value_type(&mut metadata)?;
+    info!("Handlers registered!");
+
+    {
+        // Fill them now
+        info!("Filling types");
+        let type_defs = metadata.metadata.global_metadata.type_definitions.as_vec();
+        let total = type_defs.len();
+        let mut progress = Progress::new(
+            "Filling types",
+            total,
+            config.progress_interval,
+            report_progress,
+        );
+        for tdi_u64 in 0..total {
+            progress.tick();
+            let tdi = TypeDefinitionIndex::new(tdi_u64 as u32);
+
+            trace!(
+                "Filling type {:.4} ({tdi_u64}/{total})",
+                (tdi_u64 as f64 / total as f64 * 100.0)
+            );
+
+            cs_context_collection.fill(CsTypeTag::TypeDefinitionIndex(tdi), &metadata);
+        }
+    }
+    if config.memory_report {
+        peak_memory.record("fill");
+    }
+
+    if !config.types.is_empty() {
+        info!(
+            "Filtering to {} types and their dependencies",
+            config.types.len()
+        );
+        let roots = config
+            .types
+            .iter()
+            .map(|full_name| get_tdi(full_name).map(CsTypeTag::TypeDefinitionIndex))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+
+        cs_context_collection.retain_dependencies_of(&roots);
+        info!("Kept {} contexts", cs_context_collection.all_contexts.len());
+    }
+
+    if config.remove_verbose_comments {
+        // TODO: uncomment
+        // remove_coments(&mut cpp_context_collection)?;
+    }
+
+    let mut report = make_report(&cs_context_collection, &metadata);
+
+    match config.target {
+        #[cfg(feature = "cpp")]
+        TargetLang::Cpp => {
+            use super::cpp;
+
+            let cpp_config = config.cpp_config(output_dir)?;
+            cpp::cpp_main::run_cpp(
+                cs_context_collection,
+                &metadata,
+                &cpp_config,
+                config.format || cpp_config.clang_format.is_some(),
+            )?;
+        }
+        #[cfg(feature = "json")]
+        TargetLang::SingleJSON => {
+            use super::json;
+
+            info!("Writing json file {output_dir:?}");
+            json::make_json(&metadata, &cs_context_collection, output_dir)?;
+        }
+        #[cfg(feature = "json")]
+        TargetLang::MultiJSON => {
+            use super::json;
+
+            info!("Writing json file {output_dir:?}");
+            json::make_json_folder(&metadata, &cs_context_collection, output_dir)?;
+        }
+
+        #[cfg(feature = "rust")]
+        TargetLang::Rust => {
+            use super::rust;
+
+            let mut rust_config = rust::config::RustGenerationConfig::new(output_dir);
+            if let Some(namespace_prefix) = &config.namespace_prefix {
+                rust_config.namespace_prefix = namespace_prefix.clone();
+            }
+            rust::rust_main::run_rust(cs_context_collection, &metadata, &rust_config)?;
+        }
+    }
+
+    // types are filled for and written by the target after the report is made
+    report.summary = DiagnosticsSummary::new(metadata.diagnostics.take());
+    report.timings = TimingSummary::new(metadata.timings.take(), SLOWEST_TYPES);
+    if config.memory_report {
+        peak_memory.record("write");
+    }
+    report.peak_memory = peak_memory;
+
+    Ok(report)
+}
+
+fn make_report(collection: &TypeContextCollection, metadata: &CordlMetadata) -> GenerationReport {
+    let types = collection
+        .all_contexts
+        .values()
+        .flat_map(|c| c.get_types().values())
+        .collect_vec();
+
+    // the targets pad these to the metadata size
+    for t in &types {
+        let Some(size_info) = &t.size_info else {
+            continue;
+        };
+        if size_info.instance_size == 0 || size_info.instance_size == u32::MAX {
+            continue;
+        }
+
+        let calculated_size = metadata.metadata_version.align_calculated_size(size_info);
+        if calculated_size != size_info.instance_size {
+            metadata.diagnostics.record(
+                DiagnosticKind::SizeMismatch,
+                &t.cs_name_components.combine_all(),
+                format!(
+                    "calculated 0x{calculated_size:x}, metadata 0x{:x}",
+                    size_info.instance_size
+                ),
+            );
+        }
+    }
+
+    GenerationReport {
+        context_count: collection.all_contexts.len(),
+        type_count: types.len(),
+        blacklisted_count: metadata.blacklisted_types.len(),
+        summary: Default::default(),
+        timings: Default::default(),
+        peak_memory: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "cpp")]
+    #[test]
+    fn cpp_config_defaults_to_cpp_generation_config() {
+        let output_dir = Path::new("out");
+        let config = GenerationConfig::new(TargetLang::Cpp)
+            .cpp_config(output_dir)
+            .unwrap();
+        let defaults = super::super::cpp::config::CppGenerationConfig::new(output_dir);
+
+        assert_eq!(config.header_path, defaults.header_path);
+        assert_eq!(config.include_root, defaults.include_root);
+        assert_eq!(config.global_namespace, defaults.global_namespace);
+        assert_eq!(config.namespace_prefix, defaults.namespace_prefix);
+        assert_eq!(
+            config.value_type_constructors,
+            defaults.value_type_constructors
+        );
+        assert!(config.type_overrides.is_empty());
+    }
+
+    #[cfg(feature = "cpp")]
+    #[test]
+    fn cpp_config_applies_options() {
+        let mut config = GenerationConfig::new(TargetLang::Cpp);
+        config.include_root = Some("include_root".into());
+        config.global_namespace = Some("Global".to_string());
+        config.namespace_prefix = Some("Game".to_string());
+        config.value_type_constructors = false;
+        config.layout_only = true;
+        config.modules = true;
+        config.type_overrides.insert(
+            "UnityEngine.Vector3".to_string(),
+            super::super::cpp::config::CppTypeOverride {
+                cpp_name: "::Sombrero::FastVector3".to_string(),
+                include: "sombrero/shared/FastVector3.hpp".into(),
+            },
+        );
+
+        let cpp_config = config.cpp_config(Path::new("out")).unwrap();
+
+        assert_eq!(cpp_config.include_root, Path::new("include_root"));
+        assert_eq!(cpp_config.header_path, Path::new("out").join("include"));
+        assert_eq!(cpp_config.global_namespace, "Global");
+        assert_eq!(cpp_config.namespace_prefix, "Game");
+        assert!(!cpp_config.value_type_constructors);
+        assert!(cpp_config.layout_only);
+        assert!(cpp_config.modules);
+        assert_eq!(
+            cpp_config.type_overrides["UnityEngine.Vector3"].cpp_name,
+            "::Sombrero::FastVector3"
+        );
+    }
+
+    /// Generates C++ for real metadata, run with
+    /// `CORDL_TEST_METADATA=global-metadata.dat CORDL_TEST_LIBIL2CPP=libil2cpp.so cargo test -- --ignored`
+    #[cfg(feature = "cpp")]
+    #[test]
+    #[ignore = "needs CORDL_TEST_METADATA and CORDL_TEST_LIBIL2CPP"]
+    fn generates_cpp_from_metadata() {
+        let metadata_path = std::env::var_os("CORDL_TEST_METADATA")
+            .expect("CORDL_TEST_METADATA should point at a global-metadata.dat");
+        let libil2cpp_path = std::env::var_os("CORDL_TEST_LIBIL2CPP")
+            .expect("CORDL_TEST_LIBIL2CPP should point at a libil2cpp.so");
+        let output_dir = std::env::temp_dir().join("cordl_generate_test");
+        if output_dir.exists() {
+            fs::remove_dir_all(&output_dir).unwrap();
+        }
+
+        let report = generate(
+            &GenerationConfig::new(TargetLang::Cpp),
+            Path::new(&metadata_path),
+            Path::new(&libil2cpp_path),
+            &output_dir,
+        )
+        .unwrap();
+
+        assert!(report.context_count > 0);
+        // every context holds at least its root type
+        assert!(report.type_count >= report.context_count);
+
+        let include_dir = output_dir.join("include");
+        assert!(include_dir
+            .join("cordl_internals")
+            .join("cordl_internals.hpp")
+            .exists());
+        let namespace_dirs = fs::read_dir(&include_dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir() && entry.file_name() != "cordl_internals")
+            .count();
+        assert!(namespace_dirs > 0);
+    }
+}
@@ -5,7 +5,10 @@ use crate::{
         name_components::NameComponents,
         type_resolver::{ResolvedType, ResolvedTypeData},
     },
-    generate::{cs_context_collection::TypeContextCollection, metadata::CordlMetadata},
+    generate::{
+        cs_context_collection::TypeContextCollection, metadata::CordlMetadata,
+        type_extensions::GenericParameterExtensions,
+    },
 };
 
 use super::json_data::JsonTypeEnum;
@@ -59,7 +62,7 @@ impl JsonNameResolver<'_, '_> {
                 //     .get(&method_index)
                 //     .and_then(|v| v.get(method_arg as usize));
 
-                generic_param.name(metadata.metadata).to_string().into()
+                generic_param.unshadowed_name(metadata.metadata).into()
             }
             ResolvedTypeData::Ptr(resolved_type) => {
                 let generic_formatted = self.resolve_name(resolved_type);
@@ -1,9 +1,22 @@
 use std::collections::{HashMap, HashSet};
 
-use brocolib::global_metadata::{Il2CppTypeDefinition, MethodIndex, TypeDefinitionIndex};
+use brocolib::{
+    global_metadata::{
+        Il2CppImageDefinition, Il2CppTypeDefinition, MethodIndex, TypeDefinitionIndex,
+    },
+    runtime_metadata::{Il2CppTypeEnum, TypeData},
+};
 use itertools::Itertools;
+use log::warn;
 
-use super::cs_type::CsType;
+use crate::data::{offset_overrides::OffsetOverrides, xml_docs::XmlDocs};
+
+use super::{
+    cs_type::CsType,
+    diagnostics::{DiagnosticKind, Diagnostics, TypeTimings},
+    offsets::SizeInfo,
+    type_extensions::TypeDefinitionExtensions,
+};
 
 pub struct MethodCalculations {
     pub estimated_size: usize,
@@ -17,6 +30,52 @@ pub enum PointerSize {
     Bytes8 = 8,
 }
 
+/// il2cpp metadata version
+/// Used to branch on layout differences at runtime instead of scattering feature checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataVersion {
+    V29,
+    V31,
+}
+
+impl MetadataVersion {
+    /// The version brocolib was built for
+    pub const fn current() -> Self {
+        if cfg!(feature = "il2cpp_v31") {
+            MetadataVersion::V31
+        } else {
+            MetadataVersion::V29
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            MetadataVersion::V29 => "v29",
+            MetadataVersion::V31 => "v31",
+        }
+    }
+
+    pub const fn number(&self) -> u32 {
+        match self {
+            MetadataVersion::V29 => 29,
+            MetadataVersion::V31 => 31,
+        }
+    }
+
+    /// Align the calculated size to the next multiple of natural_alignment, similiar to what happens when clang compiles our generated code
+    /// this comes down to adding our size, and removing any bits that make it more than the next multiple of alignment
+    pub fn align_calculated_size(&self, size_info: &SizeInfo) -> u32 {
+        match self {
+            MetadataVersion::V29 => match size_info.natural_alignment as u32 {
+                0 => size_info.calculated_instance_size,
+                alignment => (size_info.calculated_instance_size + alignment) & !(alignment - 1),
+            },
+            // v31 layout already accounts for alignment
+            MetadataVersion::V31 => size_info.calculated_instance_size,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TypeDefinitionPair<'a> {
     pub ty: &'a Il2CppTypeDefinition,
@@ -50,11 +109,41 @@ pub struct CordlMetadata<'a> {
     pub unity_object_tdi: TypeDefinitionIndex,
     pub string_tdi: TypeDefinitionIndex,
     pub object_tdi: TypeDefinitionIndex,
+    /// `System.ValueType`, see `base_value_type_tdis`
+    pub value_type_tdi: TypeDefinitionIndex,
+    /// `System.Enum`, see `base_value_type_tdis`
+    pub enum_tdi: TypeDefinitionIndex,
 
     pub name_to_tdi: HashMap<Il2cppFullName<'a>, TypeDefinitionIndex>,
+    /// Image (assembly) defining each type, see `type_image_name`
+    pub type_images: HashMap<TypeDefinitionIndex, &'a Il2CppImageDefinition>,
+    /// Top level types whose full name is also defined in another image
+    pub ambiguous_types: HashSet<TypeDefinitionIndex>,
     pub blacklisted_types: HashSet<TypeDefinitionIndex>,
 
+    /// Documentation to emit instead of the generated briefs, if provided
+    pub xml_docs: Option<XmlDocs>,
+    /// Field offsets to emit instead of the computed ones, if provided
+    pub offset_overrides: Option<OffsetOverrides>,
+    /// Methods that can return null, by full name e.g. `UnityEngine.Component.GetComponent`
+    pub nullable_returns: HashSet<String>,
+    /// Default variants of enums by full name, the first variant is used otherwise
+    pub enum_defaults: HashMap<String, String>,
+    /// Backing type of enums without a valid element type whose values fit it,
+    /// see `CsType::infer_enum_backing_type`
+    pub default_enum_backing_type: Il2CppTypeEnum,
+    /// Continue past unsupported metadata with a placeholder instead of panicking,
+    /// see `unimplemented_fallback`
+    pub lenient: bool,
+    /// Fallbacks taken during the run, summarized at the end
+    pub diagnostics: Diagnostics,
+    /// Time taken per type if profiling, the slowest are listed at the end
+    pub timings: TypeTimings,
+
     pub pointer_size: PointerSize,
+    pub metadata_version: MetadataVersion,
+    /// Hash of the global metadata file, see `metadata_hash`
+    pub metadata_hash: u64,
     pub packing_field_offset: u8,
     pub size_is_default_offset: u8,
     pub specified_packing_field_offset: u8,
@@ -68,9 +157,52 @@ impl<'a> CordlMetadata<'a> {
         (self.pointer_size as u8) * 2
     }
 
+    /// Name of the image (assembly) defining `tdi`, without the `.dll` extension
+    pub fn type_image_name(&self, tdi: TypeDefinitionIndex) -> &'a str {
+        self.type_images
+            .get(&tdi)
+            .map(|image| image.name(self.metadata).trim_end_matches(".dll"))
+            .unwrap_or_default()
+    }
+
+    /// Identifies the cordl build and metadata the bindings were generated from,
+    /// so runtime code can compare it against the loaded game
+    pub fn version_marker(&self) -> String {
+        format!(
+            "cordl {} il2cpp {} metadata {:016x}",
+            env!("CARGO_PKG_VERSION"),
+            self.metadata_version.as_str(),
+            self.metadata_hash
+        )
+    }
+
+    /// Handles metadata cordl doesn't support yet
+    /// Panics unless lenient, otherwise logs and returns a comment to emit in place of the output
+    /// while generating `type_name`
+    pub fn unimplemented_fallback(&self, type_name: &str, context: &str) -> String {
+        self.unimplemented_fallback_as(DiagnosticKind::Unimplemented, type_name, context)
+    }
+
+    /// `unimplemented_fallback` recorded as `kind`
+    pub fn unimplemented_fallback_as(
+        &self,
+        kind: DiagnosticKind,
+        type_name: &str,
+        context: &str,
+    ) -> String {
+        if !self.lenient {
+            panic!("Unimplemented: {context}");
+        }
+
+        warn!("Unimplemented, emitting a placeholder: {context}");
+        self.diagnostics.record(kind, type_name, context);
+        format!("cordl placeholder, unimplemented: {context}")
+    }
+
     pub fn parse(&mut self) {
         let gm = &self.metadata.global_metadata;
         self.parse_name_tdi(gm);
+        self.parse_type_images(gm);
         self.parse_type_hierarchy(gm);
         self.parse_method_size(gm);
     }
@@ -222,4 +354,81 @@ impl<'a> CordlMetadata<'a> {
             })
             .collect();
     }
+
+    fn parse_type_images(&mut self, gm: &'a brocolib::global_metadata::GlobalMetadata) {
+        let metadata = self.metadata;
+
+        self.type_images = gm
+            .images
+            .as_vec()
+            .iter()
+            .flat_map(|image| {
+                (0..image.type_count).map(move |i| {
+                    (
+                        TypeDefinitionIndex::new(image.type_start.index() + i),
+                        image,
+                    )
+                })
+            })
+            .collect();
+
+        self.ambiguous_types = self
+            .type_images
+            .iter()
+            .filter(|(tdi, _)| gm.type_definitions[**tdi].declaring_type_index == u32::MAX)
+            .into_group_map_by(|(tdi, _)| {
+                let td = &gm.type_definitions[**tdi];
+                Il2cppFullName(td.namespace(metadata), td.name(metadata))
+            })
+            .into_values()
+            .filter(|types| {
+                types
+                    .iter()
+                    .map(|(_, image)| image.name(metadata))
+                    .unique()
+                    .count()
+                    > 1
+            })
+            .flatten()
+            .map(|(tdi, _)| *tdi)
+            .collect();
+    }
+}
+
+/// `System.ValueType` and `System.Enum`, found through the type hierarchy instead of by name
+/// so stripped or obfuscated metadata still resolves them.
+/// Every enum derives from `System.Enum`, which derives from `System.ValueType`
+pub fn base_value_type_tdis(
+    metadata: &brocolib::Metadata,
+) -> Option<(TypeDefinitionIndex, TypeDefinitionIndex)> {
+    let parent_tdi = |td: &Il2CppTypeDefinition| {
+        let parent_ty = metadata
+            .runtime_metadata
+            .metadata_registration
+            .types
+            .get(td.parent_index as usize)?;
+
+        match parent_ty.data {
+            TypeData::TypeDefinitionIndex(tdi) => Some(tdi),
+            _ => None,
+        }
+    };
+
+    let enum_tdi = metadata
+        .global_metadata
+        .type_definitions
+        .as_vec()
+        .iter()
+        .filter(|td| td.is_enum_type())
+        .find_map(parent_tdi)?;
+    let value_type_tdi = parent_tdi(&metadata.global_metadata.type_definitions[enum_tdi])?;
+
+    Some((value_type_tdi, enum_tdi))
+}
+
+/// FNV-1a hash of the global metadata file, stable across builds unlike `DefaultHasher`
+pub fn metadata_hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
 }
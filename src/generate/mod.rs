@@ -3,6 +3,9 @@ pub mod cs_context_collection;
 pub mod cs_members;
 pub mod cs_type;
 pub mod cs_type_tag;
+pub mod custom_attributes;
+pub mod diagnostics;
+pub mod generation;
 pub mod metadata;
 pub mod offsets;
 pub mod type_extensions;
@@ -3,9 +3,9 @@ use core::mem;
 use crate::generate::metadata::CordlMetadata;
 use crate::generate::metadata::PointerSize;
 use crate::generate::type_extensions::TypeDefinitionExtensions;
-use crate::TypeDefinitionIndex;
 
 use brocolib::global_metadata::Il2CppTypeDefinition;
+use brocolib::global_metadata::TypeDefinitionIndex;
 use brocolib::runtime_metadata::Il2CppTypeDefinitionSizes;
 use brocolib::runtime_metadata::TypeData;
 use brocolib::runtime_metadata::{Il2CppType, Il2CppTypeEnum};
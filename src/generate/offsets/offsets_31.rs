@@ -3,9 +3,9 @@ use core::mem;
 use crate::generate::metadata::CordlMetadata;
 use crate::generate::metadata::PointerSize;
 use crate::generate::type_extensions::TypeDefinitionExtensions;
-use crate::TypeDefinitionIndex;
 
 use brocolib::global_metadata::Il2CppTypeDefinition;
+use brocolib::global_metadata::TypeDefinitionIndex;
 use brocolib::runtime_metadata::Il2CppTypeDefinitionSizes;
 use brocolib::runtime_metadata::TypeData;
 use brocolib::runtime_metadata::{Il2CppType, Il2CppTypeEnum};
@@ -25,6 +25,8 @@ pub struct SizeInfo {
     pub calculated_instance_size: u32,
     pub calculated_native_size: i32,
     pub minimum_alignment: u8,
+    /// not computed by the v31 layout, always 0
+    pub natural_alignment: u8,
     pub packing: Option<u8>,
     pub specified_packing: Option<u8>,
 }
@@ -67,6 +69,7 @@ pub fn get_size_info<'a>(
 
         native_size,
         minimum_alignment,
+        natural_alignment: 0,
         calculated_native_size: sa.actual_size as i32,
         packing,
         specified_packing,
@@ -1,16 +1,24 @@
-use std::{path::PathBuf, sync::LazyLock};
-
-pub static STATIC_CONFIG: LazyLock<RustGenerationConfig> = LazyLock::new(|| RustGenerationConfig {
-    source_path: PathBuf::from("./codegen-rs/src"),
-    cargo_config: PathBuf::from("./codegen-rs/Cargo.toml"),
-});
+use std::path::{Path, PathBuf};
 
+#[derive(Debug, Clone)]
 pub struct RustGenerationConfig {
     pub source_path: PathBuf,
     pub cargo_config: PathBuf,
+    /// Prepended to the outermost module of every generated type and its directory,
+    /// e.g. `bs_` gives `crate::bs_System::Object` in `src/bs_System`
+    pub namespace_prefix: String,
 }
 
 impl RustGenerationConfig {
+    /// Config writing the crate sources to `{output_dir}/src`
+    pub fn new(output_dir: &Path) -> Self {
+        RustGenerationConfig {
+            source_path: output_dir.join("src"),
+            cargo_config: output_dir.join("Cargo.toml"),
+            namespace_prefix: Default::default(),
+        }
+    }
+
     pub fn namespace_rs(&self, string: &str) -> String {
         let final_ns = if string.is_empty() {
             "GlobalNamespace".to_owned()
@@ -18,7 +26,7 @@ impl RustGenerationConfig {
             string.replace(['<', '>', '`', '/'], "_").replace('.', "::")
         };
 
-        format!("crate::{final_ns}")
+        format!("crate::{}{final_ns}", self.namespace_prefix)
     }
 
     #[inline]
@@ -106,13 +114,17 @@ impl RustGenerationConfig {
             "_",
         );
 
+        if s.is_empty() {
+            s = "_cordl_empty_name".to_string();
+        }
         if s.chars().next().is_some_and(|c| c.is_numeric()) {
             s = format!("_cordl_{s}");
         }
         s
     }
     pub fn namespace_path(&self, string: &str) -> String {
-        string.replace(['<', '>', '`', '/'], "_").replace('.', "/")
+        let path = string.replace(['<', '>', '`', '/'], "_").replace('.', "/");
+        format!("{}{path}", self.namespace_prefix)
     }
 
     pub(crate) fn feature_name(&self, s: &str) -> String {
@@ -1,7 +1,7 @@
 mod rust_members;
 mod rust_type;
 
-mod config;
+pub mod config;
 mod rust_context;
 mod rust_context_collection;
 mod rust_name_components;
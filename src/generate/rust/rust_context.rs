@@ -1,7 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
     fs::{create_dir_all, File},
-    io::BufWriter,
     path::{self, PathBuf},
 };
 
@@ -11,7 +10,9 @@ use log::{trace, warn};
 use std::io::Write;
 
 use crate::generate::{
-    cs_type_tag::CsTypeTag, type_extensions::TypeDefinitionExtensions, writer::Writer,
+    cs_type_tag::CsTypeTag,
+    type_extensions::TypeDefinitionExtensions,
+    writer::{Writer, WriterStyle},
 };
 
 use super::rust_type::RustType;
@@ -130,11 +131,10 @@ impl RustContext {
         }
 
         trace!("Writing {:?}", self.fundamental_path.as_path());
-        let mut typedef_writer = Writer {
-            stream: BufWriter::new(File::create(self.fundamental_path.as_path())?),
-            indent: 0,
-            newline: true,
-        };
+        let mut typedef_writer = Writer::new(
+            File::create(self.fundamental_path.as_path())?,
+            WriterStyle::default(),
+        );
 
         let modules: HashSet<&String> = self
             .typedef_types
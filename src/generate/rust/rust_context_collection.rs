@@ -1,8 +1,9 @@
 use std::{
-    collections::{HashMap, HashSet},
-    ffi::OsStr,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs::File,
     io::{BufWriter, Write},
+    path::PathBuf,
+    time::Instant,
 };
 
 use rayon::prelude::*;
@@ -10,10 +11,12 @@ use rayon::prelude::*;
 use itertools::Itertools;
 use log::{info, trace};
 use rayon::iter::ParallelIterator;
-use walkdir::WalkDir;
 
 use crate::generate::{
-    cs_context_collection::TypeContextCollection, cs_type::CsType, cs_type_tag::CsTypeTag,
+    cs_context_collection::TypeContextCollection,
+    cs_type::CsType,
+    cs_type_tag::CsTypeTag,
+    diagnostics::{TimingStage, TypeTimings},
     metadata::CordlMetadata,
 };
 
@@ -22,6 +25,12 @@ use super::{
     rust_name_resolver::RustNameResolver, rust_type::RustType,
 };
 
+/// `Cargo.toml` of the generated crate, `#cordl_features` is replaced by the feature block
+const CARGO_TEMPLATE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/cordl_internals_rs/Cargo_template.toml"
+));
+
 #[derive(Default)]
 pub struct RustContextCollection {
     // Should always be a TypeDefinitionIndex
@@ -206,12 +215,16 @@ impl RustContextCollection {
         &mut self.all_contexts
     }
 
-    pub fn write_all(&self, config: &RustGenerationConfig) -> color_eyre::Result<()> {
+    pub fn write_all(
+        &self,
+        config: &RustGenerationConfig,
+        timings: &TypeTimings,
+    ) -> color_eyre::Result<()> {
         let amount = self.all_contexts.len() as f64;
         self.all_contexts
             .iter()
             .enumerate()
-            .try_for_each(|(i, (_, c))| {
+            .try_for_each(|(i, (tag, c))| {
                 trace!(
                     "Writing {:.4}% ({}/{}) {}",
                     (i as f64 / amount * 100.0),
@@ -219,7 +232,17 @@ impl RustContextCollection {
                     amount,
                     c.fundamental_path.display(),
                 );
-                c.write(config)
+                let start = Instant::now();
+                c.write(config)?;
+                timings.record(
+                    TimingStage::Write,
+                    || match c.typedef_types.get(tag) {
+                        Some(t) => t.cs_name_components.combine_all(),
+                        None => c.fundamental_path.display().to_string(),
+                    },
+                    start.elapsed(),
+                );
+                Ok(())
             })
     }
 
@@ -274,16 +297,7 @@ impl RustContextCollection {
             .sorted()
             .join("\n");
 
-        let mut cargo_config =
-            match std::fs::read_to_string("./cordl_internals_rs/Cargo_template.toml") {
-                Ok(content) => content,
-                Err(_) => {
-                    eprintln!("Failed to load file `./cordl_internals_rs/Cargo_template.toml`");
-                    return Err(color_eyre::eyre::eyre!("Failed to load Cargo template"));
-                }
-            };
-
-        cargo_config = cargo_config.replace("#cordl_features", &feature_block);
+        let cargo_config = CARGO_TEMPLATE.replace("#cordl_features", &feature_block);
 
         let mut file = File::create(&config.cargo_config)?;
         file.write_all(cargo_config.as_bytes())?;
@@ -291,71 +305,78 @@ impl RustContextCollection {
         Ok(())
     }
 
-    pub fn write_namespace_modules(&self, config: &RustGenerationConfig) -> color_eyre::Result<()> {
-        info!("Writing namespace modules!");
-        fn make_mod_dir(dir: &std::path::Path, name: &str) -> Result<(), color_eyre::eyre::Error> {
-            if !dir.exists() {
-                return Ok(());
-            }
+    /// Appends the version marker to `lib.rs`, so runtime code can check it against the loaded game
+    pub fn write_version_constant(
+        &self,
+        config: &RustGenerationConfig,
+        version_marker: &str,
+    ) -> color_eyre::Result<()> {
+        let lib_file = File::options()
+            .create(true)
+            .append(true)
+            .open(config.source_path.join("lib.rs"))?;
+        let mut buf_writer = BufWriter::new(lib_file);
 
-            let mut modules_paths = WalkDir::new(dir)
-                .max_depth(1)
-                .min_depth(1)
-                .into_iter()
-                .map(|c| c.map(|entry| entry.into_path()))
-                .collect::<walkdir::Result<Vec<_>>>()?;
+        writeln!(
+            buf_writer,
+            "/// cordl version and il2cpp metadata these bindings were generated from"
+        )?;
+        writeln!(
+            buf_writer,
+            "pub const CORDL_VERSION: &str = \"{version_marker}\";"
+        )?;
+        buf_writer.flush()?;
 
-            // Sort so things don't break git diffs
-            modules_paths.sort();
+        Ok(())
+    }
 
-            if modules_paths.is_empty() {
-                return Ok(());
-            }
+    /// Writes `lib.rs` and a `mod.rs` per namespace directory, mirroring the namespace hierarchy.
+    /// Each context file is a private module re-exported into its namespace,
+    /// so types are reachable as `crate::Namespace::Type`
+    pub fn write_namespace_modules(&self, config: &RustGenerationConfig) -> color_eyre::Result<()> {
+        info!("Writing namespace modules!");
 
-            let mod_path = dir.join(name).with_extension("rs");
-            let mod_file = File::options()
-                .truncate(false)
-                .append(true)
-                .create(true)
-                .open(&mod_path)?;
-            let mut buf_writer = BufWriter::new(mod_file);
-
-            for module in &modules_paths {
-                if module == dir || *module == mod_path {
-                    continue;
-                }
-
-                if !module.exists() {
-                    continue;
-                }
-
-                let file_stem = module.file_stem().unwrap().to_string_lossy();
-
-                if module.is_dir() {
-                    make_mod_dir(module, "mod.rs")?;
-                    writeln!(buf_writer, "// namespace {};", file_stem)?;
-                    writeln!(buf_writer, "pub mod {};", file_stem)?;
-                } else if module.extension() == Some(OsStr::new("rs")) {
-                    writeln!(buf_writer, "// class {}; export all", file_stem)?;
-                    writeln!(buf_writer, "mod {};", file_stem)?;
-                    writeln!(buf_writer, "pub use {}::*;", file_stem)?;
-                }
+        // namespace directory -> (child namespaces, context modules)
+        // BTree so things don't break git diffs
+        let mut modules: BTreeMap<PathBuf, (BTreeSet<String>, BTreeSet<String>)> = BTreeMap::new();
+
+        for context in self.all_contexts.values() {
+            let relative_path =
+                pathdiff::diff_paths(&context.fundamental_path, &config.source_path).unwrap();
+            let file_stem = relative_path.file_stem().unwrap().to_string_lossy();
+
+            let mut dir = relative_path.parent().unwrap().to_path_buf();
+            modules
+                .entry(dir.clone())
+                .or_default()
+                .1
+                .insert(file_stem.to_string());
+
+            // register every namespace up to the crate root
+            while let Some(parent) = dir.parent() {
+                let namespace = dir.file_name().unwrap().to_string_lossy().to_string();
+                modules
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .0
+                    .insert(namespace);
+                dir = parent.to_path_buf();
             }
+        }
 
-            buf_writer.flush()?;
+        for (dir, (namespaces, contexts)) in modules {
+            let is_root = dir.as_os_str().is_empty();
+            let mod_path = match is_root {
+                true => config.source_path.join("lib.rs"),
+                false => config.source_path.join(&dir).join("mod.rs"),
+            };
 
-            Ok(())
-        }
+            let mut buf_writer = BufWriter::new(File::create(&mod_path)?);
 
-        let mod_file = File::options()
-            .create(true)
-            .append(true)
-            .truncate(false)
-            .open(config.source_path.join("lib.rs"))?;
-        let mut buf_writer = BufWriter::new(mod_file);
-        writeln!(
-            buf_writer,
-            "
+            if is_root {
+                writeln!(
+                    buf_writer,
+                    "
         #![feature(inherent_associated_types)]  
 
         #![allow(clippy::all)]
@@ -368,13 +389,25 @@ impl RustContextCollection {
         #![allow(clippy::module_name_repetitions)]
         #![allow(clippy::similar_names)]
         #![allow(clippy::case_sensitive_file_name)]
+        #![allow(deprecated)]
         #![allow(clippy::enum_variant_names)]
         #![allow(clippy::large_enum_variant)]
         "
-        )?;
-        buf_writer.flush()?;
+                )?;
+            }
 
-        make_mod_dir(&config.source_path, "lib.rs")?;
+            for namespace in namespaces {
+                writeln!(buf_writer, "// namespace {namespace};")?;
+                writeln!(buf_writer, "pub mod {namespace};")?;
+            }
+            for context in contexts {
+                writeln!(buf_writer, "// class {context}; export all")?;
+                writeln!(buf_writer, "mod {context};")?;
+                writeln!(buf_writer, "pub use {context}::*;")?;
+            }
+
+            buf_writer.flush()?;
+        }
 
         Ok(())
     }
@@ -1,7 +1,7 @@
 use brocolib::{global_metadata::TypeDefinitionIndex, runtime_metadata::Il2CppTypeEnum};
 use itertools::Itertools;
 use log::warn;
-use quote::{format_ident, quote};
+use quote::format_ident;
 use syn::parse_quote;
 
 use crate::{
@@ -190,51 +190,41 @@ pub fn handle_static_fields(
         return;
     }
 
-    return;
-    todo!();
-
     // we want only static fields
     // we ignore constants
     for field_info in fields.iter().filter(|f| !f.instance && !f.is_const) {
         let f_name = &field_info.name;
 
-        let field_ty_cpp_name =
-            name_resolver.resolve_name(cpp_type, &field_info.field_ty, TypeUsage::Field, true);
-        let field_ty_ast = field_ty_cpp_name.to_type_token();
-
-        // non const field
-        // instance field access on ref types is special
-        // ref type instance fields are specially named because the field getters are supposed to be used
-        let f_cpp_name = config.name_rs(f_name);
+        let field_ty_rs_name = name_resolver
+            .resolve_name(cpp_type, &field_info.field_ty, TypeUsage::Field, true)
+            .wrap_by_gc();
+        let field_ty_ast = field_ty_rs_name.to_type_token();
 
-        let klass_resolver = cpp_type.classof_name();
-
-        let getter_call =
-            quote!("return getStaticField<{field_ty_ast}, \"{f_name}\", {klass_resolver}>();");
+        // statics aren't stored in the instance,
+        // so they are read and written through the declaring class at runtime
+        let f_rs_name = config.name_rs(f_name);
 
+        let getter_name = format_ident!("getStaticF_{}", f_rs_name);
+        let setter_name = format_ident!("setStaticF_{}", f_rs_name);
         let setter_var_name = format_ident!("value");
-        let setter_call =
-                quote!("setStaticField<{field_ty_ast}, \"{f_name}\", {klass_resolver}>(std::forward<{field_ty_ast}>({setter_var_name}));");
-
-        let getter_name = format_ident!("getStaticF_{}", f_cpp_name);
-        let setter_name = format_ident!("setStaticF_{}", f_cpp_name);
-
-        let get_return_type = field_ty_cpp_name;
 
         let getter_decl = RustFunction {
-            name: getter_name.clone(),
+            name: getter_name,
             is_ref: false,
             is_mut: false,
             is_self: false,
             generics: Default::default(),
 
-            return_type: Some(get_return_type.to_type_token()),
+            return_type: Some(parse_quote!(quest_hook::libil2cpp::Result<#field_ty_ast>)),
             params: vec![],
             visibility: (Visibility::Public),
+            docs: field_info.docs.clone(),
+            deprecated: None,
             body: Some(parse_quote! {
-                            #getter_call
-            ,
-                        }),
+                let __cordl_ret: #field_ty_ast = <Self as quest_hook::libil2cpp::Type>::class().load_static_field(#f_name)?;
+
+                Ok(__cordl_ret)
+            }),
             where_clause: None,
         };
 
@@ -246,19 +236,22 @@ pub fn handle_static_fields(
             is_mut: false,
             is_self: false,
 
-            return_type: None,
+            return_type: Some(parse_quote!(quest_hook::libil2cpp::Result<()>)),
             params: vec![RustParam {
-                name: setter_var_name,
-                param_type: field_ty_cpp_name.to_type_token(),
+                name: setter_var_name.clone(),
+                param_type: field_ty_ast.clone(),
             }],
             visibility: (Visibility::Public),
-            body: Some(parse_quote!(
-                #setter_call
-            )),
+            docs: field_info.docs.clone(),
+            deprecated: None,
+            body: Some(parse_quote! {
+                <Self as quest_hook::libil2cpp::Type>::class().store_static_field(#f_name, #setter_var_name)?;
+
+                Ok(())
+            }),
             where_clause: None,
         };
 
-        // only push accessors if declaring ref type, or if static field
         cpp_type.methods.push(getter_decl);
         cpp_type.methods.push(setter_decl);
     }
@@ -293,7 +286,15 @@ pub(crate) fn handle_const_fields(
 
             // const fields with enum types not supported right now
             // TODO:
-            if !cpp_type.is_enum_type && matches!(f_resolved_type.data, ResolvedTypeData::Type(_)) {
+            // blobs are emitted as raw bytes regardless of their type
+            let is_blob = matches!(
+                field_info.value,
+                Some(CsValue::Object(_) | CsValue::ValueType(_))
+            );
+            if !cpp_type.is_enum_type
+                && !is_blob
+                && matches!(f_resolved_type.data, ResolvedTypeData::Type(_))
+            {
                 return None;
             }
             let def_value = field_info.value.as_ref();
@@ -341,8 +342,13 @@ pub(crate) fn handle_const_fields(
                     _ => panic!("Unexpected f64 value: {}", f),
                 },
                 CsValue::Null => parse_quote! { Default::default() },
-                CsValue::Object(_) => todo!(),
-                CsValue::ValueType(_) => todo!(),
+                CsValue::Object(bytes) | CsValue::ValueType(bytes) => {
+                    let len = bytes.len();
+                    let bytes = bytes.iter();
+                    f_type = parse_quote! { [u8; #len] };
+
+                    parse_quote! { [#(#bytes),*] }
+                }
             };
 
             let cpp_field_template = ConstRustField {
@@ -361,7 +367,7 @@ pub(crate) fn handle_const_fields(
         // enums cannot have multiple entries with the same value
         for f in fields
             .into_iter()
-            .unique_by(|f| f.1.value.as_ref().unwrap().to_string())
+            .unique_by(|f| format!("{:?}", f.1.value.as_ref().unwrap()))
         {
             cpp_type.constants.push(f.0);
         }
@@ -545,10 +551,21 @@ fn make_rust_field(
 
     assert!(f.instance && !f.is_const, "Static field not allowed!");
 
+    let visibility = match is_compiler_generated_field(&f.name) {
+        true => Visibility::Private,
+        false => Visibility::Public,
+    };
+
     RustField {
         name: format_ident!("{}", config.name_rs(&f.name)),
         field_type: field_type.wrap_by_gc().to_type_token(),
-        visibility: Visibility::Public,
+        visibility,
         offset: f.offset.unwrap_or_default(),
     }
 }
+
+/// Fields the C# compiler generates, e.g. `<Name>k__BackingField` of auto properties,
+/// which are meant to be used through their property instead
+fn is_compiler_generated_field(name: &str) -> bool {
+    name.starts_with('<')
+}
@@ -7,30 +7,31 @@ use rayon::iter::ParallelIterator;
 use crate::generate::{
     cs_context_collection::TypeContextCollection,
     metadata::CordlMetadata,
-    rust::{config::STATIC_CONFIG, rust_context_collection::RustContextCollection},
+    rust::{config::RustGenerationConfig, rust_context_collection::RustContextCollection},
     type_extensions::{TypeDefinitionExtensions, TypeDefinitionIndexExtensions},
 };
 
 pub fn run_rust(
     cs_collection: TypeContextCollection,
     metadata: &CordlMetadata,
+    config: &RustGenerationConfig,
 ) -> color_eyre::Result<()> {
     let rs_context_collection =
-        RustContextCollection::from_cs_collection(cs_collection, metadata, &STATIC_CONFIG);
+        RustContextCollection::from_cs_collection(cs_collection, metadata, config);
 
     info!("Registering handlers!");
 
     // let e = cpp_context_collection.cyclic_include_check()?;
 
-    if STATIC_CONFIG.source_path.exists() {
-        std::fs::remove_dir_all(&STATIC_CONFIG.source_path)?;
+    if config.source_path.exists() {
+        std::fs::remove_dir_all(&config.source_path)?;
     }
-    std::fs::create_dir_all(&STATIC_CONFIG.source_path)?;
+    std::fs::create_dir_all(&config.source_path)?;
 
     const write_all: bool = true;
     if write_all {
         info!("Writing all");
-        rs_context_collection.write_all(&STATIC_CONFIG)?;
+        rs_context_collection.write_all(config, &metadata.timings)?;
     } else {
         // for t in &metadata.type_definitions {
         //     // Handle the generation for a single type
@@ -68,7 +69,7 @@ pub fn run_rust(
             .find(|(_, c)| c.get_types().iter().any(|(_, t)| t.rs_name_components.generics.is_some()))
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("List Generic type");
         types()
             .find(|(_, c)| {
@@ -78,7 +79,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("Value type");
         types()
             .find(|(_, c)| {
@@ -88,7 +89,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         // info!("Nested type");
         // cpp_context_collection
         //     .get()
@@ -125,14 +126,14 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
 
         info!("Enum type");
         types()
             .find(|(_, c)| c.get_types().iter().any(|(_, t)| t.is_enum_type))
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("UnityEngine.Object");
         types()
             .find(|(_, c)| {
@@ -142,7 +143,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("BeatmapSaveDataHelpers");
         types()
             .find(|(_, c)| {
@@ -152,7 +153,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("HMUI.ViewController");
         types()
             .find(|(_, c)| {
@@ -162,7 +163,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("UnityEngine.Component");
         types()
             .find(|(_, c)| {
@@ -172,7 +173,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("UnityEngine.GameObject");
         types()
             .find(|(_, c)| {
@@ -182,7 +183,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("MainFlowCoordinator");
         types()
             .find(|(_, c)| {
@@ -192,7 +193,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("OVRPlugin");
         types()
             .find(|(_, c)| {
@@ -202,7 +203,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("HMUI.IValueChanger");
         types()
             .find(|(_, c)| {
@@ -212,7 +213,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.ValueType");
         types()
             .find(|(_, c)| {
@@ -222,7 +223,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.ValueTuple_2");
         types()
             .find(|(_, c)| {
@@ -232,7 +233,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Decimal");
         types()
             .find(|(_, c)| {
@@ -242,7 +243,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Enum");
         types()
             .find(|(_, c)| {
@@ -252,7 +253,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Multicast");
         types()
             .find(|(_, c)| {
@@ -262,7 +263,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("System.Delegate");
         types()
             .find(|(_, c)| {
@@ -272,7 +273,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("BeatmapSaveDataVersion3.BeatmapSaveData.EventBoxGroup`1");
         types()
             .find(|(_, c)| {
@@ -282,7 +283,7 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
         info!("Explicitly laid out type");
         types()
             .find(|(_, c)| {
@@ -296,15 +297,16 @@ pub fn run_rust(
             })
             .unwrap()
             .1
-            .write(&STATIC_CONFIG)?;
+            .write(config)?;
 
         // for (_, context) in cpp_context_collection.get() {
         //     context.write().unwrap();
         // }
     }
 
-    rs_context_collection.write_namespace_modules(&STATIC_CONFIG)?;
-    rs_context_collection.write_feature_block(&STATIC_CONFIG)?;
+    rs_context_collection.write_namespace_modules(config)?;
+    rs_context_collection.write_feature_block(config)?;
+    rs_context_collection.write_version_constant(config, &metadata.version_marker())?;
 
     Ok(())
 }
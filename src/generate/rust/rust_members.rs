@@ -13,6 +13,14 @@ pub enum Visibility {
     Private,
 }
 
+/// `#[deprecated]` with `message` as its note, if any
+pub fn deprecated_attribute(message: Option<&str>) -> Option<TokenStream> {
+    message.map(|message| match message.is_empty() {
+        true => quote! { #[deprecated] },
+        false => quote! { #[deprecated(note = #message)] },
+    })
+}
+
 #[derive(Clone)]
 pub struct RustStruct {
     pub fields: Vec<RustField>,
@@ -110,6 +118,10 @@ pub struct RustFunction {
     pub is_ref: bool,
     pub is_mut: bool,
     pub visibility: Visibility,
+    /// Summary from the xml documentation, written as `///` comments
+    pub docs: Option<String>,
+    /// Written as `#[deprecated]`, see `CsMethod::obsolete`
+    pub deprecated: Option<String>,
 }
 
 #[derive(Clone)]
@@ -173,14 +185,27 @@ impl RustFunction {
         let where_clause = &self.where_clause;
 
         let visibility = self.visibility.to_token_stream();
+        let docs = self
+            .docs
+            .iter()
+            .flat_map(|docs| docs.lines())
+            .map(|line| format!(" {line}"))
+            .collect_vec();
+
+        let deprecated = deprecated_attribute(self.deprecated.as_deref());
+
         let mut tokens = match self_param {
             Some(self_param) => {
                 quote! {
+                    #(#[doc = #docs])*
+                    #deprecated
                     #visibility fn #name #generics (#self_param, #(#params),*) #return_type #where_clause
                 }
             }
             None => {
                 quote! {
+                    #(#[doc = #docs])*
+                    #deprecated
                     #visibility fn #name #generics (#(#params),*) #return_type #where_clause
                 }
             }
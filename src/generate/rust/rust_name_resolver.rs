@@ -3,7 +3,10 @@ use itertools::Itertools;
 
 use crate::{
     data::type_resolver::{ResolvedType, ResolvedTypeData, TypeUsage},
-    generate::{cs_type_tag::CsTypeTag, metadata::CordlMetadata},
+    generate::{
+        cs_type_tag::CsTypeTag, metadata::CordlMetadata,
+        type_extensions::GenericParameterExtensions,
+    },
 };
 
 use super::{
@@ -79,27 +82,33 @@ impl<'b> RustNameResolver<'_, 'b> {
                 //     .get(&method_index)
                 //     .and_then(|v| v.get(method_arg as usize));
 
-                generic_param.name(metadata.metadata).to_string().into()
+                generic_param.unshadowed_name(metadata.metadata).into()
             }
             ResolvedTypeData::Ptr(resolved_type) => {
-                let generic_formatted = self
-                    .resolve_name(declaring_cpp_type, resolved_type, type_usage, hard_include)
-                    .wrap_by_gc();
-                // RustNameComponents {
-                //     namespace: Some("cordl_internals".into()),
-                //     generics: Some(vec![generic_formatted.combine_all().into()]),
-                //     name: "Ptr".into(),
-                //     ..Default::default()
-                // }
-
-                // TODO: Ptr type
+                // `void*` has no sized pointee in Rust
+                let pointee = match &resolved_type.data {
+                    ResolvedTypeData::Primitive(Il2CppTypeEnum::Void) => RustNameComponents {
+                        name: "c_void".into(),
+                        namespace: Some("std::ffi".to_string()),
+
+                        ..Default::default()
+                    },
+                    _ => self
+                        .resolve_name(declaring_cpp_type, resolved_type, type_usage, hard_include)
+                        .wrap_by_gc(),
+                };
+                // nested pointers, e.g. `int**`
+                let pointee = if pointee.is_ptr {
+                    RustNameComponents::from(pointee.combine_all())
+                } else {
+                    pointee
+                };
+
                 RustNameComponents {
-                    name: "Il2CppObject".into(),
-                    namespace: Some("quest_hook::libil2cpp".to_string()),
                     is_ptr: true,
                     is_mut: true,
 
-                    ..Default::default()
+                    ..pointee
                 }
             }
             ResolvedTypeData::Type(resolved_tag) => {
@@ -252,6 +261,8 @@ impl<'b> RustNameResolver<'_, 'b> {
             Il2CppTypeEnum::U2 => "u16",
             Il2CppTypeEnum::U4 => "u32",
             Il2CppTypeEnum::U8 => "u64",
+            Il2CppTypeEnum::I => "isize",
+            Il2CppTypeEnum::U => "usize",
 
             Il2CppTypeEnum::R4 => "f32",
             Il2CppTypeEnum::R8 => "f64",
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use color_eyre::eyre::{Context, ContextCompat, Result};
 use itertools::Itertools;
+use log::warn;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
 use syn::parse_quote;
@@ -12,12 +13,14 @@ use crate::{
         type_resolver::{ResolvedType, TypeUsage},
     },
     generate::{
-        cs_members::{CsConstructor, CsField, CsMethod, CsParam},
+        cs_members::{CSMethodFlags, CsConstructor, CsField, CsMethod, CsParam},
         cs_type::CsType,
         cs_type_tag::{self, CsTypeTag},
         metadata::CordlMetadata,
         offsets::SizeInfo,
-        type_extensions::{TypeDefinitionExtensions, TypeDefinitionIndexExtensions},
+        type_extensions::{
+            TypeDefinitionExtensions, TypeDefinitionIndexExtensions, TypeExtentions,
+        },
         writer::Writer,
     },
 };
@@ -26,8 +29,8 @@ use super::{
     config::RustGenerationConfig,
     rust_fields,
     rust_members::{
-        ConstRustField, RustFeature, RustField, RustFunction, RustGeneric, RustParam,
-        RustTraitImpl, Visibility,
+        deprecated_attribute, ConstRustField, RustFeature, RustField, RustFunction, RustGeneric,
+        RustParam, RustTraitImpl, Visibility,
     },
     rust_name_components::RustNameComponents,
     rust_name_resolver::RustNameResolver,
@@ -92,21 +95,33 @@ pub struct RustType {
     pub is_enum_type: bool,
     pub is_reference_type: bool,
     pub is_interface: bool,
+    pub is_abstract: bool,
+    /// See `CsType::is_flags_like`
+    pub is_flags_enum: bool,
+    /// Value type made only of plain data, see `TypeExtentions::is_plain_data`.
+    /// Only these can be copied and zeroed without touching managed references
+    pub is_blittable: bool,
 
     pub self_tag: CsTypeTag,
     pub self_feature: Option<RustFeature>,
 
     pub parent: Option<RustNameComponents>,
     pub backing_type_enum: Option<RustNameComponents>,
+    /// Variant marked `#[default]` instead of the first one
+    pub enum_default: Option<syn::Ident>,
 
     pub cs_name_components: NameComponents,
     pub rs_name_components: RustNameComponents,
+    /// Resolved arguments of a closed generic, `None` for open generics and non generic types
+    pub generic_instantiation_args: Option<Vec<RustNameComponents>>,
     pub(crate) prefix_comments: Vec<String>,
 
     pub requirements: RustTypeRequirements,
     pub packing: Option<u32>,
     pub size_info: Option<SizeInfo>,
     pub is_compiler_generated: bool,
+    /// Written as `#[deprecated]`, see `CsType::obsolete`
+    pub deprecated: Option<String>,
 }
 impl RustType {
     pub(crate) fn make_rust_type(
@@ -119,9 +134,17 @@ impl RustType {
         let generics = cs_type.generic_template.as_ref().map(|g| {
             g.names
                 .iter()
-                .map(|(ty, s)| RustGeneric {
-                    name: s.to_string(),
-                    bounds: vec!["quest_hook::libil2cpp::Type".to_string()],
+                .map(|(_ty, s)| {
+                    let mut bounds = vec!["quest_hook::libil2cpp::Type".to_string()];
+                    // `where T : new()`
+                    if g.is_default_constructible(s) {
+                        bounds.push("Default".to_string());
+                    }
+
+                    RustGeneric {
+                        name: s.to_string(),
+                        bounds,
+                    }
                 })
                 .collect_vec()
         });
@@ -153,19 +176,28 @@ impl RustType {
             is_enum_type: cs_type.is_enum_type,
             is_reference_type: cs_type.is_reference_type,
             is_interface: cs_type.is_interface,
+            is_abstract: cs_type.is_abstract,
+            is_flags_enum: cs_type.is_enum_type && cs_type.is_flags_like(),
+            is_blittable: false,
             parent: Default::default(),
             backing_type_enum: Default::default(),
+            enum_default: cs_type
+                .enum_default
+                .as_ref()
+                .map(|v| format_ident!("{}", config.name_rs(v))),
 
             requirements: RustTypeRequirements::default(),
             self_feature: Some(RustFeature { name: feature_name }),
             self_tag: tag,
 
             rs_name_components,
+            generic_instantiation_args: None,
             cs_name_components: cs_type.cs_name_components.clone(),
             prefix_comments: vec![],
             packing: cs_type.packing.map(|p| p as u32),
             size_info: cs_type.size_info.clone(),
             is_compiler_generated: cs_type.is_compiler_generated,
+            deprecated: cs_type.obsolete.clone(),
         }
     }
 
@@ -175,6 +207,9 @@ impl RustType {
         name_resolver: &RustNameResolver,
         config: &RustGenerationConfig,
     ) {
+        self.make_generic_instantiation_args(&cs_type, name_resolver);
+        self.make_generic_constraints(&cs_type.generic_interface_constraints, name_resolver);
+
         if cs_type.is_interface || cs_type.namespace() == "System" && cs_type.name() == "Object" {
             self.make_object_parent();
         } else {
@@ -182,19 +217,53 @@ impl RustType {
         }
 
         self.make_nested_types(&cs_type.nested_types, name_resolver);
-        self.make_interfaces(&cs_type.interfaces, name_resolver, config);
+        // ref structs cannot be boxed, so no interface conversions
+        if !cs_type.is_byref_like {
+            self.make_interfaces(&cs_type.interfaces, name_resolver, config);
+        }
 
         self.make_fields(&cs_type.fields, name_resolver, config);
+        self.is_blittable = self.is_value_type
+            && !self.is_enum_type
+            && Self::fields_are_plain_data(&cs_type, name_resolver.cordl_metadata);
+        if cs_type.methods.is_empty() {
+            self.make_opaque_storage();
+        }
+
+        match cs_type.is_byref_like {
+            // invoking a constructor boxes the instance, which ref structs can't be
+            true => {
+                let methods = cs_type
+                    .methods
+                    .iter()
+                    .filter(|m| m.name != ".ctor")
+                    .cloned()
+                    .collect_vec();
+                self.make_methods(&methods, name_resolver, config);
+            }
+            false => self.make_methods(&cs_type.methods, name_resolver, config),
+        }
 
-        self.make_methods(&cs_type.methods, name_resolver, config);
+        if cs_type.to_string_method.is_some() && !self.is_interface {
+            self.make_display();
+        }
 
         // add phantom markers
         self.make_generics();
 
-        if self.is_reference_type {
+        // abstract types can't be instantiated with `New`
+        if self.is_reference_type && !self.is_abstract {
             self.make_ref_constructors(&cs_type.constructors, name_resolver, config);
         }
 
+        if self.is_reference_type {
+            self.make_null_check();
+        }
+
+        if self.is_reference_type && !self.is_interface {
+            self.make_cast_helpers();
+        }
+
         if self.is_interface {
             self.methods.push(RustFunction {
                 name: format_ident!("from_object_mut"),
@@ -212,6 +281,8 @@ impl RustType {
                 }],
                 return_type: Some(parse_quote!(*mut Self)),
                 visibility: Visibility::Public,
+                docs: None,
+                deprecated: None,
             });
         }
 
@@ -303,7 +374,7 @@ impl RustType {
 
     fn make_nested_types(
         &mut self,
-        nested_types: &HashSet<CsTypeTag>,
+        nested_types: &[CsTypeTag],
         name_resolver: &RustNameResolver<'_, '_>,
     ) {
         let nested_types = nested_types
@@ -392,7 +463,204 @@ impl RustType {
         // }
     }
 
+    /// `is_null` helper for instance pointers, like the C++ `operator bool`
+    fn make_null_check(&mut self) {
+        let name = format_ident!("is_null");
+        // C# methods take precedence
+        if self.methods.iter().any(|m| m.name == name) {
+            return;
+        }
+
+        self.methods.push(RustFunction {
+            name,
+            body: Some(parse_quote! {
+                object_param.is_null()
+            }),
+            generics: Default::default(),
+            is_mut: false,
+            is_ref: false,
+            is_self: false,
+            where_clause: None,
+            params: vec![RustParam {
+                name: format_ident!("object_param"),
+                param_type: parse_quote!(*const Self),
+            }],
+            return_type: Some(parse_quote!(bool)),
+            visibility: Visibility::Public,
+            docs: Some("Whether the instance pointer is null".to_string()),
+            deprecated: None,
+        });
+    }
+
+    /// `try_cast` and `is` mirroring C#'s `as` and `is`
+    fn make_cast_helpers(&mut self) {
+        let try_cast = format_ident!("try_cast");
+        let is = format_ident!("is");
+        // C# methods take precedence
+        if self
+            .methods
+            .iter()
+            .any(|m| m.name == try_cast || m.name == is)
+        {
+            return;
+        }
+
+        let generics = vec![RustGeneric {
+            name: "T".to_string(),
+            bounds: vec!["quest_hook::libil2cpp::Type".to_string()],
+        }];
+
+        self.methods.push(RustFunction {
+            name: try_cast.clone(),
+            body: Some(parse_quote! {
+                let __cordl_object = quest_hook::libil2cpp::ObjectType::as_object(self);
+                if !<T as quest_hook::libil2cpp::Type>::class().is_assignable_from(__cordl_object.class()) {
+                    return None;
+                }
+                Some(unsafe { &*(__cordl_object as *const quest_hook::libil2cpp::Il2CppObject as *const T) })
+            }),
+            generics: generics.clone(),
+            is_mut: false,
+            is_ref: true,
+            is_self: true,
+            where_clause: None,
+            params: vec![],
+            return_type: Some(parse_quote!(Option<&T>)),
+            visibility: Visibility::Public,
+            docs: Some("The instance as `T` if its runtime class is assignable to `T`".to_string()),
+            deprecated: None,
+        });
+
+        self.methods.push(RustFunction {
+            name: is,
+            body: Some(parse_quote! {
+                self.#try_cast::<T>().is_some()
+            }),
+            generics,
+            is_mut: false,
+            is_ref: true,
+            is_self: true,
+            where_clause: None,
+            params: vec![],
+            return_type: Some(parse_quote!(bool)),
+            visibility: Visibility::Public,
+            docs: Some("Whether the runtime class is assignable to `T`".to_string()),
+            deprecated: None,
+        });
+    }
+
+    /// Explicit layouts are padded with `ValueTypePadding`, which isn't known to be plain data
+    fn fields_are_plain_data(cs_type: &CsType, metadata: &CordlMetadata) -> bool {
+        let td = cs_type
+            .self_tag
+            .get_tdi()
+            .get_type_definition(metadata.metadata);
+
+        !td.is_explicit_layout()
+            && cs_type
+                .fields
+                .iter()
+                .filter(|f| f.instance && !f.is_const)
+                .all(|f| {
+                    metadata.metadata_registration.types[f.field_ty.ty]
+                        .is_plain_data(metadata.metadata)
+                })
+    }
+
+    /// Opaque native value types have a size but no members,
+    /// back them with raw bytes so they still match il2cpp's size
+    fn make_opaque_storage(&mut self) {
+        if !self.is_value_type || self.is_enum_type || !self.fields.is_empty() {
+            return;
+        }
+
+        let Some(size) = self.size_info.as_ref().map(|s| s.instance_size as usize) else {
+            return;
+        };
+        if size == 0 || size == u32::MAX as usize {
+            return;
+        }
+
+        self.fields.push(RustField {
+            name: format_ident!("_opaque"),
+            field_type: parse_quote!([u8; #size]),
+            visibility: Visibility::Private,
+            offset: 0,
+        });
+    }
+
+    /// Bounds the generic parameters by their `where T : IFoo` constraints
+    fn make_generic_constraints(
+        &mut self,
+        constraints: &[(String, ResolvedType)],
+        name_resolver: &RustNameResolver,
+    ) {
+        for (name, interface) in constraints {
+            let bound = self.interface_bound(interface, name_resolver);
+
+            if let Some(generic) = self
+                .rs_name_components
+                .generics
+                .iter_mut()
+                .flatten()
+                .find(|g| &g.name == name)
+            {
+                generic.bounds.push(bound);
+            }
+        }
+    }
+
+    /// Interfaces are structs, generated types convert to them through `AsRef`
+    fn interface_bound(
+        &mut self,
+        interface: &ResolvedType,
+        name_resolver: &RustNameResolver,
+    ) -> String {
+        let interface = name_resolver.resolve_name(self, interface, TypeUsage::TypeName, true);
+        let interface = RustNameComponents {
+            is_ptr: false,
+            is_ref: false,
+            is_mut: false,
+            ..interface
+        };
+
+        format!("AsRef<{}>", interface.combine_all())
+    }
+
+    /// Closed generics render with their arguments, e.g. `List_1<i32>`, instead of the open parameters
+    fn make_generic_instantiation_args(
+        &mut self,
+        cs_type: &CsType,
+        name_resolver: &RustNameResolver,
+    ) {
+        let Some(args) = &cs_type.generic_instantiations_args_types else {
+            return;
+        };
+
+        let args = args
+            .iter()
+            .map(|arg| {
+                name_resolver
+                    .resolve_name(self, arg, TypeUsage::GenericArg, true)
+                    .wrap_by_gc()
+            })
+            .collect_vec();
+
+        self.rs_name_components.generics = Some(
+            args.iter()
+                .map(|arg| arg.combine_all())
+                .map(RustGeneric::from)
+                .collect_vec(),
+        );
+        self.generic_instantiation_args = Some(args);
+    }
+
     fn make_generics(&mut self) {
+        // closed generics have no parameters to mark
+        if self.generic_instantiation_args.is_some() {
+            return;
+        }
+
         let Some(generic) = &self.rs_name_components.generics else {
             return;
         };
@@ -458,6 +726,39 @@ impl RustType {
         }
     }
 
+    /// Implement `Display` by forwarding to the C# `ToString()` override
+    fn make_display(&mut self) {
+        let self_ident = self.rs_name_components.to_type_path_token();
+        let generics = self.get_generics(0);
+
+        let is_value_type = self.is_value_type || self.is_enum_type;
+
+        // invoke needs a mutable receiver, ToString is not expected to mutate
+        let invoke_call: Vec<syn::Stmt> = match is_value_type {
+            true => parse_quote! {
+                let __cordl_this = unsafe { &mut *(self as *const Self as *mut Self) };
+                let __cordl_ret: quest_hook::libil2cpp::Gc<quest_hook::libil2cpp::Il2CppString> = quest_hook::libil2cpp::ValueTypeExt::invoke(__cordl_this, "ToString", ()).map_err(|_| std::fmt::Error)?;
+            },
+            false => parse_quote! {
+                let __cordl_object = unsafe { &mut *(quest_hook::libil2cpp::ObjectType::as_object(self) as *const quest_hook::libil2cpp::Il2CppObject as *mut quest_hook::libil2cpp::Il2CppObject) };
+                let __cordl_ret: quest_hook::libil2cpp::Gc<quest_hook::libil2cpp::Il2CppString> = __cordl_object.invoke("ToString", ()).map_err(|_| std::fmt::Error)?;
+            },
+        };
+
+        self.traits.push(RustTraitImpl {
+            name: "std::fmt::Display".to_string(),
+            impl_data: parse_quote! {
+                impl #generics std::fmt::Display for #self_ident {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        #(#invoke_call)*
+
+                        write!(f, "{}", __cordl_ret)
+                    }
+                }
+            },
+        });
+    }
+
     fn make_ref_constructors(
         &mut self,
         constructors: &[CsConstructor],
@@ -545,6 +846,8 @@ impl RustType {
                     quest_hook::libil2cpp::Result<quest_hook::libil2cpp::Gc<Self>>
                 )),
                 visibility: (Visibility::Public),
+                docs: None,
+                deprecated: None,
             };
             self.methods.push(rust_func);
         }
@@ -617,30 +920,80 @@ impl RustType {
         name_resolver: &RustNameResolver,
         config: &RustGenerationConfig,
     ) {
+        let methods = methods
+            .iter()
+            .filter(|m| {
+                let is_static_abstract = self.is_static_abstract(m);
+                if is_static_abstract {
+                    warn!(
+                        "Skipping static abstract interface method {}::{}, it cannot be invoked",
+                        self.cs_name_components.combine_all(),
+                        m.name
+                    );
+                }
+                !is_static_abstract
+            })
+            .collect_vec();
+
+        // closed instantiations of generic methods are named after their generic arguments,
+        // so they neither collide with the open method nor with each other
+        let generic_insts = methods
+            .iter()
+            .map(|m| {
+                m.generic_inst.as_ref().map(|args| {
+                    args.iter()
+                        .map(|arg| {
+                            name_resolver.resolve_name(self, arg, TypeUsage::GenericArg, true)
+                        })
+                        .collect_vec()
+                })
+            })
+            .collect_vec();
+        let inst_names = generic_insts
+            .iter()
+            .zip(&methods)
+            .map(|(inst, m)| match inst {
+                Some(args) => format!(
+                    "{}_{}",
+                    m.name,
+                    args.iter().map(|arg| config.name_rs(&arg.name)).join("_")
+                ),
+                None => m.name.clone(),
+            })
+            .collect_vec();
+
         for (_, overload_methods) in methods
             .iter()
+            .zip(&generic_insts)
+            .zip(&inst_names)
             // .filter(|m| m.instance)
-            .into_group_map_by(|m| &m.name)
+            .into_group_map_by(|(_, inst_name)| *inst_name)
         {
             let overloaded_method_data = overload_methods
                 .iter()
-                .map(|m| (m.name.clone(), m.parameters.as_slice()))
+                .map(|((m, _), inst_name)| ((*inst_name).clone(), m.parameters.as_slice()))
                 .collect_vec();
 
-            for (i, m) in overload_methods.iter().enumerate() {
+            for (i, ((m, generic_inst), inst_name)) in overload_methods.iter().enumerate() {
                 let m_name = &m.name;
 
                 let m_name_rs = self.make_overloaded_name(
                     &overloaded_method_data,
                     name_resolver,
-                    (m_name.clone(), m.parameters.as_slice()),
+                    ((*inst_name).clone(), m.parameters.as_slice()),
                     i,
                 );
 
                 let m_ret_ty = name_resolver
                     .resolve_name(self, &m.return_type, TypeUsage::ReturnType, true)
                     .wrap_by_gc();
-                let m_ret_ty_ident = m_ret_ty.to_type_token();
+                let m_ret_ty_ident = match m.nullable_return {
+                    true => {
+                        let ret_ty = m_ret_ty.to_type_token();
+                        parse_quote!(Option<#ret_ty>)
+                    }
+                    false => m_ret_ty.to_type_token(),
+                };
                 let m_result_ty: syn::Type =
                     parse_quote!(quest_hook::libil2cpp::Result<#m_ret_ty_ident>);
 
@@ -652,7 +1005,35 @@ impl RustType {
 
                 let param_names = params.iter().map(|p| &p.name);
 
-                let body = self.make_method_body(m, m_name, param_names, m_ret_ty_ident);
+                let method_generics: Vec<syn::Type> = match generic_inst {
+                    Some(args) => args
+                        .iter()
+                        .map(|arg| arg.clone().wrap_by_gc().to_type_token())
+                        .collect(),
+                    None => m
+                        .template
+                        .iter()
+                        .flat_map(|t| t.just_names())
+                        .map(|g| {
+                            let g = format_ident!("{g}");
+                            parse_quote!(#g)
+                        })
+                        .collect(),
+                };
+
+                let body = Some(self.make_method_body(
+                    m.instance,
+                    m_name,
+                    &method_generics,
+                    param_names,
+                    m_ret_ty_ident,
+                ));
+
+                let constraint_bounds = m
+                    .generic_interface_constraints
+                    .iter()
+                    .map(|(name, interface)| (name, self.interface_bound(interface, name_resolver)))
+                    .collect_vec();
 
                 let generics = m
                     .template
@@ -660,9 +1041,22 @@ impl RustType {
                     .map(|t| {
                         t.just_names()
                             .map(|g| -> RustGeneric {
+                                // `where T : new()`
+                                let mut bounds = match t.is_default_constructible(g) {
+                                    true => vec!["Default".to_string()],
+                                    false => vec![],
+                                };
+                                // `where T : IFoo`
+                                bounds.extend(
+                                    constraint_bounds
+                                        .iter()
+                                        .filter(|(name, _)| *name == g)
+                                        .map(|(_, bound)| bound.clone()),
+                                );
+
                                 RustGeneric {
                                     name: g.clone(),
-                                    bounds: vec![],
+                                    bounds,
                                 }
                             })
                             .collect_vec()
@@ -696,7 +1090,7 @@ impl RustType {
 
                 let rust_func = RustFunction {
                     name: format_ident!("{m_name_rs}"),
-                    body: Some(body),
+                    body,
                     generics,
                     is_mut: m.instance,
                     is_ref: m.instance,
@@ -706,22 +1100,40 @@ impl RustType {
 
                     return_type: Some(m_result_ty),
                     visibility: (Visibility::Public),
+                    docs: m.docs.clone(),
+                    deprecated: m.obsolete.clone(),
                 };
                 self.methods.push(rust_func);
             }
         }
     }
 
+    /// Static abstract interface methods have no class implementation to invoke,
+    /// so they are left out instead of emitting a method that can't work
+    fn is_static_abstract(&self, m: &CsMethod) -> bool {
+        self.is_interface && !m.instance && m.method_flags.contains(CSMethodFlags::ABSTRACT)
+    }
+
     fn make_method_body<'a>(
         &self,
-        m: &CsMethod,
+        instance: bool,
         m_name: &String,
+        method_generics: &[syn::Type],
         param_names: impl Iterator<Item = &'a syn::Ident>,
         m_ret_ty: syn::Type,
     ) -> Vec<syn::Stmt> {
         let is_value_type = self.is_value_type || self.is_enum_type;
+        // the generic arguments select the instantiation to invoke
+        let is_generic = !method_generics.is_empty();
 
-        let invoke_call: Vec<syn::Stmt> = match (m.instance, is_value_type) {
+        let invoke_call: Vec<syn::Stmt> = match (instance, is_value_type) {
+            // instance generic, value type
+            (true, true) if is_generic => parse_quote! {
+
+                let __cordl_ret: #m_ret_ty = quest_hook::libil2cpp::ValueTypeExt::invoke_generic::<( #(#method_generics,)* ), _, _>(self, #m_name, ( #(#param_names),* ))?;
+
+                Ok(__cordl_ret.into())
+            },
             // instance, value type
             (true, true) => parse_quote! {
 
@@ -729,6 +1141,32 @@ impl RustType {
 
                 Ok(__cordl_ret.into())
             },
+            // instance generic, interface
+            (true, false) if self.is_interface && is_generic => parse_quote! {
+                let __cordl_object: &mut quest_hook::libil2cpp::Il2CppObject = quest_hook::libil2cpp::ObjectType::as_object_mut(self);
+
+                let __cordl_ret: #m_ret_ty = __cordl_object.invoke_interface_generic::<( #(#method_generics,)* ), _, _>(<Self as quest_hook::libil2cpp::Type>::class(), #m_name, ( #(#param_names),* ))?;
+
+                Ok(__cordl_ret.into())
+            },
+            // instance, interface
+            // explicit implementations are named `Ns.IFoo.Bar` on the class and a public `Bar`
+            // may shadow them, so dispatch through the interface method instead of by name
+            (true, false) if self.is_interface => parse_quote! {
+                let __cordl_object: &mut quest_hook::libil2cpp::Il2CppObject = quest_hook::libil2cpp::ObjectType::as_object_mut(self);
+
+                let __cordl_ret: #m_ret_ty = __cordl_object.invoke_interface(<Self as quest_hook::libil2cpp::Type>::class(), #m_name, ( #(#param_names),* ))?;
+
+                Ok(__cordl_ret.into())
+            },
+            // instance generic, ref type
+            (true, false) if is_generic => parse_quote! {
+                let __cordl_object: &mut quest_hook::libil2cpp::Il2CppObject = quest_hook::libil2cpp::ObjectType::as_object_mut(self);
+
+                let __cordl_ret: #m_ret_ty = __cordl_object.invoke_generic::<( #(#method_generics,)* ), _, _>(#m_name, ( #(#param_names),* ))?;
+
+                Ok(__cordl_ret.into())
+            },
             // instance, ref type
             (true, false) => parse_quote! {
                 let __cordl_object: &mut quest_hook::libil2cpp::Il2CppObject = quest_hook::libil2cpp::ObjectType::as_object_mut(self);
@@ -737,6 +1175,12 @@ impl RustType {
 
                 Ok(__cordl_ret.into())
             },
+            // static generic
+            (false, _) if is_generic => parse_quote! {
+                let __cordl_ret: #m_ret_ty = <Self as quest_hook::libil2cpp::Type>::class().invoke_generic::<( #(#method_generics,)* ), _, _>(#m_name, ( #(#param_names),* ) )?;
+
+                Ok(__cordl_ret.into())
+            },
             // static
             (false, _) => parse_quote! {
                 let __cordl_ret: #m_ret_ty = <Self as quest_hook::libil2cpp::Type>::class().invoke(#m_name, ( #(#param_names),* ) )?;
@@ -787,6 +1231,11 @@ impl RustType {
     }
 
     pub(crate) fn write(&self, writer: &mut Writer, config: &RustGenerationConfig) -> Result<()> {
+        // the open generic definition and its impls already cover closed generics
+        if self.generic_instantiation_args.is_some() {
+            return Ok(());
+        }
+
         if self.is_value_type {
             if self.is_enum_type {
                 self.write_enum_type(writer, config)?;
@@ -897,8 +1346,15 @@ impl RustType {
             }
         });
 
+        // not packed, the parent `Deref` can't borrow a packed field
+        let deprecated = deprecated_attribute(self.deprecated.as_deref());
+
         let mut tokens = quote! {
             #feature
+            #deprecated
+            /// Reference type, this struct is the il2cpp object itself.
+            /// It is not `Clone` since copying it would copy the object's memory,
+            /// clone the `Gc` handle to share the same object instead
             #[repr(C)]
             #[derive(Debug)]
             pub struct #name_ident {
@@ -933,13 +1389,62 @@ impl RustType {
             });
         }
 
+        tokens.extend(self.implement_raw_conversions());
+
         writer.write_pretty_tokens(tokens)?;
 
         self.write_impl(writer, config)?;
         Ok(())
     }
 
+    /// `from_raw`/`into_raw` between the type and `*mut Il2CppObject`
+    /// The parent is always the first field, so `into_raw` gives the same pointer through `Deref`
+    fn implement_raw_conversions(&self) -> Option<TokenStream> {
+        // C# methods take precedence
+        if self
+            .methods
+            .iter()
+            .any(|m| m.name == "from_raw" || m.name == "into_raw")
+        {
+            return None;
+        }
+
+        let path_ident = self.rs_name_components.to_type_path_token();
+        let generics = self.get_generics(0);
+        let feature = self.self_feature.as_ref().map(|f| {
+            let name = &f.name;
+            quote! {
+                #[cfg(feature = #name)]
+            }
+        });
+
+        Some(quote! {
+            #feature
+            impl #generics #path_ident {
+                /// Reinterprets an il2cpp object pointer as this type
+                ///
+                /// # Safety
+                /// `object` must be a non null instance of this type or a subclass that outlives `'a`
+                pub unsafe fn from_raw<'a>(object: *mut quest_hook::libil2cpp::Il2CppObject) -> &'a Self {
+                    unsafe { &*(object as *const Self) }
+                }
+
+                /// Pointer to the instance as an il2cpp object
+                pub fn into_raw(&self) -> *mut quest_hook::libil2cpp::Il2CppObject {
+                    self as *const Self as *mut quest_hook::libil2cpp::Il2CppObject
+                }
+            }
+        })
+    }
+
     fn write_enum_type(&self, writer: &mut Writer, config: &RustGenerationConfig) -> Result<()> {
+        // configured default if it exists, first variant otherwise
+        let default_index = self
+            .enum_default
+            .as_ref()
+            .and_then(|default| self.constants.iter().position(|f| &f.name == default))
+            .unwrap_or_default();
+
         let fields = self
             .constants
             .iter()
@@ -949,7 +1454,7 @@ impl RustType {
                 let val = &f.value;
 
                 // add default for enum
-                if i == 0 {
+                if i == default_index {
                     return parse_quote! {
                         #[default]
                         #name = #val
@@ -990,8 +1495,13 @@ impl RustType {
             }
         });
 
+        let conversions = self.implement_enum_conversions(&backing_type);
+
+        let deprecated = deprecated_attribute(self.deprecated.as_deref());
+
         let tokens = quote! {
             #feature
+            #deprecated
             #[repr(#backing_type)]
             #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
             pub enum #name_ident {
@@ -1000,6 +1510,8 @@ impl RustType {
 
 
             #impl_value
+
+            #conversions
         };
 
         writer.write_pretty_tokens(tokens)?;
@@ -1009,6 +1521,48 @@ impl RustType {
         Ok(())
     }
 
+    /// Conversion to the backing integer, and back for values that have a variant.
+    /// Flags enums are left without `TryFrom` since combined flags usually have no variant
+    fn implement_enum_conversions(&self, backing_type: &syn::Type) -> TokenStream {
+        let path_ident = self.rs_name_components.to_type_path_token();
+        let feature = self.self_feature.as_ref().map(|f| {
+            let name = &f.name;
+            quote! {
+                #[cfg(feature = #name)]
+            }
+        });
+
+        let try_from = (!self.is_flags_enum).then(|| {
+            let variants = self.constants.iter().map(|f| &f.name);
+
+            quote! {
+                #feature
+                impl TryFrom<#backing_type> for #path_ident {
+                    type Error = #backing_type;
+
+                    /// Fails with the value if no variant has it
+                    fn try_from(value: #backing_type) -> Result<Self, Self::Error> {
+                        match value {
+                            #(v if v == Self::#variants as #backing_type => Ok(Self::#variants),)*
+                            _ => Err(value),
+                        }
+                    }
+                }
+            }
+        });
+
+        quote! {
+            #feature
+            impl From<#path_ident> for #backing_type {
+                fn from(value: #path_ident) -> Self {
+                    value as #backing_type
+                }
+            }
+
+            #try_from
+        }
+    }
+
     fn write_value_type(&self, writer: &mut Writer, config: &RustGenerationConfig) -> Result<()> {
         let generics = self.get_generics(0);
         let generic_names = self.get_generics_unbound(0);
@@ -1051,14 +1605,25 @@ impl RustType {
             }
         });
 
+        let repr = self.repr_attribute();
+        let derives = self.value_type_derives();
+        let size_assert = self.size_assert();
+        let impl_default = self.implement_zeroed_default();
+
+        let deprecated = deprecated_attribute(self.deprecated.as_deref());
+
         let tokens = quote! {
             #feature
-            #[repr(C)]
-            #[derive(Debug, Clone, Default, PartialEq)]
+            #deprecated
+            #repr
+            #derives
             pub struct #name_ident {
                 #(#fields),*
             }
 
+            #size_assert
+
+            #impl_default
 
             #impl_value
 
@@ -1084,7 +1649,94 @@ impl RustType {
         Ok(())
     }
 
+    /// Copying a value type with `Gc` fields would duplicate managed references behind the GC's back,
+    /// so only blittable value types are `Clone`.
+    /// Packed types only get derives that copy their fields instead of referencing them unaligned
+    fn value_type_derives(&self) -> Option<TokenStream> {
+        match (self.is_blittable, self.packing) {
+            (true, None) => Some(quote! { #[derive(Debug, Clone, Copy, PartialEq)] }),
+            (true, Some(_)) => Some(quote! { #[derive(Clone, Copy)] }),
+            (false, None) => Some(quote! { #[derive(Debug, PartialEq)] }),
+            (false, Some(_)) => None,
+        }
+    }
+
+    /// Zeroed `Default` like il2cpp initializes value types,
+    /// derive doesn't work for large arrays or generic fields without a bound.
+    /// Only for blittable value types, a zeroed `Gc` field would be an invalid null handle
+    fn implement_zeroed_default(&self) -> Option<TokenStream> {
+        if !self.is_blittable {
+            return None;
+        }
+
+        let generics = self.get_generics(0);
+        let path_ident = self.rs_name_components.to_type_path_token();
+        let feature = self.self_feature.as_ref().map(|f| {
+            let name = &f.name;
+            quote! {
+                #[cfg(feature = #name)]
+            }
+        });
+
+        Some(quote! {
+            #feature
+            impl #generics Default for #path_ident {
+                fn default() -> Self {
+                    // SAFETY: repr(C) il2cpp value type, all zero is its default value
+                    unsafe { core::mem::zeroed() }
+                }
+            }
+        })
+    }
+
+    /// `#[repr(C)]`, packed like the C++ `#pragma pack` if the type has a packing
+    /// Only used for value types, reference types borrow their parent field
+    fn repr_attribute(&self) -> TokenStream {
+        match self.packing {
+            Some(packing) => {
+                let packing = proc_macro2::Literal::u32_unsuffixed(packing);
+                quote! { #[repr(C, packed(#packing))] }
+            }
+            None => quote! { #[repr(C)] },
+        }
+    }
+
+    /// Compile time check of the struct size against il2cpp's instance size, like the C++ size assert.
+    /// Only for value types, il2cpp doesn't round class instance sizes up to their alignment
+    /// like `#[repr(C)]` does, e.g. a class with a single `bool` field is 0x11
+    fn size_assert(&self) -> Option<TokenStream> {
+        // generic layouts depend on their arguments
+        if !self.is_value_type || self.is_enum_type || self.rs_name_components.generics.is_some() {
+            return None;
+        }
+
+        let size_info = self.size_info.as_ref()?;
+        let size = size_info.instance_size as usize;
+        let alignment = size_info.minimum_alignment.max(1) as usize;
+        if size == 0 || size % alignment != 0 {
+            return None;
+        }
+
+        let path_ident = self.rs_name_components.to_type_path_token();
+        let feature = self.self_feature.as_ref().map(|f| {
+            let name = &f.name;
+            quote! {
+                #[cfg(feature = #name)]
+            }
+        });
+
+        Some(quote! {
+            #feature
+            const _: () = assert!(core::mem::size_of::<#path_ident>() == #size);
+        })
+    }
+
+    /// Generic parameters of an open generic, `None` for closed generics whose generics are arguments
     fn get_generics(&self, skip_amount: usize) -> Option<syn::Generics> {
+        if self.generic_instantiation_args.is_some() {
+            return None;
+        }
+
         self.rs_name_components
             .generics
             .as_ref()
@@ -1102,6 +1754,10 @@ impl RustType {
             })
     }
     fn get_generics_unbound(&self, skip_amount: usize) -> Option<syn::Generics> {
+        if self.generic_instantiation_args.is_some() {
+            return None;
+        }
+
         self.rs_name_components
             .generics
             .as_ref()
@@ -1269,8 +1925,11 @@ impl RustType {
             }
         });
 
+        let deprecated = deprecated_attribute(self.deprecated.as_deref());
+
         let mut tokens = quote! {
             #feature
+            #deprecated
             #[repr(C)]
             #[derive(Debug)]
             pub struct #name_ident {
@@ -1492,3 +2151,224 @@ impl Writer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use brocolib::{
+        global_metadata::{MethodIndex, TypeDefinitionIndex},
+        runtime_metadata::Il2CppTypeEnum,
+    };
+
+    use crate::{data::type_resolver::ResolvedTypeData, generate::cs_members::CsMethodData};
+
+    use super::*;
+
+    /// Empty reference type or interface `Test.{name}`
+    fn rust_type(name: &str, is_interface: bool) -> RustType {
+        RustType {
+            fields: vec![],
+            constants: vec![],
+            methods: vec![],
+            traits: vec![],
+            nested_types: vec![],
+            is_value_type: false,
+            is_enum_type: false,
+            is_reference_type: true,
+            is_interface,
+            is_abstract: is_interface,
+            is_flags_enum: false,
+            is_blittable: false,
+            self_tag: CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(0)),
+            self_feature: None,
+            parent: None,
+            backing_type_enum: None,
+            enum_default: None,
+            cs_name_components: NameComponents {
+                namespace: Some("Test".to_string()),
+                name: name.to_string(),
+                ..Default::default()
+            },
+            rs_name_components: RustNameComponents {
+                name: name.to_string(),
+                ..Default::default()
+            },
+            generic_instantiation_args: None,
+            prefix_comments: vec![],
+            requirements: Default::default(),
+            packing: None,
+            size_info: None,
+            is_compiler_generated: false,
+            deprecated: None,
+        }
+    }
+
+    fn value_type(name: &str, is_blittable: bool, packing: Option<u32>) -> RustType {
+        RustType {
+            is_value_type: true,
+            is_reference_type: false,
+            is_blittable,
+            packing,
+            ..rust_type(name, false)
+        }
+    }
+
+    fn method(name: &str, instance: bool, method_flags: CSMethodFlags) -> CsMethod {
+        CsMethod {
+            name: name.to_string(),
+            method_index: MethodIndex::new(0),
+            return_type: ResolvedType {
+                data: ResolvedTypeData::Primitive(Il2CppTypeEnum::Void),
+                ty: 0,
+            },
+            parameters: vec![],
+            instance,
+            template: None,
+            generic_inst: None,
+            brief: None,
+            declaring_type: CsTypeTag::TypeDefinitionIndex(TypeDefinitionIndex::new(0)),
+            method_data: CsMethodData {
+                estimated_size: None,
+                addrs: None,
+                slot: None,
+            },
+            method_flags,
+            docs: None,
+            nullable_return: false,
+            generic_interface_constraints: vec![],
+            obsolete: None,
+        }
+    }
+
+    /// Body of a method returning `i32` with a single `value` parameter
+    fn method_body(
+        rust_type: &RustType,
+        instance: bool,
+        name: &str,
+        method_generics: &[syn::Type],
+    ) -> String {
+        let param = format_ident!("value");
+        let body = rust_type.make_method_body(
+            instance,
+            &name.to_string(),
+            method_generics,
+            [&param].into_iter(),
+            parse_quote!(i32),
+        );
+
+        quote!(#(#body)*).to_string()
+    }
+
+    #[test]
+    fn interface_instance_method_dispatches_through_interface() {
+        let interface = rust_type("IFoo", true);
+        let body = method_body(&interface, true, "Bar", &[]);
+
+        assert!(body.contains("ObjectType :: as_object_mut (self)"));
+        assert!(body.contains(
+            "invoke_interface (< Self as quest_hook :: libil2cpp :: Type > :: class () , \"Bar\" , (value)"
+        ));
+    }
+
+    #[test]
+    fn generic_interface_method_dispatches_through_interface() {
+        let interface = rust_type("IFoo", true);
+        let body = method_body(&interface, true, "Get", &[parse_quote!(T)]);
+
+        assert!(body.contains(
+            "invoke_interface_generic :: < (T ,) , _ , _ > (< Self as quest_hook :: libil2cpp :: Type > :: class () , \"Get\" , (value)"
+        ));
+    }
+
+    #[test]
+    fn generic_interface_dispatches_through_its_instantiation() {
+        // `IList<T>` implemented explicitly next to a public `Add`,
+        // `Self` is the closed interface so its class picks the interface slot
+        let mut interface = rust_type("IList", true);
+        interface.rs_name_components.generics = Some(vec![RustGeneric {
+            name: "T".to_string(),
+            bounds: vec![],
+        }]);
+        let body = method_body(&interface, true, "Add", &[]);
+
+        assert!(body.contains(
+            "__cordl_object . invoke_interface (< Self as quest_hook :: libil2cpp :: Type > :: class () , \"Add\" , (value)"
+        ));
+        assert!(!body.contains("__cordl_object . invoke (\"Add\""));
+    }
+
+    #[test]
+    fn interface_static_method_invokes_on_class() {
+        let interface = rust_type("IFoo", true);
+        let body = method_body(&interface, false, "Create", &[]);
+
+        assert!(body.contains(
+            "< Self as quest_hook :: libil2cpp :: Type > :: class () . invoke (\"Create\" , (value)"
+        ));
+        assert!(!body.contains("invoke_interface"));
+    }
+
+    #[test]
+    fn class_instance_method_invokes_by_name() {
+        let class = rust_type("Foo", false);
+        let body = method_body(&class, true, "Bar", &[]);
+
+        assert!(body.contains("__cordl_object . invoke (\"Bar\" , (value)"));
+    }
+
+    #[test]
+    fn only_static_abstract_interface_methods_are_skipped() {
+        let interface = rust_type("IFoo", true);
+        let class = rust_type("Foo", false);
+
+        let static_abstract = method(
+            "Create",
+            false,
+            CSMethodFlags::STATIC | CSMethodFlags::ABSTRACT,
+        );
+        let instance_abstract = method("Bar", true, CSMethodFlags::ABSTRACT);
+        let static_method = method("Create", false, CSMethodFlags::STATIC);
+
+        assert!(interface.is_static_abstract(&static_abstract));
+        assert!(!interface.is_static_abstract(&instance_abstract));
+        assert!(!interface.is_static_abstract(&static_method));
+        assert!(!class.is_static_abstract(&static_abstract));
+    }
+
+    fn derives(value_type: &RustType) -> String {
+        value_type
+            .value_type_derives()
+            .map(|derives| derives.to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn blittable_value_type_is_copied_and_zeroed() {
+        let vector = value_type("Vector3", true, None);
+
+        assert_eq!(
+            derives(&vector),
+            "# [derive (Debug , Clone , Copy , PartialEq)]"
+        );
+        let default = vector.implement_zeroed_default().unwrap().to_string();
+        assert!(default.contains("impl Default for Vector3"));
+        assert!(default.contains("core :: mem :: zeroed ()"));
+    }
+
+    #[test]
+    fn packed_blittable_value_type_only_copies() {
+        let packed = value_type("Packed", true, Some(1));
+        assert_eq!(derives(&packed), "# [derive (Clone , Copy)]");
+    }
+
+    #[test]
+    fn value_type_with_references_is_not_cloned_or_zeroed() {
+        // e.g. `KeyValuePair<string, object>`, holding `Gc` fields
+        let pair = value_type("KeyValuePair", false, None);
+
+        assert_eq!(derives(&pair), "# [derive (Debug , PartialEq)]");
+        assert!(pair.implement_zeroed_default().is_none());
+
+        let packed = value_type("Packed", false, Some(1));
+        assert!(packed.value_type_derives().is_none());
+    }
+}
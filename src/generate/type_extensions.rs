@@ -1,7 +1,10 @@
 use core::panic;
 
 use brocolib::{
-    global_metadata::{Il2CppMethodDefinition, Il2CppTypeDefinition, TypeDefinitionIndex},
+    global_metadata::{
+        Il2CppGenericParameter, Il2CppMethodDefinition, Il2CppTypeDefinition, MethodIndex,
+        TypeDefinitionIndex,
+    },
     runtime_metadata::{Il2CppType, Il2CppTypeEnum, TypeData},
     Metadata,
 };
@@ -13,7 +16,10 @@ pub const PARAM_ATTRIBUTE_IN: u16 = 0x0001;
 pub const PARAM_ATTRIBUTE_OUT: u16 = 0x0002;
 pub const PARAM_ATTRIBUTE_OPTIONAL: u16 = 0x0010;
 
+pub const GENERIC_PARAMETER_ATTRIBUTE_DEFAULT_CONSTRUCTOR_CONSTRAINT: u16 = 0x0010;
+
 pub const TYPE_ATTRIBUTE_INTERFACE: u32 = 0x00000020;
+pub const TYPE_ATTRIBUTE_ABSTRACT: u32 = 0x00000080;
 pub const TYPE_ATTRIBUTE_NESTED_PUBLIC: u32 = 0x00000002;
 pub const TYPE_ATTRIBUTE_EXPLICIT_LAYOUT: u32 = 0x00000010;
 pub const TYPE_ATTRIBUTE_SPECIAL_NAME: u32 = 0x00000400;
@@ -21,6 +27,7 @@ pub const TYPE_ATTRIBUTE_SPECIAL_NAME: u32 = 0x00000400;
 pub const FIELD_ATTRIBUTE_PUBLIC: u16 = 0x0006;
 pub const FIELD_ATTRIBUTE_PRIVATE: u16 = 0x0001;
 pub const FIELD_ATTRIBUTE_STATIC: u16 = 0x0010;
+pub const FIELD_ATTRIBUTE_INIT_ONLY: u16 = 0x0020;
 pub const FIELD_ATTRIBUTE_LITERAL: u16 = 0x0040;
 
 pub const METHOD_ATTRIBUTE_PUBLIC: u16 = 0x0006;
@@ -28,6 +35,7 @@ pub const METHOD_ATTRIBUTE_STATIC: u16 = 0x0010;
 pub const METHOD_ATTRIBUTE_FINAL: u16 = 0x0020;
 pub const METHOD_ATTRIBUTE_VIRTUAL: u16 = 0x0040;
 pub const METHOD_ATTRIBUTE_HIDE_BY_SIG: u16 = 0x0080;
+pub const METHOD_ATTRIBUTE_NEW_SLOT: u16 = 0x0100;
 pub const METHOD_ATTRIBUTE_ABSTRACT: u16 = 0x0400;
 pub const METHOD_ATTRIBUTE_SPECIAL_NAME: u16 = 0x0800;
 
@@ -39,6 +47,7 @@ pub trait MethodDefintionExtensions {
     fn is_hidden_sig(&self) -> bool;
     fn is_special_name(&self) -> bool;
     fn is_final_method(&self) -> bool;
+    fn is_override_method(&self) -> bool;
 }
 
 impl MethodDefintionExtensions for Il2CppMethodDefinition {
@@ -58,6 +67,11 @@ impl MethodDefintionExtensions for Il2CppMethodDefinition {
         (self.flags & METHOD_ATTRIBUTE_ABSTRACT) != 0
     }
 
+    /// virtual methods which reuse their base slot
+    fn is_override_method(&self) -> bool {
+        self.is_virtual_method() && (self.flags & METHOD_ATTRIBUTE_NEW_SLOT) == 0
+    }
+
     fn is_hidden_sig(&self) -> bool {
         (self.flags & METHOD_ATTRIBUTE_HIDE_BY_SIG) != 0
     }
@@ -91,10 +105,62 @@ impl ParameterDefinitionExtensions for Il2CppType {
     }
 }
 
+pub trait GenericParameterExtensions {
+    fn has_default_constructor_constraint(&self) -> bool;
+    fn unshadowed_name(&self, metadata: &Metadata) -> String;
+    fn constraint_types(&self, metadata: &Metadata) -> Vec<usize>;
+}
+
+impl GenericParameterExtensions for Il2CppGenericParameter {
+    /// `where T : new()`
+    fn has_default_constructor_constraint(&self) -> bool {
+        (self.flags & GENERIC_PARAMETER_ATTRIBUTE_DEFAULT_CONSTRUCTOR_CONSTRAINT) != 0
+    }
+
+    /// Name to emit for the parameter
+    /// C# allows method generics to shadow their declaring type's (`class C<T> { T Foo<T>() }`),
+    /// C++ and Rust don't, so shadowing method generics are prefixed with `M_`
+    fn unshadowed_name(&self, metadata: &Metadata) -> String {
+        let name = self.name(metadata);
+        let owner = self.owner(metadata);
+        if owner.is_method == 0 {
+            return name.to_string();
+        }
+
+        let method = &metadata.global_metadata.methods[MethodIndex::new(owner.owner_index)];
+        let declaring_td = &metadata.global_metadata.type_definitions[method.declaring_type];
+
+        let shadows = declaring_td.generic_container_index.is_valid()
+            && declaring_td
+                .generic_container(metadata)
+                .generic_parameters(metadata)
+                .iter()
+                .any(|p| p.name(metadata) == name);
+
+        match shadows {
+            true => format!("M_{name}"),
+            false => name.to_string(),
+        }
+    }
+
+    /// Type indices of the `where T : ...` type constraints, classes and interfaces
+    fn constraint_types(&self, metadata: &Metadata) -> Vec<usize> {
+        let start = self.constraints_start as usize;
+        let end = start + self.constraints_count as usize;
+
+        metadata.global_metadata.generic_parameter_constraints[start..end]
+            .iter()
+            .map(|ty| *ty as usize)
+            .collect()
+    }
+}
+
 pub trait TypeExtentions {
     fn is_static(&self) -> bool;
     fn is_constant(&self) -> bool;
+    fn is_init_only(&self) -> bool;
     fn is_byref(&self) -> bool;
+    fn is_plain_data(&self, metadata: &Metadata) -> bool;
 
     fn fill_generic_inst<'a>(
         &'a self,
@@ -113,10 +179,56 @@ impl TypeExtentions for Il2CppType {
         (self.attrs & FIELD_ATTRIBUTE_LITERAL) != 0
     }
 
+    // FIELD_ATTRIBUTE_INIT_ONLY, C# readonly
+    fn is_init_only(&self) -> bool {
+        (self.attrs & FIELD_ATTRIBUTE_INIT_ONLY) != 0
+    }
+
     fn is_byref(&self) -> bool {
         self.byref
     }
 
+    /// Primitives, pointers, enums and value types made only of those,
+    /// i.e. nothing that holds a managed reference
+    fn is_plain_data(&self, metadata: &Metadata) -> bool {
+        if self.byref {
+            return false;
+        }
+
+        match self.ty {
+            Il2CppTypeEnum::Boolean
+            | Il2CppTypeEnum::Char
+            | Il2CppTypeEnum::I1
+            | Il2CppTypeEnum::U1
+            | Il2CppTypeEnum::I2
+            | Il2CppTypeEnum::U2
+            | Il2CppTypeEnum::I4
+            | Il2CppTypeEnum::U4
+            | Il2CppTypeEnum::I8
+            | Il2CppTypeEnum::U8
+            | Il2CppTypeEnum::R4
+            | Il2CppTypeEnum::R8
+            | Il2CppTypeEnum::I
+            | Il2CppTypeEnum::U
+            | Il2CppTypeEnum::Ptr => true,
+            Il2CppTypeEnum::Valuetype => {
+                let TypeData::TypeDefinitionIndex(tdi) = self.data else {
+                    return false;
+                };
+                let td = &metadata.global_metadata.type_definitions[tdi];
+
+                td.is_enum_type()
+                    || td.fields(metadata).iter().all(|f| {
+                        let f_ty = &metadata.runtime_metadata.metadata_registration.types
+                            [f.type_index as usize];
+
+                        f_ty.is_static() || f_ty.is_constant() || f_ty.is_plain_data(metadata)
+                    })
+            }
+            _ => false,
+        }
+    }
+
     /// Returns the actual type for the given generic inst
     /// or drills down and fixes it in generic instantiations
     fn fill_generic_inst<'a>(
@@ -172,6 +284,7 @@ pub trait TypeDefinitionExtensions {
     fn is_special_name(&self) -> bool;
     fn is_compiler_generated(&self, metadata: &Metadata) -> bool;
     fn is_interface(&self) -> bool;
+    fn is_abstract_type(&self) -> bool;
     fn is_explicit_layout(&self) -> bool;
     fn is_assignable_to(&self, other_td: &Il2CppTypeDefinition, metadata: &Metadata) -> bool;
 
@@ -207,6 +320,9 @@ impl TypeDefinitionExtensions for Il2CppTypeDefinition {
     fn is_interface(&self) -> bool {
         self.flags & TYPE_ATTRIBUTE_INTERFACE != 0
     }
+    fn is_abstract_type(&self) -> bool {
+        self.flags & TYPE_ATTRIBUTE_ABSTRACT != 0
+    }
     fn is_explicit_layout(&self) -> bool {
         self.flags & TYPE_ATTRIBUTE_EXPLICIT_LAYOUT != 0
     }
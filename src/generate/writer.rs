@@ -3,13 +3,73 @@ use std::{
     io::{BufWriter, Write},
 };
 
-pub struct Writer {
-    pub stream: BufWriter<File>,
+/// Whitespace written for each indentation level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndentStyle {
+    /// Written as is, without indentation
+    #[default]
+    None,
+    /// Number of spaces per level
+    Spaces(u8),
+    Tabs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Whitespace style of written files, the default writes everything as is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WriterStyle {
+    pub indent: IndentStyle,
+    pub line_ending: LineEnding,
+}
+
+impl WriterStyle {
+    fn indentation(&self, level: u16) -> String {
+        match self.indent {
+            IndentStyle::None => String::new(),
+            IndentStyle::Spaces(width) => " ".repeat(level as usize * width as usize),
+            IndentStyle::Tabs => "\t".repeat(level as usize),
+        }
+    }
+}
+
+pub struct Writer<W: Write = BufWriter<File>> {
+    pub stream: W,
     pub indent: u16,
     pub newline: bool,
+    pub style: WriterStyle,
 }
 
 impl Writer {
+    pub fn new(file: File, style: WriterStyle) -> Self {
+        Writer::from_stream(BufWriter::new(file), style)
+    }
+}
+
+impl<W: Write> Writer<W> {
+    pub fn from_stream(stream: W, style: WriterStyle) -> Self {
+        Writer {
+            stream,
+            indent: 0,
+            newline: true,
+            style,
+        }
+    }
+
     pub fn indent(&mut self) {
         self.indent += 1;
     }
@@ -21,19 +81,46 @@ impl Writer {
     }
 }
 
-impl Write for Writer {
+impl<W: Write> Write for Writer<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        // TODO: One day we will write indented
-        // if self.indent > 0 && self.newline {
-        //     self.stream.write_all("\t".repeat(self.indent.into()).as_bytes())?;
-        // }
-        self.newline = buf.ends_with(b"\n");
-        self.stream.write(buf)
-
-        // let buffer = str::repeat(" ", self.indent.into());
-        // self.stream.write_all(buffer.as_bytes())?;
-        // self.stream.write_all(buf)?;
-        // return Ok(buf.len());
+        if self.style == WriterStyle::default() {
+            // the stream may take only part of `buf`
+            let written = self.stream.write(buf)?;
+            if written > 0 {
+                self.newline = buf[..written].ends_with(b"\n");
+            }
+            return Ok(written);
+        }
+
+        let indentation = self.style.indentation(self.indent);
+        let line_ending = self.style.line_ending.as_str();
+
+        // `writeln!` may split a line over several calls,
+        // so only indent where a previous call ended the line
+        let mut lines = buf.split(|b| *b == b'\n').peekable();
+        while let Some(line) = lines.next() {
+            let ends_line = lines.peek().is_some();
+            let line = match ends_line {
+                true => line.strip_suffix(b"\r").unwrap_or(line),
+                false => line,
+            };
+
+            if !line.is_empty() {
+                if self.newline {
+                    self.stream.write_all(indentation.as_bytes())?;
+                }
+                self.stream.write_all(line)?;
+                self.newline = false;
+            }
+
+            // every segment but the last was terminated by a newline
+            if ends_line {
+                self.stream.write_all(line_ending.as_bytes())?;
+                self.newline = true;
+            }
+        }
+
+        Ok(buf.len())
     }
     fn flush(&mut self) -> std::io::Result<()> {
         self.stream.flush()
@@ -72,3 +159,90 @@ pub trait Sortable {
 //         self.sort_level().partial_cmp(&other.sort_level())
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{IndentStyle, LineEnding, Writer, WriterStyle};
+
+    fn vec_writer(indent: IndentStyle, line_ending: LineEnding) -> Writer<Vec<u8>> {
+        Writer::from_stream(
+            Vec::new(),
+            WriterStyle {
+                indent,
+                line_ending,
+            },
+        )
+    }
+
+    /// A block with a line split over several writes and an empty line
+    fn write_block(writer: &mut Writer<Vec<u8>>) -> String {
+        writeln!(writer, "struct A {{").unwrap();
+        writer.indent();
+        write!(writer, "int").unwrap();
+        writeln!(writer, " a;").unwrap();
+        writeln!(writer).unwrap();
+        writer.dedent();
+        writeln!(writer, "}};").unwrap();
+
+        String::from_utf8(writer.stream.clone()).unwrap()
+    }
+
+    #[test]
+    fn none_writes_as_is() {
+        let mut writer = vec_writer(IndentStyle::None, LineEnding::Lf);
+        assert_eq!(write_block(&mut writer), "struct A {\nint a;\n\n};\n");
+        assert!(writer.newline);
+    }
+
+    #[test]
+    fn spaces_indent_each_line_once() {
+        let mut writer = vec_writer(IndentStyle::Spaces(4), LineEnding::Lf);
+        assert_eq!(write_block(&mut writer), "struct A {\n    int a;\n\n};\n");
+    }
+
+    #[test]
+    fn tabs_indent() {
+        let mut writer = vec_writer(IndentStyle::Tabs, LineEnding::Lf);
+        assert_eq!(write_block(&mut writer), "struct A {\n\tint a;\n\n};\n");
+    }
+
+    #[test]
+    fn line_endings_are_normalized() {
+        let mut writer = vec_writer(IndentStyle::None, LineEnding::CrLf);
+        write!(writer, "a\r\nb\n").unwrap();
+        assert_eq!(writer.stream, b"a\r\nb\r\n");
+
+        let mut writer = vec_writer(IndentStyle::Spaces(2), LineEnding::Lf);
+        write!(writer, "a\r\nb").unwrap();
+        assert_eq!(writer.stream, b"a\nb");
+        assert!(!writer.newline);
+    }
+
+    /// Takes at most one byte per write, like a stream under pressure may
+    struct ByteAtATime(Vec<u8>);
+
+    impl Write for ByteAtATime {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.extend(buf.first());
+            Ok(buf.len().min(1))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn none_tracks_newline_of_written_bytes() {
+        let mut writer = Writer::from_stream(ByteAtATime(vec![]), WriterStyle::default());
+
+        assert_eq!(writer.write(b"a\n").unwrap(), 1);
+        assert!(!writer.newline);
+        assert_eq!(writer.write(b"\n").unwrap(), 1);
+        assert!(writer.newline);
+        // nothing written keeps the last state
+        assert_eq!(writer.write(b"").unwrap(), 0);
+        assert!(writer.newline);
+    }
+}
@@ -36,7 +36,10 @@ impl<R: ReadBytesExt> ReadBytesExtensions for R {
             // Yes we treat UInt32.MaxValue (and Int32.MinValue, see ReadCompressedInt32) specially
             val = u32::MAX;
         } else {
-            panic!("Invalid compressed integer format");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid compressed integer format {read:#x}"),
+            ));
         }
 
         Ok(val)
@@ -52,8 +55,9 @@ impl<R: ReadBytesExt> ReadBytesExtensions for R {
 
         let is_negative: bool = (encoded & 1) != 0;
         encoded >>= 1;
+        // encoded <= i32::MAX here, so this can't overflow
         let result = if is_negative {
-            -((encoded + 1) as i32)
+            -(encoded as i32) - 1
         } else {
             encoded as i32
         };
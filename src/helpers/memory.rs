@@ -0,0 +1,93 @@
+use std::fmt;
+
+/// Peak resident set size of the process in KiB, `VmHWM` of `/proc/self/status`.
+/// `None` where procfs isn't available, e.g. on Windows or macOS
+pub fn peak_rss_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_hwm(&status)
+}
+
+/// `VmHWM:   123456 kB` -> `123456`
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Peak resident memory measured after each generation stage.
+/// The peak never decreases, so a stage raising it is the one holding the most memory
+#[derive(Debug, Clone, Default)]
+pub struct PeakMemory {
+    /// Stage name -> peak RSS in KiB so far, in stage order
+    pub stages: Vec<(&'static str, u64)>,
+}
+
+impl PeakMemory {
+    /// Records the peak so far after `stage`, nothing if it can't be measured
+    pub fn record(&mut self, stage: &'static str) {
+        if let Some(kib) = peak_rss_kib() {
+            self.stages.push((stage, kib));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+impl fmt::Display for PeakMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stage, kib) in &self.stages {
+            writeln!(f, "after {stage}: {:.1} MiB", *kib as f64 / 1024.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_peak_from_status() {
+        let status =
+            "Name:\tcordl\nVmPeak:\t 2048000 kB\nVmHWM:\t  812345 kB\nVmRSS:\t  700000 kB\n";
+        assert_eq!(parse_vm_hwm(status), Some(812345));
+    }
+
+    #[test]
+    fn missing_peak_is_none() {
+        assert_eq!(parse_vm_hwm("Name:\tcordl\nVmRSS:\t  700000 kB\n"), None);
+        assert_eq!(parse_vm_hwm("VmHWM:\t  garbage\n"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn peak_is_measured_on_linux() {
+        let mut peak_memory = PeakMemory::default();
+        peak_memory.record("parse");
+        peak_memory.record("fill");
+
+        let [("parse", parse), ("fill", fill)] = peak_memory.stages[..] else {
+            panic!("expected both stages, got {:?}", peak_memory.stages);
+        };
+        assert!(parse > 0);
+        assert!(fill >= parse);
+    }
+
+    #[test]
+    fn display_lists_stages_in_mib() {
+        let peak_memory = PeakMemory {
+            stages: vec![("parse", 1024), ("write", 1536)],
+        };
+        assert_eq!(
+            peak_memory.to_string(),
+            "after parse: 1.0 MiB\nafter write: 1.5 MiB\n"
+        );
+    }
+}
@@ -1,2 +1,4 @@
 pub mod cursor;
+pub mod memory;
+pub mod progress;
 pub mod sorting;
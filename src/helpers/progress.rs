@@ -0,0 +1,113 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Called with the stage name, types processed so far and the total
+pub type ProgressCallback = dyn Fn(&str, usize, usize) + Sync;
+
+/// Owned `ProgressCallback` that can be stored in a config
+#[derive(Clone)]
+pub struct ProgressReporter(pub Arc<dyn Fn(&str, usize, usize) + Send + Sync>);
+
+impl ProgressReporter {
+    pub fn new(callback: impl Fn(&str, usize, usize) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressReporter")
+    }
+}
+
+/// Counts processed types for a generation stage,
+/// invoking the callback at most once per `interval` and always on the last type
+pub struct Progress<'a> {
+    stage: &'a str,
+    processed: usize,
+    total: usize,
+    interval: Duration,
+    last_report: Option<Instant>,
+    callback: &'a ProgressCallback,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(
+        stage: &'a str,
+        total: usize,
+        interval: Duration,
+        callback: &'a ProgressCallback,
+    ) -> Self {
+        Self {
+            stage,
+            processed: 0,
+            total,
+            interval,
+            last_report: None,
+            callback,
+        }
+    }
+
+    /// Marks one more type as processed
+    pub fn tick(&mut self) {
+        self.processed += 1;
+
+        let now = Instant::now();
+        let is_due = self
+            .last_report
+            .is_none_or(|last| now.duration_since(last) >= self.interval);
+
+        if is_due || self.processed == self.total {
+            self.last_report = Some(now);
+            (self.callback)(self.stage, self.processed, self.total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Runs `total` ticks and returns the processed counts that were reported
+    fn reported(total: usize, interval: Duration) -> Vec<usize> {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let callback = {
+            let reports = reports.clone();
+            move |stage: &str, processed: usize, reported_total: usize| {
+                assert_eq!(stage, "Filling");
+                assert_eq!(reported_total, total);
+                reports.lock().unwrap().push(processed);
+            }
+        };
+
+        let mut progress = Progress::new("Filling", total, interval, &callback);
+        for _ in 0..total {
+            progress.tick();
+        }
+
+        let reports = reports.lock().unwrap();
+        reports.clone()
+    }
+
+    #[test]
+    fn counts_are_monotonic() {
+        assert_eq!(reported(5, Duration::ZERO), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn throttled_ticks_are_dropped() {
+        // only the first tick is due within the hour
+        assert_eq!(reported(100, Duration::from_secs(3600)), [1, 100]);
+    }
+
+    #[test]
+    fn final_tick_always_fires() {
+        assert_eq!(reported(1, Duration::from_secs(3600)), [1]);
+        assert_eq!(reported(2, Duration::from_secs(3600)), [1, 2]);
+    }
+}
@@ -0,0 +1,29 @@
+#![feature(entry_insert)]
+#![feature(let_chains)]
+#![feature(slice_as_chunks)]
+#![feature(read_buf)]
+#![feature(map_try_insert)]
+#![feature(lazy_cell)]
+#![feature(exit_status_error)]
+#![feature(iterator_try_collect)]
+
+// re-exported so the binary and library users name the same metadata types
+#[cfg(feature = "il2cpp_v31")]
+pub extern crate brocolib_il2cpp_v31 as brocolib;
+
+#[cfg(feature = "il2cpp_v29")]
+pub extern crate brocolib_il2cpp_v29 as brocolib;
+
+use byteorder::LittleEndian;
+use include_dir::{include_dir, Dir};
+
+pub mod data;
+pub mod generate;
+// mod handlers;
+pub mod helpers;
+
+pub use generate::generation::{generate, GenerationConfig, GenerationReport, TargetLang};
+
+static INTERNALS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/cordl_internals");
+
+pub type Endian = LittleEndian;
@@ -1,55 +1,16 @@
-#![feature(entry_insert)]
-#![feature(let_chains)]
-#![feature(slice_as_chunks)]
-#![feature(read_buf)]
-#![feature(map_try_insert)]
-#![feature(lazy_cell)]
-#![feature(exit_status_error)]
-#![feature(iterator_try_collect)]
-
-#[cfg(feature = "il2cpp_v31")]
-extern crate brocolib_il2cpp_v31 as brocolib;
-
-#[cfg(feature = "il2cpp_v29")]
-extern crate brocolib_il2cpp_v29 as brocolib;
-
-use brocolib::{global_metadata::TypeDefinitionIndex, runtime_metadata::TypeData};
-use byteorder::LittleEndian;
-use color_eyre::eyre::Context;
-use generate::metadata::CordlMetadata;
-use itertools::Itertools;
+use cordl::brocolib::runtime_metadata::Il2CppTypeEnum;
+use cordl::{GenerationConfig, TargetLang};
 extern crate pretty_env_logger;
 
-use include_dir::{include_dir, Dir};
-use log::{info, trace, warn};
-use rayon::prelude::*;
+use log::{info, warn};
 
 use std::{
-    fs,
     path::{Path, PathBuf},
-    time,
+    time::Duration,
 };
 
 use clap::{Parser, Subcommand};
 
-use crate::generate::{cs_context_collection::TypeContextCollection, cs_type_tag::CsTypeTag};
-mod data;
-mod generate;
-// mod handlers;
-mod helpers;
-
-#[derive(Clone, Copy, Debug, clap::ValueEnum)]
-enum TargetLang {
-    #[cfg(feature = "cpp")]
-    Cpp,
-    #[cfg(feature = "json")]
-    SingleJSON,
-    #[cfg(feature = "json")]
-    MultiJSON,
-    #[cfg(feature = "rust")]
-    Rust,
-}
-
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
@@ -71,10 +32,220 @@ struct Cli {
     #[clap(value_parser)]
     target: TargetLang,
 
+    /// Where to write the output, defaults to `./codegen` for C++, `./codegen-rs` for Rust,
+    /// `./json` for a single JSON file and `./multi_json` for a JSON folder
+    #[clap(short, long, value_parser, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// The root directory generated C++ includes are relative to
+    #[cfg(feature = "cpp")]
+    #[clap(long, value_parser, value_name = "DIR")]
+    include_root: Option<PathBuf>,
+
+    /// Don't generate constructors for value types, keeping them C++ aggregates
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    no_value_type_ctors: bool,
+
+    /// The C++ namespace for types without a namespace
+    #[cfg(feature = "cpp")]
+    #[clap(long, value_parser, value_name = "NAMESPACE")]
+    global_namespace: Option<String>,
+
+    /// Use a hand written C++ type instead of generating one, e.g. `UnityEngine.Vector3=::UnityEngine::Vector3@custom/vector3.hpp`
+    #[cfg(feature = "cpp")]
+    #[clap(long = "type-override", value_parser = parse_type_override, value_name = "CS_NAME=CPP_NAME@INCLUDE")]
+    type_overrides: Vec<(String, cordl::generate::cpp::config::CppTypeOverride)>,
+
+    /// Write a CSV of each type's field offsets and sizes next to its header
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    layout_report: bool,
+
+    /// Re-export a type into an old C++ namespace in `cordl_compat.hpp`, e.g. `UnityEngine.Vector3=OldUnity`
+    #[cfg(feature = "cpp")]
+    #[clap(long = "compat-shim", value_parser = parse_compat_shim, value_name = "CS_NAME=OLD_NAMESPACE")]
+    compat_shims: Vec<(String, String)>,
+
+    /// Generate `_ptr` helpers calling methods directly through their function pointers
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    method_pointers: bool,
+
+    /// Emit pointer typed fields as raw `T*` instead of `cordl_internals::Ptr<T>`
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    raw_field_pointers: bool,
+
+    /// Assert plain data value types are trivially copyable and standard layout
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    trivially_copyable_asserts: bool,
+
+    /// Emit the number of instance fields of each type as `__cordl_field_count`
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    field_count_members: bool,
+
+    /// Indent generated headers by this many spaces per level, unindented by default
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    indent_width: Option<u8>,
+
+    /// Indent generated headers with tabs instead of spaces
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    indent_tabs: bool,
+
+    /// Write generated headers with CRLF line endings
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    crlf: bool,
+
+    /// How types with an explicit packing are laid out
+    #[cfg(feature = "cpp")]
+    #[clap(long, value_enum, default_value_t)]
+    packing_style: cordl::generate::cpp::config::PackingStyle,
+
+    /// Manifest of prebuilt headers to include instead of generating their types, one `CS_NAME CPP_NAME INCLUDE` per line
+    #[cfg(feature = "cpp")]
+    #[clap(long, value_parser, value_name = "FILE")]
+    prebuilt_manifest: Option<PathBuf>,
+
+    /// Write `cordl_manifest.txt` so the generated headers can be used as a prebuilt set
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    write_manifest: bool,
+
+    /// Only forward declare value types instead of writing their definitions
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    no_value_types: bool,
+
+    /// Only forward declare reference types instead of writing their definitions
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    no_reference_types: bool,
+
+    /// Only forward declare enums instead of writing their definitions
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    no_enums: bool,
+
+    /// Only forward declare interfaces instead of writing their definitions
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    no_interfaces: bool,
+
+    /// Skip methods stripped from the binary (estimated size of 0)
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    skip_stripped_methods: bool,
+
+    /// Emit at most this many methods per type, property accessors and operators are always kept
+    #[cfg(feature = "cpp")]
+    #[clap(long, value_name = "COUNT")]
+    max_methods_per_type: Option<usize>,
+
+    /// Write each assembly's types under its own directory,
+    /// types defined with the same name in several assemblies also get an assembly namespace
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    split_assemblies: bool,
+
+    /// Resolve each reference type's class once and reuse it in the generated methods
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    cached_class: bool,
+
+    /// Prefix for the outermost namespace and include directory of every generated type, e.g. `bs_`
+    #[clap(long)]
+    namespace_prefix: Option<String>,
+
+    /// Write all types into a single self contained `cordl_all.hpp`
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    amalgamate: bool,
+
+    /// Keep closed generic instantiations next to their template instead of a separate `.generics.hpp`
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    no_split_generic_instantiations: bool,
+
+    /// clang-format binary to format the headers with, implies --format
+    #[cfg(feature = "cpp")]
+    #[clap(long, value_name = "FILE")]
+    clang_format: Option<PathBuf>,
+
+    /// Only emit fields at their offsets with reference fields as `void*`,
+    /// for reading memory without calling into il2cpp
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    layout_only: bool,
+
+    /// C++ name of an il2cpp primitive instead of the default, e.g. `R4=float`
+    #[cfg(feature = "cpp")]
+    #[clap(long = "primitive-override", value_parser = parse_primitive_override, value_name = "IL2CPP_TYPE=CPP_NAME")]
+    primitive_overrides: Vec<(String, String)>,
+
+    /// Also write an experimental C++20 module interface unit next to every header
+    #[cfg(feature = "cpp")]
+    #[clap(long)]
+    modules: bool,
+
+    /// Skip generating compiler generated types such as closures and iterators,
+    /// references to them are resolved like blacklisted types
+    #[clap(long)]
+    skip_compiler_generated: bool,
+
+    /// Emit placeholders for unsupported metadata instead of aborting
+    #[clap(long)]
+    lenient: bool,
+
+    /// Time filling and writing each type and list the slowest at the end
+    #[clap(long)]
+    type_timings: bool,
+
+    /// Measure peak resident memory after parsing, filling and writing, Linux only
+    #[clap(long)]
+    memory_report: bool,
+
     /// Whether to generate generic method specializations
     #[clap(short, long)]
     gen_generic_methods_specializations: bool,
 
+    /// C# xml documentation to emit comments from
+    #[clap(long, value_parser, value_name = "FILE")]
+    xml_docs: Option<PathBuf>,
+
+    /// Field offsets overriding the ones computed from metadata, one `TYPE FIELD OFFSET` per line
+    #[clap(long, value_parser, value_name = "FILE")]
+    offset_overrides: Option<PathBuf>,
+
+    /// Number of threads for parallel generation, defaults to one per core
+    #[clap(long, value_parser, value_name = "COUNT")]
+    threads: Option<usize>,
+
+    /// Minimum time between progress reports in milliseconds
+    #[clap(long, value_parser, value_name = "MS", default_value_t = 1000)]
+    progress_interval: u64,
+
+    /// Only generate these types (full names, comma separated) and their dependencies
+    #[clap(long, value_parser, value_delimiter = ',', value_name = "TYPES")]
+    types: Vec<String>,
+
+    /// Methods that can return null (full names, comma separated), e.g. `UnityEngine.Component.GetComponent`
+    #[clap(long, value_parser, value_delimiter = ',', value_name = "METHODS")]
+    nullable_returns: Vec<String>,
+
+    /// Default variant of an enum instead of its first one, e.g. `UnityEngine.KeyCode=None`
+    #[clap(long = "enum-default", value_parser = parse_enum_default, value_name = "ENUM=VARIANT")]
+    enum_defaults: Vec<(String, String)>,
+
+    /// Backing type of enums without one in the metadata, when their values fit it, e.g. `I8`
+    #[clap(long, value_parser = parse_enum_backing_type, default_value = "I4", value_name = "IL2CPP_TYPE")]
+    default_enum_backing_type: Il2CppTypeEnum,
+
     #[clap(subcommand)]
     command: Option<Commands>,
 }
@@ -82,9 +253,81 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {}
 
-static INTERNALS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/cordl_internals");
+#[cfg(feature = "cpp")]
+fn parse_type_override(
+    s: &str,
+) -> Result<(String, cordl::generate::cpp::config::CppTypeOverride), String> {
+    let (cs_name, cpp) = s
+        .split_once('=')
+        .ok_or("expected CS_NAME=CPP_NAME@INCLUDE")?;
+    let (cpp_name, include) = cpp.split_once('@').ok_or("expected CPP_NAME@INCLUDE")?;
+
+    Ok((
+        cs_name.to_string(),
+        cordl::generate::cpp::config::CppTypeOverride {
+            cpp_name: cpp_name.to_string(),
+            include: PathBuf::from(include),
+        },
+    ))
+}
+
+#[cfg(feature = "cpp")]
+fn parse_compat_shim(s: &str) -> Result<(String, String), String> {
+    let (cs_name, namespace) = s.split_once('=').ok_or("expected CS_NAME=OLD_NAMESPACE")?;
+
+    Ok((cs_name.to_string(), namespace.to_string()))
+}
+
+#[cfg(feature = "cpp")]
+fn parse_primitive_override(s: &str) -> Result<(String, String), String> {
+    let (il2cpp_type, cpp_name) = s.split_once('=').ok_or("expected IL2CPP_TYPE=CPP_NAME")?;
+
+    let primitives = cordl::generate::cpp::config::CppGenerationConfig::OVERRIDABLE_PRIMITIVES
+        .map(|ty| format!("{ty:?}"));
+    if !primitives.contains(&il2cpp_type.to_string()) {
+        return Err(format!(
+            "unknown primitive {il2cpp_type}, expected one of {}",
+            primitives.join(", ")
+        ));
+    }
 
-pub type Endian = LittleEndian;
+    Ok((il2cpp_type.to_string(), cpp_name.to_string()))
+}
+
+fn parse_enum_default(s: &str) -> Result<(String, String), String> {
+    let (enum_name, variant) = s.split_once('=').ok_or("expected ENUM=VARIANT")?;
+
+    Ok((enum_name.to_string(), variant.to_string()))
+}
+
+fn parse_enum_backing_type(s: &str) -> Result<Il2CppTypeEnum, String> {
+    [
+        Il2CppTypeEnum::I1,
+        Il2CppTypeEnum::U1,
+        Il2CppTypeEnum::I2,
+        Il2CppTypeEnum::U2,
+        Il2CppTypeEnum::I4,
+        Il2CppTypeEnum::U4,
+        Il2CppTypeEnum::I8,
+        Il2CppTypeEnum::U8,
+    ]
+    .into_iter()
+    .find(|ty| format!("{ty:?}") == s)
+    .ok_or_else(|| format!("expected an integer Il2CppTypeEnum variant like I4, got {s}"))
+}
+
+fn default_output(target: TargetLang) -> PathBuf {
+    match target {
+        #[cfg(feature = "cpp")]
+        TargetLang::Cpp => PathBuf::from("./codegen"),
+        #[cfg(feature = "json")]
+        TargetLang::SingleJSON => PathBuf::from("./json"),
+        #[cfg(feature = "json")]
+        TargetLang::MultiJSON => PathBuf::from("./multi_json"),
+        #[cfg(feature = "rust")]
+        TargetLang::Rust => PathBuf::from("./codegen-rs"),
+    }
+}
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
@@ -93,6 +336,7 @@ fn main() -> color_eyre::Result<()> {
         .filter_level(log::LevelFilter::Trace)
         .parse_default_env()
         .init();
+
     if !cli.format {
         info!("Add --format/-f to format with clang-format at end")
     }
@@ -101,341 +345,108 @@ fn main() -> color_eyre::Result<()> {
         "Running on {}",
         Path::new("./").canonicalize().unwrap().display()
     );
-    let global_metadata_data = fs::read(&cli.metadata)
-        .with_context(|| format!("il2cpp metadata not found {}", cli.metadata.display()))?;
-    let elf_data = fs::read(&cli.libil2cpp).with_context(|| {
-        format!(
-            "libil2cpp.so shared object not found {}",
-            cli.metadata.display()
-        )
-    })?;
-    let il2cpp_metadata = brocolib::Metadata::parse(&global_metadata_data, &elf_data)?;
-
-    let get_tdi = |full_name: &str| {
-        let tdi = il2cpp_metadata
-            .global_metadata
-            .type_definitions
-            .as_vec()
-            .iter()
-            .position(|t| t.full_name(&il2cpp_metadata, false) == full_name)
-            .unwrap_or_else(|| panic!("Unable to find TDI for {full_name}"));
-
-        TypeDefinitionIndex::new(tdi as u32)
-    };
 
-    let unity_object_tdi_idx = get_tdi("UnityEngine.Object");
-    let object_tdi_idx = get_tdi("System.Object");
-    let str_tdi_idx = get_tdi("System.String");
-
-    let mut metadata = CordlMetadata {
-        metadata: &il2cpp_metadata,
-        code_registration: &il2cpp_metadata.runtime_metadata.code_registration,
-        metadata_registration: &il2cpp_metadata.runtime_metadata.metadata_registration,
-        method_calculations: Default::default(),
-        parent_to_child_map: Default::default(),
-        child_to_parent_map: Default::default(),
-
-        unity_object_tdi: unity_object_tdi_idx,
-        object_tdi: object_tdi_idx,
-        string_tdi: str_tdi_idx,
-
-        name_to_tdi: Default::default(),
-        blacklisted_types: Default::default(),
-        pointer_size: generate::metadata::PointerSize::Bytes8,
-        // For most il2cpp versions
-        packing_field_offset: 7,
-        size_is_default_offset: 12,
-        specified_packing_field_offset: 13,
-        packing_is_default_offset: 11,
+    let config = GenerationConfig {
+        format: cli.format,
+        remove_verbose_comments: cli.remove_verbose_comments,
+        skip_compiler_generated: cli.skip_compiler_generated,
+        lenient: cli.lenient,
+        type_timings: cli.type_timings,
+        memory_report: cli.memory_report,
+        gen_generic_methods_specializations: cli.gen_generic_methods_specializations,
+        xml_docs: cli.xml_docs,
+        offset_overrides: cli.offset_overrides,
+        progress_interval: Duration::from_millis(cli.progress_interval),
+        threads: cli.threads,
+        types: cli.types,
+        nullable_returns: cli.nullable_returns.into_iter().collect(),
+        enum_defaults: cli.enum_defaults.into_iter().collect(),
+        default_enum_backing_type: cli.default_enum_backing_type,
+        namespace_prefix: cli.namespace_prefix,
+        #[cfg(feature = "cpp")]
+        include_root: cli.include_root,
+        #[cfg(feature = "cpp")]
+        value_type_constructors: !cli.no_value_type_ctors,
+        #[cfg(feature = "cpp")]
+        global_namespace: cli.global_namespace,
+        #[cfg(feature = "cpp")]
+        type_overrides: cli.type_overrides.into_iter().collect(),
+        #[cfg(feature = "cpp")]
+        layout_report: cli.layout_report,
+        #[cfg(feature = "cpp")]
+        compat_shims: cli.compat_shims,
+        #[cfg(feature = "cpp")]
+        method_pointers: cli.method_pointers,
+        #[cfg(feature = "cpp")]
+        raw_field_pointers: cli.raw_field_pointers,
+        #[cfg(feature = "cpp")]
+        trivially_copyable_asserts: cli.trivially_copyable_asserts,
+        #[cfg(feature = "cpp")]
+        field_count_members: cli.field_count_members,
+        #[cfg(feature = "cpp")]
+        writer_style: cordl::generate::writer::WriterStyle {
+            indent: match (cli.indent_tabs, cli.indent_width) {
+                (true, _) => cordl::generate::writer::IndentStyle::Tabs,
+                (false, Some(width)) => cordl::generate::writer::IndentStyle::Spaces(width),
+                (false, None) => cordl::generate::writer::IndentStyle::None,
+            },
+            line_ending: match cli.crlf {
+                true => cordl::generate::writer::LineEnding::CrLf,
+                false => cordl::generate::writer::LineEnding::Lf,
+            },
+        },
+        #[cfg(feature = "cpp")]
+        packing_style: cli.packing_style,
+        #[cfg(feature = "cpp")]
+        prebuilt_manifest: cli.prebuilt_manifest,
+        #[cfg(feature = "cpp")]
+        write_manifest: cli.write_manifest,
+        #[cfg(feature = "cpp")]
+        type_kinds: cordl::generate::cpp::config::TypeKindFilter {
+            value_types: !cli.no_value_types,
+            reference_types: !cli.no_reference_types,
+            enums: !cli.no_enums,
+            interfaces: !cli.no_interfaces,
+        },
+        #[cfg(feature = "cpp")]
+        method_filter: cordl::generate::cpp::config::MethodFilter {
+            skip_stripped: cli.skip_stripped_methods,
+            max_per_type: cli.max_methods_per_type,
+        },
+        #[cfg(feature = "cpp")]
+        split_assemblies: cli.split_assemblies,
+        #[cfg(feature = "cpp")]
+        cached_class: cli.cached_class,
+        #[cfg(feature = "cpp")]
+        amalgamate: cli.amalgamate,
+        #[cfg(feature = "cpp")]
+        split_generic_instantiations: !cli.no_split_generic_instantiations,
+        #[cfg(feature = "cpp")]
+        clang_format: cli.clang_format,
+        #[cfg(feature = "cpp")]
+        layout_only: cli.layout_only,
+        #[cfg(feature = "cpp")]
+        primitive_overrides: cli.primitive_overrides.into_iter().collect(),
+        #[cfg(feature = "cpp")]
+        modules: cli.modules,
+        ..GenerationConfig::new(cli.target)
     };
-    let t = time::Instant::now();
-    info!("Parsing metadata methods");
-    metadata.parse();
-    info!("Finished in {}ms", t.elapsed().as_millis());
-
-    let mut cs_context_collection = TypeContextCollection::new();
-
-    // blacklist types
-    {
-        let mut blacklist_type = |full_name: &str| {
-            let tdi = metadata
-                .metadata
-                .global_metadata
-                .type_definitions
-                .as_vec()
-                .iter()
-                .enumerate()
-                .find(|(_, t)| t.full_name(metadata.metadata, false) == full_name);
-
-            if let Some((tdi, _td)) = tdi {
-                info!("Blacklisted {full_name}");
-
-                metadata
-                    .blacklisted_types
-                    .insert(TypeDefinitionIndex::new(tdi as u32));
-            } else {
-                warn!("Unable to blacklist {full_name}")
-            }
-        };
-
-        blacklist_type("UnityEngine.XR.XRInputSubsystemDescriptor");
-        blacklist_type("UnityEngine.XR.XRMeshSubsystemDescriptor");
-        blacklist_type("UnityEngine.XR.XRDisplaySubsystem");
-        blacklist_type("UIToolkitUtilities.Controls.Table"); // TODO: Make System.Enum work properly
-                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass4_0`1");
-                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass8_0`1");
-                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass7_0`1");
-                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass5_0`1");
-                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__DisplayClass10_0");
-                                                             // blacklist_type("NetworkPacketSerializer`2::<>c__6`1");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass14_0`5");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass10_0`1");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass11_0`2");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass12_0`3");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass13_0`4");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass14_0`5");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass15_0`1");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass16_0`2");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass17_0`3");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass18_0`4");
-                                                             // blacklist_type("RpcHandler`1::<>c__DisplayClass19_0`5");
-
-        // Incorrect offsets / sizes due to il2cpp bug
-        blacklist_type("UnityEngine.InputSystem.InputInteractionContext");
-        blacklist_type("UnityEngine.InputSystem.IInputInteraction");
-        blacklist_type("UnityEngine.InputSystem.LowLevel.ActionEvent");
-        blacklist_type("UnityEngine.InputSystem.Interactions.HoldInteraction");
-        blacklist_type("UnityEngine.InputSystem.Interactions.MultiTapInteraction");
-        blacklist_type("UnityEngine.InputSystem.Interactions.PressInteraction");
-        blacklist_type("UnityEngine.InputSystem.Interactions.TapInteraction");
-        blacklist_type("UnityEngine.InputSystem.Interactions.SlowTapInteraction");
-        blacklist_type("UnityEngine.InputSystem.LowLevel.UseWindowsGamingInputCommand");
-        blacklist_type("UnityEngine.InputSystem.LowLevel.EnableIMECompositionCommand");
-        blacklist_type("UnityEngine.InputSystem.LowLevel.MouseState");
-        blacklist_type("UnityEngine.InputSystem.LowLevel.QueryCanRunInBackground");
-        blacklist_type("UnityEngine.InputSystem.LowLevel.QueryEnabledStateCommand");
-        blacklist_type("UnityEngine.InputSystem.Utilities.InputActionTrace");
-        blacklist_type("UnityEngine.InputSystem.Utilities.InputActionTrace::ActionEventPtr");
-        blacklist_type("UnityEngine.InputSystem.Utilities.InputActionTrace::Enumerator");
-        blacklist_type("System.MonoLimitationAttribute");
-    }
-    {
-        let _blacklist_types = |full_name: &str| {
-            let tdis = metadata
-                .metadata
-                .global_metadata
-                .type_definitions
-                .as_vec()
-                .iter()
-                .enumerate()
-                .filter(|(_, t)| t.full_name(metadata.metadata, false).contains(full_name))
-                .collect_vec();
-
-            match tdis.is_empty() {
-                true => warn!("Unable to blacklist {full_name}"),
-                false => {
-                    for (tdi, td) in tdis {
-                        info!("Blacklisted {}", td.full_name(metadata.metadata, true));
-
-                        metadata
-                            .blacklisted_types
-                            .insert(TypeDefinitionIndex::new(tdi as u32));
-                    }
-                }
-            }
-        };
-        // blacklist_types("<>c__DisplayClass");
-    }
-    {
-        // First, make all the contexts
-        info!("Making types");
-        let type_defs = metadata.metadata.global_metadata.type_definitions.as_vec();
-        let total = type_defs.len();
-        for tdi_u64 in 0..total {
-            let tdi = TypeDefinitionIndex::new(tdi_u64 as u32);
-
-            let ty_def = &metadata.metadata.global_metadata.type_definitions[tdi];
-            let _ty = &metadata.metadata_registration.types[ty_def.byval_type_index as usize];
-
-            // only make the roots
-            if ty_def.declaring_type_index != u32::MAX {
-                continue;
-            }
-
-            trace!(
-                "Making types {:.4}% ({tdi_u64}/{total})",
-                (tdi_u64 as f64 / total as f64 * 100.0)
-            );
-            cs_context_collection.make_from(&metadata, TypeData::TypeDefinitionIndex(tdi), None);
-            cs_context_collection.alias_nested_types_il2cpp(
-                tdi,
-                CsTypeTag::TypeDefinitionIndex(tdi),
-                &metadata,
-            );
-        }
-    }
-    {
-        // First, make all the contexts
-        info!("Making nested types");
-        let type_defs = metadata.metadata.global_metadata.type_definitions.as_vec();
-        let total = type_defs.len();
-        for tdi_u64 in 0..total {
-            let tdi = TypeDefinitionIndex::new(tdi_u64 as u32);
-
-            let ty_def = &metadata.metadata.global_metadata.type_definitions[tdi];
-
-            if ty_def.declaring_type_index == u32::MAX {
-                continue;
-            }
-
-            trace!(
-                "Making nested types {:.4}% ({tdi_u64}/{total})",
-                (tdi_u64 as f64 / total as f64 * 100.0)
-            );
-            cs_context_collection.make_nested_from(&metadata, tdi);
-        }
-    }
+    let output = cli.output.unwrap_or_else(|| default_output(cli.target));
 
-    // {
-    //     let total = metadata.metadata_registration.generic_method_table.len() as f64;
-    //     info!("Making generic type instantiations");
-    //     for (i, generic_class) in metadata
-    //         .metadata_registration
-    //         .generic_method_table
-    //         .iter()
-    //         .enumerate()
-    //     {
-    //         trace!(
-    //             "Making generic type instantiations {:.4}% ({i}/{total})",
-    //             (i as f64 / total * 100.0)
-    //         );
-    //         let method_spec = metadata
-    //             .metadata_registration
-    //             .method_specs
-    //             .get(generic_class.generic_method_index as usize)
-    //             .unwrap();
-
-    //         cpp_context_collection.make_generic_from(method_spec, &mut metadata, &STATIC_CONFIG);
-    //     }
-    // }
-    // {
-    //     let total = metadata.metadata_registration.generic_method_table.len() as f64;
-    //     info!("Filling generic types!");
-    //     for (i, generic_class) in metadata
-    //         .metadata_registration
-    //         .generic_method_table
-    //         .iter()
-    //         .enumerate()
-    //     {
-    //         trace!(
-    //             "Filling generic type instantiations {:.4}% ({i}/{total})",
-    //             (i as f64 / total * 100.0)
-    //         );
-    //         let method_spec = metadata
-    //             .metadata_registration
-    //             .method_specs
-    //             .get(generic_class.generic_method_index as usize)
-    //             .unwrap();
-
-    //         cpp_context_collection.fill_generic_class_inst(
-    //             method_spec,
-    //             &mut metadata,
-    //
-    //         );
-    //     }
-    // }
-
-    if cli.gen_generic_methods_specializations {
-        let total = metadata.metadata_registration.generic_method_table.len() as f64;
-        info!("Filling generic methods!");
-        for (i, generic_class) in metadata
-            .metadata_registration
-            .generic_method_table
-            .iter()
-            .enumerate()
-        {
-            trace!(
-                "Filling generic method instantiations {:.4}% ({i}/{total})",
-                (i as f64 / total * 100.0)
-            );
-            let method_spec = metadata
-                .metadata_registration
-                .method_specs
-                .get(generic_class.generic_method_index as usize)
-                .unwrap();
-
-            cs_context_collection.fill_generic_method_inst(method_spec, &mut metadata);
-        }
+    let report = cordl::generate(&config, &cli.metadata, &cli.libil2cpp, &output)?;
+    info!(
+        "Generated {} types in {} contexts, {} blacklisted",
+        report.type_count, report.context_count, report.blacklisted_count
+    );
+    if !report.summary.is_empty() {
+        warn!("Diagnostics:\n{}", report.summary);
     }
-
-    info!("Registering handlers!");
-    // il2cpp_internals::register_il2cpp_types(&mut metadata)?;
-
-    // TODO: uncomment
-    // unity::register_unity(&mut metadata)?;
-    // object::register_system(&mut metadata)?;
-    // value_type::register_value_type(&mut metadata)?;
-    info!("Handlers registered!");
-
-    {
-        // Fill them now
-        info!("Filling types");
-        let type_defs = metadata.metadata.global_metadata.type_definitions.as_vec();
-        let total = type_defs.len();
-        for tdi_u64 in 0..total {
-            let tdi = TypeDefinitionIndex::new(tdi_u64 as u32);
-
-            trace!(
-                "Filling type {:.4} ({tdi_u64}/{total})",
-                (tdi_u64 as f64 / total as f64 * 100.0)
-            );
-
-            cs_context_collection.fill(CsTypeTag::TypeDefinitionIndex(tdi), &metadata);
-        }
+    if !report.timings.is_empty() {
+        info!("Timings:\n{}", report.timings);
     }
-
-    if cli.remove_verbose_comments {
-        // TODO: uncomment
-        // remove_coments(&mut cpp_context_collection)?;
+    if !report.peak_memory.is_empty() {
+        info!("Peak memory:\n{}", report.peak_memory);
     }
 
-    match cli.target {
-        #[cfg(feature = "cpp")]
-        TargetLang::Cpp => {
-            use generate::cpp;
-
-            cpp::cpp_main::run_cpp(cs_context_collection, &metadata, cli.format)?;
-            Ok(())
-        }
-        #[cfg(feature = "json")]
-        TargetLang::SingleJSON => {
-            use generate::json;
-
-            let json = Path::new("./json");
-            println!("Writing json file {json:?}");
-            json::make_json(&metadata, &cs_context_collection, json)?;
-            Ok(())
-        }
-        #[cfg(feature = "json")]
-        TargetLang::MultiJSON => {
-            use generate::json;
-
-            let json_folder = Path::new("./multi_json");
-
-            println!("Writing json file {json_folder:?}");
-            json::make_json_folder(&metadata, &cs_context_collection, json_folder)?;
-            Ok(())
-        }
-
-        #[cfg(feature = "rust")]
-        TargetLang::Rust => {
-            use generate::rust;
-            rust::rust_main::run_rust(cs_context_collection, &metadata)?;
-
-            Ok(())
-        }
-        _ => color_eyre::Result::<()>::Ok(()),
-    }?;
-
     Ok(())
 }